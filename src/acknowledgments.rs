@@ -0,0 +1,98 @@
+use crate::models::{StartupEntry, StartupSource};
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// A flagged entry the user reviewed and accepted. It stays acknowledged
+/// only while its command is unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Acknowledgment {
+    pub name: String,
+    pub source: StartupSource,
+    pub command: String,
+    pub acknowledged_at: String,
+}
+
+impl Acknowledgment {
+    fn identifies(&self, entry: &StartupEntry) -> bool {
+        self.source == entry.source && self.name.eq_ignore_ascii_case(&entry.name)
+    }
+}
+
+pub struct AcknowledgmentStore {
+    acknowledgments: Vec<Acknowledgment>,
+    store_path: PathBuf,
+}
+
+impl AcknowledgmentStore {
+    pub fn new() -> Result<Self> {
+        let config_dir = dirs::config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get config directory"))?
+            .join("deepboot");
+
+        if !config_dir.exists() {
+            fs::create_dir_all(&config_dir)
+                .context("Failed to create config directory")?;
+        }
+
+        let store_path = config_dir.join("acknowledged.json");
+        let acknowledgments = if store_path.exists() {
+            let content = fs::read_to_string(&store_path)
+                .context("Failed to read acknowledgments")?;
+            serde_json::from_str(&content).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            acknowledgments,
+            store_path,
+        })
+    }
+
+    /// Whether the entry was acknowledged with its current command.
+    pub fn is_acknowledged(&self, entry: &StartupEntry) -> bool {
+        self.acknowledgments
+            .iter()
+            .any(|a| a.identifies(entry) && a.command == entry.command)
+    }
+
+    /// Sets the `acknowledged` marker on every entry.
+    pub fn annotate(&self, entries: &mut [StartupEntry]) {
+        for entry in entries.iter_mut() {
+            entry.acknowledged = self.is_acknowledged(entry);
+        }
+    }
+
+    /// Acknowledges the entry, or withdraws an existing acknowledgment.
+    /// Returns whether the entry is acknowledged afterwards.
+    pub fn toggle(&mut self, entry: &StartupEntry) -> Result<bool> {
+        let acknowledged = if self.is_acknowledged(entry) {
+            self.acknowledgments.retain(|a| !a.identifies(entry));
+            false
+        } else {
+            // Replace any acknowledgment made for an older command
+            self.acknowledgments.retain(|a| !a.identifies(entry));
+            self.acknowledgments.push(Acknowledgment {
+                name: entry.name.clone(),
+                source: entry.source.clone(),
+                command: entry.command.clone(),
+                acknowledged_at: Local::now().to_rfc3339(),
+            });
+            true
+        };
+
+        self.save()?;
+        Ok(acknowledged)
+    }
+
+    fn save(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(&self.acknowledgments)
+            .context("Failed to serialize acknowledgments")?;
+        fs::write(&self.store_path, content)
+            .context("Failed to save acknowledgments")?;
+        Ok(())
+    }
+}
@@ -2,7 +2,19 @@ use crate::models::{Action, StartupEntry};
 use anyhow::Result;
 
 // Action handlers
-pub fn handle_action(entry: &StartupEntry, action: Action) -> Result<()> {
+
+/// Performs the action. In dry-run mode nothing is touched; the operation
+/// that would have run is returned instead so the caller can log it.
+pub fn handle_action(entry: &StartupEntry, action: Action, dry_run: bool) -> Result<Option<String>> {
+    if dry_run {
+        return plan_action(entry, action).map(Some);
+    }
+    let result = apply_action(entry, action);
+    crate::scanner::ScanCache::invalidate();
+    result.map(|_| None)
+}
+
+fn apply_action(entry: &StartupEntry, action: Action) -> Result<()> {
     match action {
         Action::Disable => match entry.source {
             crate::models::StartupSource::TaskScheduler => {
@@ -17,6 +29,9 @@ pub fn handle_action(entry: &StartupEntry, action: Action) -> Result<()> {
             crate::models::StartupSource::Service => {
                 crate::services::ServicesScanner::disable_service(entry)
             }
+            crate::models::StartupSource::StartupFolder => {
+                crate::startup_folder::StartupFolderScanner::disable_entry(entry)
+            }
         },
         Action::Remove => match entry.source {
             crate::models::StartupSource::TaskScheduler => {
@@ -28,14 +43,138 @@ pub fn handle_action(entry: &StartupEntry, action: Action) -> Result<()> {
             | crate::models::StartupSource::RegistryWow6432Node => {
                 crate::registry::RegistryScanner::remove_entry(entry)
             }
+            // Every caller asks the user before dispatching an action
+            crate::models::StartupSource::Service => {
+                crate::services::ServicesScanner::remove_service(entry, true)
+            }
+            crate::models::StartupSource::StartupFolder => {
+                crate::startup_folder::StartupFolderScanner::remove_entry(entry)
+            }
+        },
+        Action::Enable => match entry.source {
+            crate::models::StartupSource::TaskScheduler => {
+                crate::task_scheduler::TaskSchedulerScanner::enable_task(entry)
+            }
+            crate::models::StartupSource::RegistryRun
+            | crate::models::StartupSource::RegistryRunOnce
+            | crate::models::StartupSource::RegistryRunServices
+            | crate::models::StartupSource::RegistryWow6432Node => {
+                crate::registry::RegistryScanner::enable_entry(entry)
+            }
             crate::models::StartupSource::Service => {
-                crate::services::ServicesScanner::remove_service(entry)
+                crate::services::ServicesScanner::enable_service(entry)
+            }
+            crate::models::StartupSource::StartupFolder => {
+                crate::startup_folder::StartupFolderScanner::enable_entry(entry)
             }
         },
-        Action::Enable => {
-            // Enable logic would go here
-            anyhow::bail!("Enable action not yet implemented")
+    }
+}
+
+/// Creates `entry` (name, command, source and scope) as a new startup item,
+/// replacing one of the same name only with `force`. In dry-run mode
+/// nothing is created and the operation is returned instead, as with
+/// `handle_action`.
+pub fn handle_add(entry: &StartupEntry, force: bool, dry_run: bool) -> Result<Option<String>> {
+    use crate::models::StartupSource;
+
+    if dry_run {
+        let replacing = if force { ", replacing any existing one" } else { "" };
+        return Ok(Some(format!(
+            "Add {} entry '{}' for {}{}: {}",
+            entry.source,
+            entry.name,
+            entry.scope.to_string().to_lowercase(),
+            replacing,
+            entry.stored_command()
+        )));
+    }
+
+    let command = entry.stored_command();
+    let result = match entry.source {
+        StartupSource::RegistryRun | StartupSource::RegistryRunOnce => {
+            crate::registry::RegistryScanner::add_entry(&entry.name, command, &entry.source, entry.scope, force)
+        }
+        StartupSource::TaskScheduler => {
+            crate::task_scheduler::TaskSchedulerScanner::add_task(&entry.name, command, entry.scope, force)
+        }
+        StartupSource::StartupFolder => {
+            crate::startup_folder::StartupFolderScanner::add_entry(&entry.name, command, entry.scope, force)
+        }
+        _ => anyhow::bail!("Entries can't be added to {}", entry.source),
+    };
+    crate::scanner::ScanCache::invalidate();
+    result.map(|_| None)
+}
+
+/// Describes the concrete operation `handle_action` would perform for an
+/// entry, resolving registry keys and task paths, without changing anything.
+pub fn plan_action(entry: &StartupEntry, action: Action) -> Result<String> {
+    use crate::models::StartupSource;
+
+    match entry.source {
+        StartupSource::TaskScheduler => {
+            let path = crate::task_scheduler::TaskSchedulerScanner::task_path(entry)?;
+            Ok(match action {
+                Action::Disable => format!("Disable scheduled task {}", path),
+                Action::Enable => format!("Enable scheduled task {}", path),
+                Action::Remove => format!("Delete scheduled task {}", path),
+            })
+        }
+        StartupSource::Service => {
+            let name = crate::services::ServicesScanner::service_name(entry)?;
+            match action {
+                Action::Disable => Ok(format!("sc config {} start= disabled", name)),
+                Action::Enable => Ok(format!(
+                    "sc config {} start= {}",
+                    name,
+                    crate::services::ServicesScanner::auto_start_mode(entry.delayed_start)
+                )),
+                Action::Remove => Ok(format!("sc stop {}, wait until STOPPED, then sc delete {}", name, name)),
+            }
+        }
+        StartupSource::StartupFolder => {
+            let path = crate::startup_folder::StartupFolderScanner::file_path(entry)?;
+            Ok(match action {
+                Action::Disable => format!("Move {} into the Disabled subfolder", path.display()),
+                Action::Enable => format!("Move {} back into the Startup folder", path.display()),
+                Action::Remove => format!("Delete {}", path.display()),
+            })
+        }
+        _ => {
+            let key = crate::registry::RegistryScanner::key_path(entry)?;
+            let approval = crate::registry::RegistryScanner::approval_key_path(entry)?;
+            Ok(match (action, approval) {
+                (Action::Disable, Some(approved)) => {
+                    format!("Set StartupApproved value '{}' in {} to disabled (03 ...)", entry.name, approved)
+                }
+                (Action::Enable, Some(approved)) => format!(
+                    "Set StartupApproved value '{}' in {} to enabled (02 ...), re-creating '{}' in {} if missing",
+                    entry.name, approved, entry.name, key
+                ),
+                (Action::Enable, None) => {
+                    format!("Set registry value '{}' in {} to: {}", entry.name, key, entry.stored_command())
+                }
+                _ => format!("Delete registry value '{}' from {}", entry.name, key),
+            })
         }
     }
 }
 
+/// Returns the first configured pattern found in the entry's name, description
+/// or command, marking it as likely security or backup software that the user
+/// should think twice about disabling.
+pub fn critical_pattern<'a>(entry: &StartupEntry, patterns: &'a [String]) -> Option<&'a str> {
+    let haystack = format!(
+        "{} {} {}",
+        entry.name,
+        entry.description.as_deref().unwrap_or(""),
+        entry.command
+    )
+    .to_lowercase();
+
+    patterns
+        .iter()
+        .find(|pattern| !pattern.is_empty() && haystack.contains(&pattern.to_lowercase()))
+        .map(String::as_str)
+}
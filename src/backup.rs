@@ -1,21 +1,124 @@
-use crate::models::StartupEntry;
+use crate::models::{Action, StartupEntry, StartupSource};
+use crate::registry::RegistryScanner;
+use crate::services::ServicesScanner;
+use crate::startup_folder::StartupFolderScanner;
+use crate::task_scheduler::TaskSchedulerScanner;
 use anyhow::{Context, Result};
 use chrono::Local;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupEntry {
     pub entry: StartupEntry,
     pub original_path: String,
     pub backup_timestamp: String,
+    #[serde(default)]
+    pub task_xml: Option<String>, // Registration XML, kept in action backups to recreate removed tasks
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Backup {
     pub timestamp: String,
     pub entries: Vec<BackupEntry>,
+    #[serde(default = "full_scope")]
+    pub scope: String,
+}
+
+fn full_scope() -> String {
+    "all".to_string()
+}
+
+const ACTION_SCOPE: &str = "action";
+
+/// A backup file as listed in the backups view and by `--list-backups`.
+pub struct BackupListing {
+    pub path: PathBuf,
+    pub timestamp: String,
+    pub scope: String,
+    pub entry_count: Option<usize>, // None when the file can't be read
+}
+
+/// Outcome of restoring a backup; one entry failing doesn't stop the rest.
+#[derive(Debug, Default)]
+pub struct RestoreSummary {
+    pub restored: usize,
+    pub failed: Vec<(String, String)>, // Entry name and reason
+}
+
+impl RestoreSummary {
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Restored {} entries, {} failed", self.restored, self.failed.len());
+        for (name, reason) in &self.failed {
+            summary.push_str(&format!("\n  {}: {}", name, reason));
+        }
+        summary
+    }
+}
+
+/// What restoring one backed-up entry does, decided by its source.
+#[derive(Debug, PartialEq)]
+pub enum RestoreStep {
+    RegistryValue { key_path: String }, // Write the value back under this key
+    Task { path: String },              // Set the task's state, recreating it from saved XML if removed
+    Service,                            // Set the service's start type
+    StartupFile,                        // Move the file into or out of the Disabled subfolder
+}
+
+impl RestoreStep {
+    pub fn for_entry(backup_entry: &BackupEntry) -> Self {
+        let entry = &backup_entry.entry;
+        match entry.source {
+            StartupSource::RegistryRun
+            | StartupSource::RegistryRunOnce
+            | StartupSource::RegistryRunServices
+            | StartupSource::RegistryWow6432Node => RestoreStep::RegistryValue {
+                key_path: backup_entry.original_path.clone(),
+            },
+            StartupSource::TaskScheduler => {
+                let path = backup_entry
+                    .original_path
+                    .strip_prefix("TaskScheduler:")
+                    .unwrap_or(&entry.name);
+                // Older backups only recorded the task name
+                let path = if path.starts_with('\\') {
+                    path.to_string()
+                } else {
+                    format!("\\{}", path)
+                };
+                RestoreStep::Task { path }
+            }
+            StartupSource::Service => RestoreStep::Service,
+            StartupSource::StartupFolder => RestoreStep::StartupFile,
+        }
+    }
+
+    /// The operation restoring `backup_entry` performs, for dry runs.
+    pub fn describe(&self, backup_entry: &BackupEntry) -> Result<String> {
+        let entry = &backup_entry.entry;
+        let state = if entry.enabled { "enabled" } else { "disabled" };
+        match self {
+            RestoreStep::RegistryValue { key_path } => Ok(format!(
+                "Set registry value '{}' in {} to: {} ({})",
+                entry.name,
+                key_path,
+                entry.stored_command(),
+                state
+            )),
+            RestoreStep::Task { path } => Ok(match backup_entry.task_xml {
+                Some(_) => format!(
+                    "Set scheduled task {} to {}, recreating it from the saved XML if removed",
+                    path, state
+                ),
+                None => format!("Set scheduled task {} to {}", path, state),
+            }),
+            RestoreStep::Service | RestoreStep::StartupFile => {
+                let action = if entry.enabled { Action::Enable } else { Action::Disable };
+                crate::actions::plan_action(entry, action)
+            }
+        }
+    }
 }
 
 pub struct BackupManager {
@@ -37,30 +140,156 @@ impl BackupManager {
         Ok(Self { backup_dir })
     }
 
-    pub fn create_backup(&self, entries: &[StartupEntry]) -> Result<PathBuf> {
-        let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-        let backup_file = self.backup_dir.join(format!("backup_{}.json", timestamp));
+    pub fn backup_dir(&self) -> &Path {
+        &self.backup_dir
+    }
 
-        let backup = Backup {
-            timestamp: Local::now().to_rfc3339(),
-            entries: entries
-                .iter()
-                .map(|entry| BackupEntry {
-                    entry: entry.clone(),
-                    original_path: Self::get_entry_path(entry),
-                    backup_timestamp: Local::now().to_rfc3339(),
-                })
-                .collect(),
+    pub fn create_backup(&self, entries: &[StartupEntry], max_backups: usize) -> Result<PathBuf> {
+        self.write_backup(entries, "all", max_backups)
+    }
+
+    /// Backs up the entries a pending disable/remove is about to change, so
+    /// the confirmation prompt can point at the file that undoes it.
+    pub fn create_action_backup(&self, entries: &[StartupEntry], max_backups: usize) -> Result<PathBuf> {
+        self.write_backup(entries, ACTION_SCOPE, max_backups)
+    }
+
+    /// Creates the launch-time backup according to the configured scope:
+    /// `"all"` snapshots every entry, `"enabled"` only enabled entries and
+    /// `"changed"` only entries that are new or differ from the previous backups.
+    /// Returns `None` when a `"changed"` backup would be empty.
+    pub fn create_auto_backup(
+        &self,
+        entries: &[StartupEntry],
+        scope: &str,
+        max_backups: usize,
+    ) -> Result<Option<PathBuf>> {
+        match scope {
+            "enabled" => {
+                let enabled: Vec<StartupEntry> =
+                    entries.iter().filter(|e| e.enabled).cloned().collect();
+                self.write_backup(&enabled, "enabled", max_backups).map(Some)
+            }
+            "changed" => match self.previous_state()? {
+                Some(previous) => {
+                    let changed: Vec<StartupEntry> = entries
+                        .iter()
+                        .filter(|e| !previous.iter().any(|p| Self::same_state(p, e)))
+                        .cloned()
+                        .collect();
+                    if changed.is_empty() {
+                        Ok(None)
+                    } else {
+                        self.write_backup(&changed, "changed", max_backups).map(Some)
+                    }
+                }
+                // Nothing to diff against yet, so start with a full snapshot
+                None => self.write_backup(entries, "all", max_backups).map(Some),
+            },
+            _ => self.write_backup(entries, "all", max_backups).map(Some),
+        }
+    }
+
+    /// Reconstructs the last known entry set by merging incremental backups
+    /// back to the most recent full (or enabled-only) snapshot.
+    fn previous_state(&self) -> Result<Option<Vec<StartupEntry>>> {
+        let mut known = Vec::new();
+        for path in self.list_backups()? {
+            let backup = match self.load_backup(&path) {
+                Ok(backup) => backup,
+                Err(e) => {
+                    log::warn!("Ignoring unreadable backup {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            // Action backups only hold the entries of one action
+            if backup.scope == ACTION_SCOPE {
+                continue;
+            }
+            let is_delta = backup.scope == "changed";
+            known.extend(backup.entries.into_iter().map(|b| b.entry));
+            if !is_delta {
+                return Ok(Some(known));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn same_state(a: &StartupEntry, b: &StartupEntry) -> bool {
+        a.name == b.name && a.source == b.source && a.command == b.command && a.enabled == b.enabled
+    }
+
+    /// Writes a backup, then deletes all but the `max_backups` most recent
+    /// ones; 0 keeps them all.
+    fn write_backup(&self, entries: &[StartupEntry], scope: &str, max_backups: usize) -> Result<PathBuf> {
+        let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+        // Action backups can land in the same second as the launch backup
+        let backup_file = if scope == ACTION_SCOPE {
+            self.backup_dir.join(format!("backup_{}_{}.json", timestamp, ACTION_SCOPE))
+        } else {
+            self.backup_dir.join(format!("backup_{}.json", timestamp))
         };
 
+        let backup = Self::snapshot(entries, scope);
         let content = serde_json::to_string_pretty(&backup)
             .context("Failed to serialize backup")?;
         fs::write(&backup_file, content)
             .context("Failed to write backup file")?;
 
+        // Pruning must not fail the backup
+        if max_backups > 0 {
+            if let Err(e) = self.prune(max_backups) {
+                log::warn!("Failed to prune old backups: {}", e);
+            }
+        }
+
         Ok(backup_file)
     }
 
+    /// Deletes all but the `keep` most recent backups. Returns how many
+    /// were deleted.
+    pub fn prune(&self, keep: usize) -> Result<usize> {
+        let mut removed = 0;
+        for path in self.list_backups()?.into_iter().skip(keep) {
+            self.delete_backup(&path)
+                .with_context(|| format!("Failed to delete backup {:?}", path))?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// The entries in backup form, as written to backup and profile files.
+    pub fn snapshot(entries: &[StartupEntry], scope: &str) -> Backup {
+        Backup {
+            timestamp: Local::now().to_rfc3339(),
+            entries: entries
+                .iter()
+                .map(|entry| {
+                    let mut backup_entry = BackupEntry {
+                        entry: entry.clone(),
+                        original_path: Self::get_entry_path(entry),
+                        backup_timestamp: Local::now().to_rfc3339(),
+                        task_xml: None,
+                    };
+                    // Only worth the COM round trip for the few entries an
+                    // action is about to change
+                    if scope == ACTION_SCOPE && entry.source == StartupSource::TaskScheduler {
+                        match TaskSchedulerScanner::export_task(entry) {
+                            Ok((path, xml)) => {
+                                backup_entry.original_path = format!("TaskScheduler:{}", path);
+                                backup_entry.task_xml = Some(xml);
+                            }
+                            Err(e) => log::warn!("Failed to export task '{}': {}", entry.name, e),
+                        }
+                    }
+                    backup_entry
+                })
+                .collect(),
+            scope: scope.to_string(),
+        }
+    }
+
     pub fn list_backups(&self) -> Result<Vec<PathBuf>> {
         let mut backups: Vec<PathBuf> = fs::read_dir(&self.backup_dir)
             .context("Failed to read backup directory")?
@@ -83,6 +312,40 @@ impl BackupManager {
         Ok(backups)
     }
 
+    /// Every backup with its time, scope and size, most recent first.
+    /// Unreadable files are still listed, with "-" for what can't be read.
+    pub fn listings(&self) -> Result<Vec<BackupListing>> {
+        Ok(self
+            .list_backups()?
+            .into_iter()
+            .map(|path| match self.load_backup(&path) {
+                Ok(backup) => BackupListing {
+                    timestamp: chrono::DateTime::parse_from_rfc3339(&backup.timestamp)
+                        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or(backup.timestamp),
+                    scope: backup.scope,
+                    entry_count: Some(backup.entries.len()),
+                    path,
+                },
+                Err(_) => BackupListing {
+                    timestamp: "-".to_string(),
+                    scope: "-".to_string(),
+                    entry_count: None,
+                    path,
+                },
+            })
+            .collect())
+    }
+
+    /// Finds a backup given as a path, or as a file name in the backup
+    /// directory.
+    pub fn resolve(&self, file: &Path) -> PathBuf {
+        if file.exists() || file.components().count() > 1 {
+            return file.to_path_buf();
+        }
+        self.backup_dir.join(file)
+    }
+
     pub fn load_backup(&self, path: &PathBuf) -> Result<Backup> {
         let content = fs::read_to_string(path)
             .context("Failed to read backup file")?;
@@ -91,20 +354,67 @@ impl BackupManager {
         Ok(backup)
     }
 
-    pub fn restore_backup(&self, backup: &Backup) -> Result<()> {
-        // This would restore entries from backup
-        // Implementation depends on the entry type
-        // For now, we'll just log what would be restored
-        log::info!("Restoring backup from {}", backup.timestamp);
-        log::info!("Entries to restore: {}", backup.entries.len());
-        
-        // TODO: Implement actual restoration logic
-        // This would involve:
-        // 1. For registry entries: Write back to registry
-        // 2. For task scheduler: Recreate tasks
-        // 3. For services: Re-enable services
-        
-        Ok(())
+    /// Re-creates every entry in the backup in the state it was saved in.
+    /// Failures are collected in the summary instead of stopping the restore.
+    pub fn restore_backup(&self, backup: &Backup) -> RestoreSummary {
+        log::info!("Restoring {} entries from backup {}", backup.entries.len(), backup.timestamp);
+
+        let mut summary = RestoreSummary::default();
+        for backup_entry in &backup.entries {
+            match Self::restore_entry(backup_entry) {
+                Ok(()) => summary.restored += 1,
+                Err(e) => {
+                    log::warn!("Failed to restore '{}': {}", backup_entry.entry.name, e);
+                    summary.failed.push((backup_entry.entry.name.clone(), e.to_string()));
+                }
+            }
+        }
+        crate::scanner::ScanCache::invalidate();
+        summary
+    }
+
+    /// What `restore_backup` would do for each entry, by entry name,
+    /// without changing anything.
+    pub fn plan_restore(&self, backup: &Backup) -> Vec<(String, Result<String>)> {
+        backup
+            .entries
+            .iter()
+            .map(|backup_entry| {
+                let operation = RestoreStep::for_entry(backup_entry).describe(backup_entry);
+                (backup_entry.entry.name.clone(), operation)
+            })
+            .collect()
+    }
+
+    fn restore_entry(backup_entry: &BackupEntry) -> Result<()> {
+        let entry = &backup_entry.entry;
+        match RestoreStep::for_entry(backup_entry) {
+            RestoreStep::RegistryValue { key_path } => RegistryScanner::restore_entry(entry, &key_path),
+            RestoreStep::Task { path } => {
+                TaskSchedulerScanner::restore_task(entry, &path, backup_entry.task_xml.as_deref())
+            }
+            RestoreStep::Service => ServicesScanner::restore_start_mode(entry),
+            RestoreStep::StartupFile => StartupFolderScanner::restore_entry(entry),
+        }
+    }
+
+    /// Copies a settings file into the backup directory as `<stem>_<timestamp>.<ext>`.
+    pub fn backup_file(&self, path: &Path) -> Result<PathBuf> {
+        let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "file".to_string());
+        let file_name = match path.extension() {
+            Some(ext) => format!("{}_{}.{}", stem, timestamp, ext.to_string_lossy()),
+            None => format!("{}_{}", stem, timestamp),
+        };
+        let backup_file = self.backup_dir.join(file_name);
+
+        fs::copy(path, &backup_file)
+            .with_context(|| format!("Failed to back up {:?}", path))?;
+
+        Ok(backup_file)
     }
 
     pub fn delete_backup(&self, path: &PathBuf) -> Result<()> {
@@ -115,25 +425,134 @@ impl BackupManager {
 
     fn get_entry_path(entry: &StartupEntry) -> String {
         match entry.source {
-            crate::models::StartupSource::TaskScheduler => {
-                format!("TaskScheduler:{}", entry.name)
-            }
-            crate::models::StartupSource::RegistryRun => {
-                "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Run".to_string()
-            }
-            crate::models::StartupSource::RegistryRunOnce => {
-                "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\RunOnce".to_string()
+            StartupSource::TaskScheduler => match &entry.task {
+                Some(task) => format!("TaskScheduler:{}", task.path),
+                None => format!("TaskScheduler:{}", entry.name),
+            },
+            // The hive matters for restoring, so look up where the value lives
+            StartupSource::RegistryRun
+            | StartupSource::RegistryRunOnce
+            | StartupSource::RegistryRunServices
+            | StartupSource::RegistryWow6432Node => {
+                RegistryScanner::key_path(entry).unwrap_or_default()
             }
-            crate::models::StartupSource::RegistryRunServices => {
-                "HKLM\\Software\\Microsoft\\Windows\\CurrentVersion\\RunServices".to_string()
-            }
-            crate::models::StartupSource::RegistryWow6432Node => {
-                "HKLM\\Software\\Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\Run".to_string()
-            }
-            crate::models::StartupSource::Service => {
+            StartupSource::Service => {
                 entry.description.as_deref().unwrap_or("Unknown Service").to_string()
             }
+            StartupSource::StartupFolder => {
+                entry.description.clone().unwrap_or_else(|| entry.name.clone())
+            }
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup_entry(source: StartupSource, original_path: &str) -> BackupEntry {
+        BackupEntry {
+            entry: StartupEntry::new("Updater".to_string(), "C:\\Tools\\updater.exe".to_string(), source, true),
+            original_path: original_path.to_string(),
+            backup_timestamp: "20240101_120000".to_string(),
+            task_xml: None,
+        }
+    }
+
+    #[test]
+    fn routes_every_source_to_its_restore_step() {
+        for source in [
+            StartupSource::RegistryRun,
+            StartupSource::RegistryRunOnce,
+            StartupSource::RegistryRunServices,
+            StartupSource::RegistryWow6432Node,
+        ] {
+            let key_path = "HKLM\\Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+            assert_eq!(
+                RestoreStep::for_entry(&backup_entry(source, key_path)),
+                RestoreStep::RegistryValue { key_path: key_path.to_string() }
+            );
+        }
+        assert_eq!(
+            RestoreStep::for_entry(&backup_entry(StartupSource::TaskScheduler, "TaskScheduler:\\Vendor\\Updater")),
+            RestoreStep::Task { path: "\\Vendor\\Updater".to_string() }
+        );
+        assert_eq!(
+            RestoreStep::for_entry(&backup_entry(StartupSource::Service, "Service:Updater")),
+            RestoreStep::Service
+        );
+        assert_eq!(
+            RestoreStep::for_entry(&backup_entry(StartupSource::StartupFolder, "C:\\Startup\\Updater.lnk")),
+            RestoreStep::StartupFile
+        );
+    }
+
+    /// A manager over its own directory under the system temp dir, removed on drop.
+    struct TempBackups(BackupManager);
+
+    impl TempBackups {
+        fn new(name: &str) -> Self {
+            let backup_dir = std::env::temp_dir().join(format!("deepboot_{}_{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&backup_dir);
+            fs::create_dir_all(&backup_dir).unwrap();
+            Self(BackupManager { backup_dir })
+        }
+
+        /// Writes an empty backup named as if made `days_ago` days back.
+        fn add_backup(&self, days_ago: i64, suffix: &str) -> PathBuf {
+            let timestamp = (Local::now() - chrono::Duration::days(days_ago)).format("%Y%m%d_%H%M%S");
+            let path = self.0.backup_dir.join(format!("backup_{}{}.json", timestamp, suffix));
+            let content = serde_json::to_string(&BackupManager::snapshot(&[], "all")).unwrap();
+            fs::write(&path, content).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempBackups {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0.backup_dir);
+        }
+    }
+
+    #[test]
+    fn prune_keeps_only_the_newest_backups() {
+        let backups = TempBackups::new("prune_test");
+        let newest = [backups.add_backup(1, "_action"), backups.add_backup(2, ""), backups.add_backup(3, "")];
+        let oldest = [backups.add_backup(10, ""), backups.add_backup(40, "_action")];
+        // Files that aren't backups are never touched
+        let unrelated = backups.0.backup_dir.join("notes.json");
+        fs::write(&unrelated, "{}").unwrap();
+
+        assert_eq!(backups.0.prune(3).unwrap(), oldest.len());
+        assert!(newest.iter().all(|path| path.exists()));
+        assert!(oldest.iter().all(|path| !path.exists()));
+        assert!(unrelated.exists());
+    }
+
+    #[test]
+    fn writing_a_backup_prunes_to_max_backups() {
+        let backups = TempBackups::new("max_backups_test");
+        for days_ago in 1..=4 {
+            backups.add_backup(days_ago, "");
+        }
+
+        let path = backups.0.create_backup(&[], 2).unwrap();
+        let remaining = backups.0.list_backups().unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0], path);
+
+        // 0 keeps every backup
+        backups.0.create_action_backup(&[], 0).unwrap();
+        assert_eq!(backups.0.list_backups().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn task_paths_of_older_backups_get_a_leading_backslash() {
+        // Older backups recorded only the task name
+        assert_eq!(
+            RestoreStep::for_entry(&backup_entry(StartupSource::TaskScheduler, "Updater")),
+            RestoreStep::Task { path: "\\Updater".to_string() }
+        );
+    }
+}
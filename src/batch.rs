@@ -11,26 +11,31 @@ impl BatchProcessor {
         Self { logger }
     }
 
+    /// Applies the action to every entry. With `dry_run` nothing is changed;
+    /// each entry's intended operation is logged instead.
     pub fn process_batch(
         &self,
         entries: &[StartupEntry],
         action: Action,
+        dry_run: bool,
     ) -> BatchResult {
         let mut success_count = 0;
         let mut failed_count = 0;
         let mut errors = Vec::new();
+        let mut succeeded = Vec::new();
 
-        for entry in entries {
-            match handle_action(entry, action) {
-                Ok(_) => {
+        for (index, entry) in entries.iter().enumerate() {
+            match handle_action(entry, action, dry_run) {
+                Ok(operation) => {
                     success_count += 1;
+                    succeeded.push(index);
                     if let Some(ref logger) = self.logger {
-                        let _ = logger.log_action(
-                            &action.to_string(),
-                            &entry.name,
-                            true,
-                            None,
-                        );
+                        let _ = match operation {
+                            Some(operation) => {
+                                logger.log_dry_run(&action.to_string(), &entry.name, &operation)
+                            }
+                            None => logger.log_action(&action.to_string(), &entry.name, true, None),
+                        };
                     }
                 }
                 Err(e) => {
@@ -49,12 +54,15 @@ impl BatchProcessor {
             }
         }
 
-        if let Some(ref logger) = self.logger {
-            let _ = logger.log_batch_action(
-                &action.to_string(),
-                entries.len(),
-                success_count,
-            );
+        // Simulated batches leave only their per-entry records
+        if !dry_run && !entries.is_empty() {
+            if let Some(ref logger) = self.logger {
+                let _ = logger.log_batch_action(
+                    &action.to_string(),
+                    entries.len(),
+                    success_count,
+                );
+            }
         }
 
         BatchResult {
@@ -62,6 +70,8 @@ impl BatchProcessor {
             success: success_count,
             failed: failed_count,
             errors,
+            succeeded,
+            dry_run,
         }
     }
 }
@@ -72,6 +82,8 @@ pub struct BatchResult {
     pub success: usize,
     pub failed: usize,
     pub errors: Vec<String>,
+    pub succeeded: Vec<usize>, // Positions in the processed slice
+    pub dry_run: bool,
 }
 
 impl BatchResult {
@@ -84,7 +96,8 @@ impl BatchResult {
 
     pub fn summary(&self) -> String {
         format!(
-            "Batch operation completed: {} successful, {} failed out of {} total ({:.1}% success rate)",
+            "Batch operation completed{}: {} successful, {} failed out of {} total ({:.1}% success rate)",
+            if self.dry_run { " (dry run, nothing changed)" } else { "" },
             self.success,
             self.failed,
             self.total,
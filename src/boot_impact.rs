@@ -0,0 +1,131 @@
+use crate::models::{StartupEntry, StartupImpact};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where the Diagnostic Policy Service records what each startup process
+/// cost during recent sign-ins (`<SID>_StartupInfo<N>.xml`). Task Manager's
+/// "Startup impact" column is computed from the same files.
+const STARTUP_INFO_SUBPATH: &str = "System32\\WDI\\LogFiles\\StartupInfo";
+
+// Task Manager's thresholds: High above 1 s of CPU or 3 MB of disk I/O,
+// Medium above 300 ms or 300 KB, Low below both
+const HIGH_CPU_US: u64 = 1_000_000;
+const HIGH_DISK_BYTES: u64 = 3 * 1024 * 1024;
+const MEDIUM_CPU_US: u64 = 300_000;
+const MEDIUM_DISK_BYTES: u64 = 300 * 1024;
+
+/// Average cost of one process across the recorded sign-ins.
+#[derive(Debug, Clone, Copy, Default)]
+struct Usage {
+    cpu_us: u64,
+    disk_bytes: u64,
+    samples: u64,
+}
+
+impl Usage {
+    fn impact(&self) -> StartupImpact {
+        let cpu = self.cpu_us / self.samples.max(1);
+        let disk = self.disk_bytes / self.samples.max(1);
+        if cpu > HIGH_CPU_US || disk > HIGH_DISK_BYTES {
+            StartupImpact::High
+        } else if cpu > MEDIUM_CPU_US || disk > MEDIUM_DISK_BYTES {
+            StartupImpact::Medium
+        } else {
+            StartupImpact::Low
+        }
+    }
+}
+
+pub struct BootImpact {
+    by_path: HashMap<String, Usage>, // Lowercase executable path
+}
+
+impl BootImpact {
+    /// Reads every StartupInfo file that is accessible. The folder is
+    /// usually readable only by administrators; without it every entry
+    /// rates Unknown.
+    pub fn load() -> Self {
+        let mut by_path = HashMap::new();
+        for file in Self::startup_info_files() {
+            match fs::read(&file) {
+                Ok(data) => Self::parse(&decode(&data), &mut by_path),
+                Err(e) => log::debug!("Failed to read {:?}: {}", file, e),
+            }
+        }
+        Self { by_path }
+    }
+
+    /// Sets the boot impact of every entry.
+    pub fn annotate(entries: &mut [StartupEntry]) {
+        let data = Self::load();
+        for entry in entries.iter_mut() {
+            entry.impact = Some(data.impact_of(entry));
+        }
+    }
+
+    pub fn impact_of(&self, entry: &StartupEntry) -> StartupImpact {
+        entry
+            .target_path()
+            .and_then(|path| self.by_path.get(&path.to_string_lossy().to_lowercase()))
+            .map(Usage::impact)
+            .unwrap_or(StartupImpact::Unknown)
+    }
+
+    fn startup_info_files() -> Vec<PathBuf> {
+        let Some(windows) = std::env::var_os("SystemRoot") else {
+            return Vec::new();
+        };
+        let Ok(dir) = fs::read_dir(Path::new(&windows).join(STARTUP_INFO_SUBPATH)) else {
+            return Vec::new();
+        };
+        dir.flatten()
+            .map(|e| e.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("xml"))
+            })
+            .collect()
+    }
+
+    /// Adds the `<Process Name="...">` records of one file. Each holds a
+    /// `<CpuUsage>` in microseconds and a `<DiskUsage>` in bytes.
+    fn parse(xml: &str, by_path: &mut HashMap<String, Usage>) {
+        for record in xml.split("<Process ").skip(1) {
+            let Some(name) = attribute(record, "Name") else {
+                continue;
+            };
+            let usage = by_path.entry(name.to_lowercase()).or_default();
+            usage.cpu_us += element(record, "CpuUsage").unwrap_or(0);
+            usage.disk_bytes += element(record, "DiskUsage").unwrap_or(0);
+            usage.samples += 1;
+        }
+    }
+}
+
+/// The files are written as UTF-16 with a byte order mark, but accept UTF-8 too.
+fn decode(data: &[u8]) -> String {
+    match data {
+        [0xFF, 0xFE, rest @ ..] => {
+            let wide: Vec<u16> = rest
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&wide)
+        }
+        _ => String::from_utf8_lossy(data).to_string(),
+    }
+}
+
+fn attribute<'a>(record: &'a str, name: &str) -> Option<&'a str> {
+    let start = record.find(&format!("{}=\"", name))? + name.len() + 2;
+    let len = record[start..].find('"')?;
+    Some(&record[start..start + len])
+}
+
+fn element(record: &str, name: &str) -> Option<u64> {
+    let open = record.find(&format!("<{}", name))?;
+    let start = open + record[open..].find('>')? + 1;
+    let len = record[start..].find('<')?;
+    record[start..start + len].trim().parse().ok()
+}
@@ -0,0 +1,1092 @@
+use crate::actions::{critical_pattern, handle_action, handle_add, plan_action};
+use crate::backup::BackupManager;
+use crate::config::{AppConfig, ConfigManager};
+use crate::batch::{BatchProcessor, BatchResult};
+use crate::diagnostics::Diagnostics;
+use crate::doctor::Doctor;
+use crate::export::Exporter;
+use crate::filter::Filter;
+use crate::logger::ActionLogger;
+use crate::models::{Action, EntryScope, ResetTarget, Severity, StartupEntry, StartupSource};
+use crate::monitor::Monitor;
+use crate::profile::ProfileManager;
+use crate::risk::Risk;
+use crate::scanner::{ScanOptions, ScanSource, ScanUpdate, Scanner};
+use crate::stats::ScanStatistics;
+use crate::whitelist::WhitelistManager;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const USAGE: &str = "Usage:
+  deepboot [--min-severity <level>] [--with-hashes] [--dry-run] [--no-cache] [--quiet]
+                                Scan and open the interactive TUI, optionally
+                                showing only entries flagged at or above
+                                <level> (none, low, medium, high) and adding
+                                executable SHA-256 hashes to exports;
+                                --no-cache scans even if a recent cached
+                                scan exists; --quiet leaves out the banner,
+                                summary and scan progress and never waits
+                                for Enter
+  deepboot --scan --stats-json  Scan and print the statistics (counts by
+                                source, status, scope and risk, health and
+                                footprint) as JSON, then exit
+  deepboot reset config         Back up and restore the default config
+  deepboot reset whitelist      Back up and restore the default whitelist
+  deepboot doctor [--fix]       Check config and whitelist files for problems
+  deepboot --import-whitelist <path>
+                                Merge another whitelist.json (e.g. a team
+                                baseline) into yours, adding the keys you
+                                don't have yet
+  deepboot disable <name> [--source <src>] [--yes]
+                                Disable one entry by name
+  deepboot remove <name> [--source <src>] [--yes]
+                                Remove one entry by name; services are only
+                                removed after confirming, or with --yes
+  deepboot enable <name> [--source <src>] [--yes]
+                                Re-enable one disabled entry by name
+  deepboot --add --name <name> --command <command> --source <add-src>
+         [--scope user|machine] [--force]
+                                Add a startup entry running <command>, for
+                                the current user (default) or all users;
+                                an existing entry of that name is only
+                                replaced with --force
+  deepboot plan <disable|remove|enable> [--source <src>] [--match <text>] [--file <path>]
+                                Print the exact operations an action would
+                                perform on the matching entries, without
+                                changing anything
+  deepboot import <file> [--yes]
+                                Apply the disable decisions of a JSON export
+                                (e.g. from another machine) to this one,
+                                after showing which entries match
+  deepboot --diff <old.json> <new.json> [--diff-json]
+                                Compare two JSON exports and list entries
+                                added, removed, disabled or enabled, and
+                                commands that changed; --diff-json prints
+                                the differences as JSON
+  deepboot --save-profile <name>
+                                Save every entry's enabled/disabled state as
+                                a named profile
+  deepboot --apply-profile <name> [--yes]
+                                Enable and disable entries to match a saved
+                                profile, after showing what would change
+  deepboot --backup-now         Scan and back up every entry
+  deepboot --list-backups       List backups with their time, scope and
+                                number of entries
+  deepboot --restore <file> [--yes]
+                                Restore the entries of a backup (a path or a
+                                file name from --list-backups), after showing
+                                them
+  deepboot watch [--interval <seconds>] [--beep] [--event-log]
+                                Rescan periodically (default: the
+                                watch_interval_seconds setting) and report
+                                entries that are added or enabled, optionally
+                                beeping and writing to the Application event
+                                log; --watch works too
+
+  <src> is one of: task_scheduler, registry, services, startup_folder. Without
+  --source the sources are searched in the configured lookup_order until one
+  matches.
+  <add-src> is one of: registry-run, registry-run-once, task-scheduler,
+  startup-folder.
+  --match selects entries whose name, command or description contains <text>;
+  --file reads entry names from a text file, one per line (# starts a comment).
+  --dry-run also applies to disable, remove, enable, import, --add,
+  --apply-profile and --restore: the operations
+  are printed and logged but nothing is changed.
+  --quiet also hides the scan progress of the commands that scan.
+  Commands that ask for confirmation fail with exit code 1 instead of asking
+  when --quiet is given or stdin is not a terminal; confirm up front with
+  --yes (doctor: --fix).
+
+Exit codes:
+  0  success
+  1  an operation failed (or any other error)
+  2  a source could not be scanned
+  3  an operation failed for lack of administrator rights";
+
+/// Exit codes scripts can rely on besides 0 for success. An error carrying
+/// one, anywhere in its chain, ends the process with it; any other error
+/// exits with `OperationFailed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    OperationFailed = 1,
+    ScanFailed = 2,
+    ElevationRequired = 3,
+}
+
+impl ExitStatus {
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// An error reading `message` that ends the process with this status.
+    pub fn error(self, message: String) -> anyhow::Error {
+        anyhow::Error::new(self).context(message)
+    }
+
+    /// The status an error ends the process with.
+    pub fn of(error: &anyhow::Error) -> Self {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<Self>())
+            .copied()
+            .unwrap_or(ExitStatus::OperationFailed)
+    }
+
+    /// The status for failed operations on `failed` entries: elevation
+    /// required if one of them needs an administrator and we aren't one.
+    fn for_failures<'a>(mut failed: impl Iterator<Item = &'a StartupEntry>) -> Self {
+        if !crate::privileges::is_elevated() && failed.any(|entry| entry.requires_admin()) {
+            ExitStatus::ElevationRequired
+        } else {
+            ExitStatus::OperationFailed
+        }
+    }
+}
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitStatus::OperationFailed => write!(f, "operation failed"),
+            ExitStatus::ScanFailed => write!(f, "scan failed"),
+            ExitStatus::ElevationRequired => write!(f, "run DeepBoot as administrator"),
+        }
+    }
+}
+
+impl std::error::Error for ExitStatus {}
+
+/// Options for the interactive session that override the config for this run.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    pub min_severity: Option<Severity>,
+    pub with_hashes: bool,
+    pub dry_run: bool,
+    pub no_cache: bool,
+    pub quiet: bool,
+}
+
+impl LaunchOptions {
+    /// Removes launch options from `args`, leaving any subcommand behind.
+    pub fn extract(args: &mut Vec<String>) -> Result<Self> {
+        let mut options = Self::default();
+
+        if let Some(pos) = args.iter().position(|a| a == "--min-severity") {
+            let level = args
+                .get(pos + 1)
+                .ok_or_else(|| anyhow::anyhow!("Missing level for '--min-severity'\n\n{}", USAGE))?;
+            options.min_severity = Some(Severity::from_key(level).ok_or_else(|| {
+                anyhow::anyhow!("Unknown severity '{}'\n\n{}", level, USAGE)
+            })?);
+            args.drain(pos..pos + 2);
+        }
+
+        if let Some(pos) = args.iter().position(|a| a == "--with-hashes") {
+            options.with_hashes = true;
+            args.remove(pos);
+        }
+
+        if let Some(pos) = args.iter().position(|a| a == "--dry-run") {
+            options.dry_run = true;
+            args.remove(pos);
+        }
+
+        if let Some(pos) = args.iter().position(|a| a == "--no-cache") {
+            options.no_cache = true;
+            args.remove(pos);
+        }
+
+        if let Some(pos) = args.iter().position(|a| a == "--quiet") {
+            options.quiet = true;
+            args.remove(pos);
+        }
+
+        Ok(options)
+    }
+
+    /// Applies the overrides to the in-memory config without saving them.
+    pub fn apply(&self, config: &mut AppConfig) {
+        if let Some(severity) = self.min_severity {
+            config.min_severity = severity.key().to_string();
+        }
+        if self.with_hashes {
+            config.export_hashes = true;
+        }
+        if self.dry_run {
+            config.dry_run = true;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliCommand {
+    Reset(ResetTarget),
+    Doctor { fix: bool },
+    Apply {
+        action: Action,
+        name: String,
+        source: Option<ScanSource>,
+        yes: bool,
+    },
+    Plan {
+        action: Action,
+        source: Option<ScanSource>,
+        pattern: Option<String>,
+        file: Option<PathBuf>,
+    },
+    Import { file: PathBuf, yes: bool },
+    Watch {
+        interval: Option<u64>,
+        beep: bool,
+        event_log: bool,
+    },
+    SaveProfile { name: String },
+    ApplyProfile { name: String, yes: bool },
+    ImportWhitelist { file: PathBuf },
+    StatsJson,
+    Diff { old: PathBuf, new: PathBuf, json: bool },
+    BackupNow,
+    ListBackups,
+    Restore { file: PathBuf, yes: bool },
+    Add {
+        name: String,
+        command: String,
+        source: StartupSource,
+        scope: EntryScope,
+        force: bool,
+    },
+}
+
+impl CliCommand {
+    /// Parses the command line (without the program name). Returns `None` when
+    /// no subcommand was given and the TUI should be started.
+    pub fn parse(args: &[String]) -> Result<Option<Self>> {
+        let command = match args.first().map(String::as_str) {
+            None => return Ok(None),
+            Some("reset") => match args.get(1).map(String::as_str) {
+                Some("config") => CliCommand::Reset(ResetTarget::Config),
+                Some("whitelist") => CliCommand::Reset(ResetTarget::Whitelist),
+                _ => anyhow::bail!("Expected 'config' or 'whitelist' after 'reset'\n\n{}", USAGE),
+            },
+            Some("doctor") => match args.get(1).map(String::as_str) {
+                None => CliCommand::Doctor { fix: false },
+                Some("--fix") => CliCommand::Doctor { fix: true },
+                Some(other) => anyhow::bail!("Unknown option '{}' for 'doctor'\n\n{}", other, USAGE),
+            },
+            Some(verb @ ("disable" | "remove" | "enable")) => {
+                let action = match verb {
+                    "disable" => Action::Disable,
+                    "remove" => Action::Remove,
+                    _ => Action::Enable,
+                };
+                let name = args
+                    .get(1)
+                    .ok_or_else(|| anyhow::anyhow!("Missing entry name for '{}'\n\n{}", verb, USAGE))?
+                    .clone();
+                let (mut source, mut yes) = (None, false);
+                let mut options = args[2..].iter();
+                while let Some(option) = options.next() {
+                    match option.as_str() {
+                        "--source" => {
+                            let key = options
+                                .next()
+                                .ok_or_else(|| anyhow::anyhow!("Missing value for '--source'\n\n{}", USAGE))?;
+                            source = Some(ScanSource::from_key(key).ok_or_else(|| {
+                                anyhow::anyhow!("Unknown source '{}'\n\n{}", key, USAGE)
+                            })?);
+                        }
+                        "--yes" => yes = true,
+                        _ => anyhow::bail!("Unexpected arguments for '{}'\n\n{}", verb, USAGE),
+                    }
+                }
+                CliCommand::Apply { action, name, source, yes }
+            }
+            Some("plan") => {
+                let action = match args.get(1).map(String::as_str) {
+                    Some("disable") => Action::Disable,
+                    Some("remove") => Action::Remove,
+                    Some("enable") => Action::Enable,
+                    _ => anyhow::bail!("Expected 'disable', 'remove' or 'enable' after 'plan'\n\n{}", USAGE),
+                };
+                let (mut source, mut pattern, mut file) = (None, None, None);
+                let mut options = args[2..].iter();
+                while let Some(option) = options.next() {
+                    let value = options
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("Missing value for '{}'\n\n{}", option, USAGE))?;
+                    match option.as_str() {
+                        "--source" => {
+                            source = Some(ScanSource::from_key(value).ok_or_else(|| {
+                                anyhow::anyhow!("Unknown source '{}'\n\n{}", value, USAGE)
+                            })?)
+                        }
+                        "--match" => pattern = Some(value.clone()),
+                        "--file" => file = Some(PathBuf::from(value)),
+                        _ => anyhow::bail!("Unknown option '{}' for 'plan'\n\n{}", option, USAGE),
+                    }
+                }
+                if source.is_none() && pattern.is_none() && file.is_none() {
+                    anyhow::bail!("'plan' needs at least one of --source, --match or --file\n\n{}", USAGE);
+                }
+                CliCommand::Plan { action, source, pattern, file }
+            }
+            Some("import") => {
+                let file = args
+                    .get(1)
+                    .ok_or_else(|| anyhow::anyhow!("Missing export file for 'import'\n\n{}", USAGE))?;
+                let yes = match args.get(2).map(String::as_str) {
+                    None => false,
+                    Some("--yes") => true,
+                    Some(other) => anyhow::bail!("Unknown option '{}' for 'import'\n\n{}", other, USAGE),
+                };
+                CliCommand::Import { file: PathBuf::from(file), yes }
+            }
+            Some(flag @ ("--save-profile" | "--apply-profile")) => {
+                let name = args
+                    .get(1)
+                    .ok_or_else(|| anyhow::anyhow!("Missing profile name for '{}'\n\n{}", flag, USAGE))?;
+                ProfileManager::validate_name(name)?;
+                let yes = match args.get(2).map(String::as_str) {
+                    None => false,
+                    Some("--yes") if flag == "--apply-profile" => true,
+                    Some(other) => anyhow::bail!("Unknown option '{}' for '{}'\n\n{}", other, flag, USAGE),
+                };
+                if flag == "--save-profile" {
+                    CliCommand::SaveProfile { name: name.clone() }
+                } else {
+                    CliCommand::ApplyProfile { name: name.clone(), yes }
+                }
+            }
+            Some("--scan") if args.get(1).map(String::as_str) == Some("--stats-json") && args.len() == 2 => {
+                CliCommand::StatsJson
+            }
+            Some("--stats-json") if args.len() == 1 => CliCommand::StatsJson,
+            Some("--diff") => {
+                let (Some(old), Some(new)) = (args.get(1), args.get(2)) else {
+                    anyhow::bail!("'--diff' needs an old and a new export file\n\n{}", USAGE);
+                };
+                let json = match args.get(3).map(String::as_str) {
+                    None => false,
+                    Some("--diff-json") => true,
+                    Some(other) => anyhow::bail!("Unknown option '{}' for '--diff'\n\n{}", other, USAGE),
+                };
+                CliCommand::Diff { old: PathBuf::from(old), new: PathBuf::from(new), json }
+            }
+            Some("--backup-now") if args.len() == 1 => CliCommand::BackupNow,
+            Some("--list-backups") if args.len() == 1 => CliCommand::ListBackups,
+            Some("--restore") => {
+                let file = args
+                    .get(1)
+                    .ok_or_else(|| anyhow::anyhow!("Missing backup file for '--restore'\n\n{}", USAGE))?;
+                let yes = match args.get(2).map(String::as_str) {
+                    None => false,
+                    Some("--yes") => true,
+                    Some(other) => anyhow::bail!("Unknown option '{}' for '--restore'\n\n{}", other, USAGE),
+                };
+                CliCommand::Restore { file: PathBuf::from(file), yes }
+            }
+            Some("--add") => {
+                let (mut name, mut command, mut source, mut scope, mut force) =
+                    (None, None, None, EntryScope::User, false);
+                let mut options = args[1..].iter();
+                while let Some(option) = options.next() {
+                    if option == "--force" {
+                        force = true;
+                        continue;
+                    }
+                    let value = options
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("Missing value for '{}'\n\n{}", option, USAGE))?;
+                    match option.as_str() {
+                        "--name" => name = Some(value.clone()),
+                        "--command" => command = Some(value.clone()),
+                        "--source" => {
+                            source = Some(Self::add_source(value).ok_or_else(|| {
+                                anyhow::anyhow!("Entries can't be added to '{}'\n\n{}", value, USAGE)
+                            })?)
+                        }
+                        "--scope" => {
+                            scope = match value.as_str() {
+                                "user" => EntryScope::User,
+                                "machine" => EntryScope::Machine,
+                                _ => anyhow::bail!("Unknown scope '{}'\n\n{}", value, USAGE),
+                            }
+                        }
+                        _ => anyhow::bail!("Unknown option '{}' for '--add'\n\n{}", option, USAGE),
+                    }
+                }
+                let (Some(name), Some(command), Some(source)) = (name, command, source) else {
+                    anyhow::bail!("'--add' needs --name, --command and --source\n\n{}", USAGE);
+                };
+                if name.trim().is_empty() || command.trim().is_empty() {
+                    anyhow::bail!("'--add' needs a non-empty name and command\n\n{}", USAGE);
+                }
+                CliCommand::Add { name, command, source, scope, force }
+            }
+            Some("--import-whitelist") => {
+                let file = args
+                    .get(1)
+                    .ok_or_else(|| anyhow::anyhow!("Missing whitelist file for '--import-whitelist'\n\n{}", USAGE))?;
+                if let Some(other) = args.get(2) {
+                    anyhow::bail!("Unknown option '{}' for '--import-whitelist'\n\n{}", other, USAGE);
+                }
+                CliCommand::ImportWhitelist { file: PathBuf::from(file) }
+            }
+            Some("watch") | Some("--watch") => {
+                let (mut interval, mut beep, mut event_log) = (None, false, false);
+                let mut options = args[1..].iter();
+                while let Some(option) = options.next() {
+                    match option.as_str() {
+                        "--interval" => {
+                            let value = options.next().ok_or_else(|| {
+                                anyhow::anyhow!("Missing seconds for '--interval'\n\n{}", USAGE)
+                            })?;
+                            interval = match value.parse::<u64>() {
+                                Ok(seconds) if seconds > 0 => Some(seconds),
+                                _ => anyhow::bail!("Invalid interval '{}'\n\n{}", value, USAGE),
+                            };
+                        }
+                        "--beep" => beep = true,
+                        "--event-log" => event_log = true,
+                        _ => anyhow::bail!("Unknown option '{}' for 'watch'\n\n{}", option, USAGE),
+                    }
+                }
+                CliCommand::Watch { interval, beep, event_log }
+            }
+            Some("help") | Some("-h") | Some("--help") => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            Some(other) => anyhow::bail!("Unknown command '{}'\n\n{}", other, USAGE),
+        };
+
+        Ok(Some(command))
+    }
+
+    pub fn run(&self, options: &LaunchOptions) -> Result<()> {
+        match self {
+            CliCommand::Reset(target) => Self::run_reset(*target),
+            CliCommand::Doctor { fix } => Self::run_doctor(*fix, options.quiet),
+            CliCommand::Apply { action, name, source, yes } => {
+                Self::run_apply(*action, name, *source, *yes, options)
+            }
+            CliCommand::Plan { action, source, pattern, file } => {
+                Self::run_plan(*action, *source, pattern.as_deref(), file.as_deref(), options)
+            }
+            CliCommand::Import { file, yes } => Self::run_import(file, *yes, options),
+            CliCommand::Watch { interval, beep, event_log } => {
+                Self::run_watch(*interval, *beep, *event_log)
+            }
+            CliCommand::SaveProfile { name } => Self::run_save_profile(name, options),
+            CliCommand::ApplyProfile { name, yes } => Self::run_apply_profile(name, *yes, options),
+            CliCommand::ImportWhitelist { file } => Self::run_import_whitelist(file),
+            CliCommand::StatsJson => Self::run_stats_json(),
+            CliCommand::Diff { old, new, json } => Self::run_diff(old, new, *json),
+            CliCommand::BackupNow => Self::run_backup_now(options),
+            CliCommand::ListBackups => Self::run_list_backups(),
+            CliCommand::Restore { file, yes } => Self::run_restore(file, *yes, options),
+            CliCommand::Add { name, command, source, scope, force } => {
+                Self::run_add(name, command, source, *scope, *force, options)
+            }
+        }
+    }
+
+    /// The sources `--add` can create entries in, by their `--source` key.
+    /// Dashes and underscores are both accepted.
+    fn add_source(key: &str) -> Option<StartupSource> {
+        match key.replace('-', "_").as_str() {
+            "registry_run" => Some(StartupSource::RegistryRun),
+            "registry_run_once" => Some(StartupSource::RegistryRunOnce),
+            "task_scheduler" => Some(StartupSource::TaskScheduler),
+            "startup_folder" => Some(StartupSource::StartupFolder),
+            _ => None,
+        }
+    }
+
+    fn run_add(
+        name: &str,
+        command: &str,
+        source: &StartupSource,
+        scope: EntryScope,
+        force: bool,
+        options: &LaunchOptions,
+    ) -> Result<()> {
+        let mut config_manager = ConfigManager::new()?;
+        options.apply(config_manager.get_mut());
+        let dry_run = config_manager.get().dry_run;
+
+        let entry = StartupEntry::new(name.to_string(), crate::paths::expand_env_vars(command), source.clone(), true)
+            .with_raw_command(command.to_string())
+            .with_scope(scope);
+        let action_logger = ActionLogger::new()?;
+
+        match handle_add(&entry, force, dry_run) {
+            Ok(Some(operation)) => {
+                let _ = action_logger.log_dry_run("Add", &entry.name, &operation);
+                println!("(dry run) Would add '{}' ({}):", entry.name, entry.source);
+                println!("  {}", operation);
+                Ok(())
+            }
+            Ok(None) => {
+                let _ = action_logger.log_action("Add", &entry.name, true, None);
+                println!("Added '{}' ({}, {})", entry.name, entry.source, entry.scope.to_string().to_lowercase());
+                Ok(())
+            }
+            Err(e) => {
+                let _ = action_logger.log_action("Add", &entry.name, false, Some(&e.to_string()));
+                let status = ExitStatus::for_failures(std::iter::once(&entry));
+                Err(e.context(status).context(format!("Failed to add '{}'", entry.name)))
+            }
+        }
+    }
+
+    fn run_backup_now(options: &LaunchOptions) -> Result<()> {
+        let manager = BackupManager::new()?;
+        let max_backups = ConfigManager::new()?.get().max_backups;
+        let (entries, failed) = Self::scan(options.quiet)?;
+        let path = manager.create_backup(&entries, max_backups)?;
+        println!("\nBacked up {} entries to {:?}", entries.len(), path);
+        Self::check_scan(failed)
+    }
+
+    fn run_list_backups() -> Result<()> {
+        let manager = BackupManager::new()?;
+        let listings = manager.listings()?;
+        if listings.is_empty() {
+            println!("No backups in {:?}", manager.backup_dir());
+            return Ok(());
+        }
+
+        println!("Backups in {:?}, most recent first:\n", manager.backup_dir());
+        for listing in &listings {
+            let entries = match listing.entry_count {
+                Some(count) => format!("{} entries", count),
+                None => "unreadable".to_string(),
+            };
+            let file_name = listing.path.file_name().unwrap_or_default().to_string_lossy();
+            println!("{:<19}  {:<8} {:>12}  {}", listing.timestamp, listing.scope, entries, file_name);
+        }
+        Ok(())
+    }
+
+    fn run_restore(file: &Path, yes: bool, options: &LaunchOptions) -> Result<()> {
+        let manager = BackupManager::new()?;
+        let path = manager.resolve(file);
+        let backup = manager
+            .load_backup(&path)
+            .with_context(|| format!("Failed to load backup {:?}", path))?;
+
+        println!(
+            "Backup {:?} ({} scope, saved {}) has {} entries:\n",
+            path,
+            backup.scope,
+            backup.timestamp,
+            backup.entries.len()
+        );
+        for backup_entry in &backup.entries {
+            let entry = &backup_entry.entry;
+            println!(
+                "  {} [{}] restored as {}",
+                entry.name,
+                entry.source,
+                if entry.enabled { "enabled" } else { "disabled" }
+            );
+        }
+        if backup.entries.is_empty() {
+            println!("Nothing to restore.");
+            return Ok(());
+        }
+
+        let mut config_manager = ConfigManager::new()?;
+        options.apply(config_manager.get_mut());
+        if config_manager.get().dry_run {
+            let action_logger = ActionLogger::new()?;
+            println!();
+            for (name, operation) in manager.plan_restore(&backup) {
+                match operation {
+                    Ok(operation) => {
+                        println!("(dry run) {}: {}", name, operation);
+                        let _ = action_logger.log_dry_run("Restore", &name, &operation);
+                    }
+                    Err(e) => eprintln!("(dry run) {}: can't plan the restore: {}", name, e),
+                }
+            }
+            println!("\n(dry run) Nothing is changed.");
+            return Ok(());
+        }
+        if !yes && !Self::confirm("\nRestore these entries? [y/N] ", "--yes", options.quiet)? {
+            println!("No changes made.");
+            return Ok(());
+        }
+
+        let summary = manager.restore_backup(&backup);
+        let _ = ActionLogger::new()?.log_action("Restore", &backup.timestamp, summary.failed.is_empty(), None);
+        println!("{}", summary.summary());
+
+        if summary.failed.is_empty() {
+            return Ok(());
+        }
+        let failed = backup
+            .entries
+            .iter()
+            .map(|backup_entry| &backup_entry.entry)
+            .filter(|entry| summary.failed.iter().any(|(name, _)| *name == entry.name));
+        Err(ExitStatus::for_failures(failed).error(format!(
+            "{} of {} entries could not be restored",
+            summary.failed.len(),
+            backup.entries.len()
+        )))
+    }
+
+    fn run_diff(old: &Path, new: &Path, json: bool) -> Result<()> {
+        let diff = Exporter::diff_exports(&Exporter::import_json(old)?, &Exporter::import_json(new)?);
+
+        if json {
+            let json = serde_json::to_string_pretty(&diff).context("Failed to serialize differences")?;
+            println!("{}", json);
+            return Ok(());
+        }
+
+        println!("Comparing {:?} with {:?}\n", old, new);
+        if diff.is_empty() {
+            println!("No differences.");
+            return Ok(());
+        }
+        for line in diff.lines() {
+            println!("{}", line);
+        }
+        println!("\n{}", diff.summary());
+        Ok(())
+    }
+
+    fn run_stats_json() -> Result<()> {
+        let config = ConfigManager::new()?.get().clone();
+        // Only failures are reported, on stderr, so the output can be piped
+        let (mut entries, failed) = Self::scan(true)?;
+        Diagnostics::annotate(&mut entries);
+        Risk::annotate(&mut entries);
+
+        let stats = ScanStatistics::from_entries(&entries, &config.footprint, &WhitelistManager::new()?);
+        let json = serde_json::to_string_pretty(&stats).context("Failed to serialize statistics")?;
+        println!("{}", json);
+        Self::check_scan(failed)
+    }
+
+    /// Scans every source, printing the progress unless `quiet`. Sources
+    /// that fail are always reported, on stderr, and counted.
+    fn scan(quiet: bool) -> Result<(Vec<StartupEntry>, usize)> {
+        let mut failed = 0;
+        let entries = Scanner::scan_with(&ActionLogger::new()?, None, &Self::scan_options()?, |update| {
+            if let ScanUpdate::Failed(..) = update {
+                failed += 1;
+                update.print();
+            } else if !quiet {
+                update.print();
+            }
+        });
+        Ok((entries, failed))
+    }
+
+    /// Scanner settings from the config file; the launch options don't
+    /// change any of them.
+    fn scan_options() -> Result<ScanOptions> {
+        Ok(ScanOptions::from_config(ConfigManager::new()?.get()))
+    }
+
+    /// Ends a command that otherwise succeeded with `ScanFailed` if some
+    /// sources couldn't be scanned, since its results are incomplete.
+    fn check_scan(failed: usize) -> Result<()> {
+        if failed == 0 {
+            return Ok(());
+        }
+        Err(ExitStatus::ScanFailed.error(format!(
+            "{} source{} could not be scanned",
+            failed,
+            if failed == 1 { "" } else { "s" }
+        )))
+    }
+
+    /// Turns a batch's failures into an error carrying the matching exit
+    /// status.
+    fn check_batch(entries: &[StartupEntry], result: &BatchResult) -> Result<()> {
+        if result.failed == 0 {
+            return Ok(());
+        }
+        let failed = entries
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !result.succeeded.contains(i))
+            .map(|(_, entry)| entry);
+        Err(ExitStatus::for_failures(failed).error(format!("{} of {} operations failed", result.failed, result.total)))
+    }
+
+    fn run_import_whitelist(file: &Path) -> Result<()> {
+        let mut manager = WhitelistManager::new()?;
+        let result = manager.import(file)?;
+        for (category, count) in &result.added {
+            println!("  {:<10} +{}", category.label(), count);
+        }
+        println!("{}", result.summary());
+        println!("Whitelist saved to {:?}", manager.path());
+        Ok(())
+    }
+
+    fn run_save_profile(name: &str, options: &LaunchOptions) -> Result<()> {
+        let manager = ProfileManager::new()?;
+        let (entries, failed) = Self::scan(options.quiet)?;
+        let path = manager.save(name, &entries)?;
+        println!("\nSaved {} entries to profile '{}' ({:?})", entries.len(), name, path);
+        Self::check_scan(failed)
+    }
+
+    fn run_apply_profile(name: &str, yes: bool, options: &LaunchOptions) -> Result<()> {
+        let profile = ProfileManager::new()?.load(name)?;
+        println!("Profile '{}' has {} entries (saved {})", name, profile.entries.len(), profile.timestamp);
+
+        let action_logger = ActionLogger::new()?;
+        let (current, failed) = Self::scan(options.quiet)?;
+        let diff = ProfileManager::diff(&profile, &current);
+
+        println!();
+        for line in diff.lines() {
+            println!("{}", line);
+        }
+        println!("\n{}", diff.summary());
+        if diff.is_empty() {
+            println!("Nothing to change.");
+            return Self::check_scan(failed);
+        }
+
+        let mut config_manager = ConfigManager::new()?;
+        options.apply(config_manager.get_mut());
+        let dry_run = config_manager.get().dry_run;
+
+        if dry_run {
+            println!("\n(dry run) Nothing is changed; the operations would be:");
+            for (entry, action) in diff
+                .to_disable
+                .iter()
+                .map(|e| (e, Action::Disable))
+                .chain(diff.to_enable.iter().map(|e| (e, Action::Enable)))
+            {
+                match plan_action(entry, action) {
+                    Ok(operation) => println!("  {}", operation),
+                    Err(e) => println!("  ! Cannot {} {}: {}", action.to_string().to_lowercase(), entry.name, e),
+                }
+            }
+        } else {
+            if !yes && !Self::confirm("\nApply these changes? [y/N] ", "--yes", options.quiet)? {
+                println!("No changes made.");
+                return Ok(());
+            }
+
+            if config_manager.get().auto_backup {
+                let changed: Vec<_> = diff.to_disable.iter().chain(&diff.to_enable).cloned().collect();
+                let max_backups = config_manager.get().max_backups;
+                match BackupManager::new().and_then(|manager| manager.create_action_backup(&changed, max_backups)) {
+                    Ok(path) => println!("Backup saved to {:?}", path),
+                    Err(e) => eprintln!("Warning: backup failed: {}", e),
+                }
+            }
+        }
+
+        let (disabled, enabled) = ProfileManager::apply(&diff, Some(action_logger), dry_run);
+        for (label, result) in [("Disable", &disabled), ("Enable", &enabled)] {
+            if result.total > 0 {
+                println!("{}: {}", label, result.summary());
+            }
+            for error in &result.errors {
+                eprintln!("  {}", error);
+            }
+        }
+        Self::check_batch(&diff.to_disable, &disabled)?;
+        Self::check_batch(&diff.to_enable, &enabled)?;
+        Self::check_scan(failed)
+    }
+
+    fn run_watch(interval: Option<u64>, beep: bool, event_log: bool) -> Result<()> {
+        let config_manager = ConfigManager::new()?;
+        let config = config_manager.get();
+        let seconds = match interval {
+            Some(seconds) => seconds,
+            None => config.watch_interval_seconds.max(1),
+        };
+        let options = ScanOptions::from_config(config);
+        Monitor::new(Duration::from_secs(seconds), beep, event_log, ActionLogger::new()?, options).run()
+    }
+
+    fn run_reset(target: ResetTarget) -> Result<()> {
+        let backup_manager = BackupManager::new()?;
+        let backup_path = match target {
+            ResetTarget::Config => ConfigManager::new()?.reset(&backup_manager)?,
+            ResetTarget::Whitelist => WhitelistManager::new()?.reset(&backup_manager)?,
+        };
+
+        println!("Reset {} to defaults.", target);
+        if let Some(path) = backup_path {
+            println!("Previous {} saved to: {:?}", target, path);
+        }
+        Ok(())
+    }
+
+    fn run_doctor(fix: bool, quiet: bool) -> Result<()> {
+        let doctor = Doctor::new()?;
+
+        // The stale-whitelist check needs to know what is currently installed
+        println!("Scanning startup entries to check the whitelist...");
+        let action_logger = ActionLogger::new()?;
+        let entries = Scanner::scan_all(&action_logger, None, &Self::scan_options()?);
+        println!();
+
+        let report = doctor.diagnose(&entries)?;
+        Doctor::print_report(&report);
+
+        if report.issues.is_empty() {
+            println!("\nNo problems found.");
+            return Ok(());
+        }
+
+        if !fix
+            && !Self::confirm(
+                &format!("\nFix {} issue(s)? The current files are backed up first. [y/N] ", report.issues.len()),
+                "--fix",
+                quiet,
+            )?
+        {
+            println!("No changes made.");
+            return Ok(());
+        }
+
+        let fixed = doctor.apply(&report, &BackupManager::new()?)?;
+        println!("Repaired {} file(s).", fixed);
+        Ok(())
+    }
+
+    fn run_apply(
+        action: Action,
+        name: &str,
+        source: Option<ScanSource>,
+        yes: bool,
+        options: &LaunchOptions,
+    ) -> Result<()> {
+        let mut config_manager = ConfigManager::new()?;
+        options.apply(config_manager.get_mut());
+        let config = config_manager.get();
+
+        // A known source is scanned alone; otherwise stop at the first match
+        let order = match source {
+            Some(source) => vec![source],
+            None => ScanSource::parse_order(&config.lookup_order),
+        };
+
+        let entry = Scanner::find_entry(name, &order, &ScanOptions::from_config(config))?;
+        let action_logger = ActionLogger::new()?;
+
+        if action == Action::Enable && entry.enabled {
+            println!("'{}' is already enabled", entry.name);
+            return Ok(());
+        }
+
+        let critical = match action {
+            Action::Enable => None,
+            _ => critical_pattern(&entry, &config.critical_patterns),
+        };
+        if let Some(pattern) = critical {
+            eprintln!(
+                "Warning: '{}' looks like security or backup software (matches '{}')",
+                entry.name, pattern
+            );
+        }
+
+        // Services are stopped and deleted for good, so ask first unless --yes
+        if action == Action::Remove
+            && !config.dry_run
+            && !yes
+            && entry.source == StartupSource::Service
+            && !Self::confirm(
+                &format!("Stop and delete service '{}'? This cannot be undone. [y/N] ", entry.name),
+                "--yes",
+                options.quiet,
+            )?
+        {
+            println!("No changes made.");
+            return Ok(());
+        }
+
+        match handle_action(&entry, action, config.dry_run) {
+            Ok(Some(operation)) => {
+                let _ = action_logger.log_dry_run(&action.to_string(), &entry.name, &operation);
+                println!("(dry run) Would {} '{}' ({}):", action.to_string().to_lowercase(), entry.name, entry.source);
+                println!("  {}", operation);
+                Ok(())
+            }
+            Ok(None) => {
+                let _ = action_logger.log_action(&action.to_string(), &entry.name, true, None);
+                println!("{}d '{}' ({})", action, entry.name, entry.source);
+                Ok(())
+            }
+            Err(e) => {
+                let _ = action_logger.log_action(
+                    &action.to_string(),
+                    &entry.name,
+                    false,
+                    Some(&e.to_string()),
+                );
+                let status = ExitStatus::for_failures(std::iter::once(&entry));
+                Err(e.context(status).context(format!("Failed to {} '{}'", action, entry.name)))
+            }
+        }
+    }
+
+    fn run_plan(
+        action: Action,
+        source: Option<ScanSource>,
+        pattern: Option<&str>,
+        file: Option<&Path>,
+        options: &LaunchOptions,
+    ) -> Result<()> {
+        let (mut entries, failed) = match source {
+            Some(source) => (
+                source
+                    .scan(&Self::scan_options()?)
+                    .context(ExitStatus::ScanFailed)
+                    .with_context(|| format!("Failed to scan {}", source))?,
+                0,
+            ),
+            None => Self::scan(options.quiet)?,
+        };
+
+        if let Some(pattern) = pattern {
+            // Plans act on every match, so stick to exact substrings here
+            entries = Filter::new()
+                .with_search(pattern.to_string())
+                .with_fuzzy(false)
+                .apply(&entries);
+        }
+
+        if let Some(file) = file {
+            let content = fs::read_to_string(file)
+                .with_context(|| format!("Failed to read {:?}", file))?;
+            let names: Vec<&str> = content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .collect();
+
+            for name in &names {
+                if !entries.iter().any(|e| e.name.eq_ignore_ascii_case(name)) {
+                    eprintln!("Warning: no matching entry named '{}'", name);
+                }
+            }
+            entries.retain(|e| names.iter().any(|name| e.name.eq_ignore_ascii_case(name)));
+        }
+
+        println!(
+            "\nPlan: {} {} entr{} (nothing is changed)\n",
+            action.to_string().to_lowercase(),
+            entries.len(),
+            if entries.len() == 1 { "y" } else { "ies" }
+        );
+
+        for (i, entry) in entries.iter().enumerate() {
+            println!("{}. {} [{}]", i + 1, entry.name, entry.source);
+            match plan_action(entry, action) {
+                Ok(operation) => println!("   {}", operation),
+                Err(e) => println!("   ! Cannot {}: {}", action.to_string().to_lowercase(), e),
+            }
+        }
+
+        Self::check_scan(failed)
+    }
+
+    fn run_import(file: &Path, yes: bool, options: &LaunchOptions) -> Result<()> {
+        let imported = Exporter::import_json(file)?;
+        println!("Read {} entries from {:?}", imported.len(), file);
+
+        let action_logger = ActionLogger::new()?;
+        let (current, failed) = Self::scan(options.quiet)?;
+        let diff = Exporter::diff_import(&imported, &current);
+
+        println!();
+        for entry in &diff.already_disabled {
+            println!("  = {} [{}] is already disabled", entry.name, entry.source);
+        }
+        for entry in &diff.skipped {
+            println!("  - {} [{}] skipped: not found on this machine", entry.name, entry.source);
+        }
+        for entry in &diff.to_disable {
+            println!("  + {} [{}] will be disabled", entry.name, entry.source);
+        }
+
+        if diff.to_disable.is_empty() {
+            println!("\nNothing to disable.");
+            return Self::check_scan(failed);
+        }
+
+        let mut config_manager = ConfigManager::new()?;
+        options.apply(config_manager.get_mut());
+        let dry_run = config_manager.get().dry_run;
+
+        if dry_run {
+            println!("\n(dry run) Nothing is changed; the operations would be:");
+            for entry in &diff.to_disable {
+                match plan_action(entry, Action::Disable) {
+                    Ok(operation) => println!("  {}", operation),
+                    Err(e) => println!("  ! Cannot disable {}: {}", entry.name, e),
+                }
+            }
+        } else {
+            let prompt = format!(
+                "\nDisable {} entr{}? [y/N] ",
+                diff.to_disable.len(),
+                if diff.to_disable.len() == 1 { "y" } else { "ies" }
+            );
+            if !yes && !Self::confirm(&prompt, "--yes", options.quiet)? {
+                println!("No changes made.");
+                return Ok(());
+            }
+
+            if config_manager.get().auto_backup {
+                let max_backups = config_manager.get().max_backups;
+                match BackupManager::new()
+                    .and_then(|manager| manager.create_action_backup(&diff.to_disable, max_backups))
+                {
+                    Ok(path) => println!("Backup saved to {:?}", path),
+                    Err(e) => eprintln!("Warning: backup failed: {}", e),
+                }
+            }
+        }
+
+        let result = BatchProcessor::new(Some(action_logger)).process_batch(&diff.to_disable, Action::Disable, dry_run);
+        println!("{}", result.summary());
+        for error in &result.errors {
+            eprintln!("  {}", error);
+        }
+        Self::check_batch(&diff.to_disable, &result)?;
+        Self::check_scan(failed)
+    }
+
+    /// Asks a yes/no question on stdin. With `--quiet`, or when stdin is no
+    /// terminal, nobody can answer, so this fails (exit code 1) naming
+    /// `flag`, the option that confirms up front.
+    fn confirm(prompt: &str, flag: &str, quiet: bool) -> Result<bool> {
+        if quiet || !io::stdin().is_terminal() {
+            return Err(ExitStatus::OperationFailed.error(format!(
+                "Confirmation needed ({}), but {}; pass {} to go ahead",
+                prompt.trim().trim_end_matches(" [y/N]"),
+                if quiet { "--quiet never waits for input" } else { "stdin is not a terminal" },
+                flag
+            )));
+        }
+        print!("{}", prompt);
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        Ok(matches!(input.trim(), "y" | "Y" | "yes"))
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use windows::Win32::Foundation::{GlobalFree, HANDLE, HWND};
+use windows::Win32::System::DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData};
+use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+use windows::Win32::System::Ole::CF_UNICODETEXT;
+
+/// Replaces the clipboard contents with `text`.
+pub fn set_text(text: &str) -> Result<()> {
+    let mut wide: Vec<u16> = text.encode_utf16().collect();
+    wide.push(0);
+
+    unsafe {
+        let memory = GlobalAlloc(GMEM_MOVEABLE, wide.len() * std::mem::size_of::<u16>())
+            .context("Failed to allocate clipboard memory")?;
+        let target = GlobalLock(memory) as *mut u16;
+        if target.is_null() {
+            let _ = GlobalFree(memory);
+            anyhow::bail!("Failed to lock clipboard memory");
+        }
+        std::ptr::copy_nonoverlapping(wide.as_ptr(), target, wide.len());
+        let _ = GlobalUnlock(memory);
+
+        if let Err(e) = OpenClipboard(HWND::default()) {
+            let _ = GlobalFree(memory);
+            return Err(e).context("Failed to open the clipboard");
+        }
+        let result = EmptyClipboard().and_then(|_| SetClipboardData(CF_UNICODETEXT.0 as u32, HANDLE(memory.0)));
+        let _ = CloseClipboard();
+
+        // The clipboard owns the memory once SetClipboardData succeeds
+        if let Err(e) = result {
+            let _ = GlobalFree(memory);
+            return Err(e).context("Failed to set clipboard data");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anyhow::{Context, Result};
+use windows::Win32::Foundation::RPC_E_CHANGED_MODE;
+use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED};
+
+/// COM initialized on the current thread until this is dropped. Drop it
+/// after every COM object created while it was held.
+pub struct ComGuard {
+    initialized: bool, // Whether this call's initialization must be undone
+}
+
+impl ComGuard {
+    /// Initializes COM in the multithreaded apartment. A thread that already
+    /// joined another apartment (`RPC_E_CHANGED_MODE`) can still use COM, so
+    /// that is no error, but COM is then left for its owner to tear down.
+    /// `S_FALSE`, already initialized the same way, still takes a reference
+    /// that is released on drop like a first initialization.
+    pub fn init() -> Result<Self> {
+        let hr = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+        if hr == RPC_E_CHANGED_MODE {
+            return Ok(Self { initialized: false });
+        }
+        hr.ok().context("Failed to initialize COM")?;
+        Ok(Self { initialized: true })
+    }
+}
+
+impl Drop for ComGuard {
+    fn drop(&mut self) {
+        if self.initialized {
+            unsafe { CoUninitialize() };
+        }
+    }
+}
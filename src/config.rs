@@ -1,32 +1,482 @@
+use crate::backup::BackupManager;
 use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Format version written to config.json. Bump it with a `migrate` step when
+/// a setting is renamed or changes meaning; new settings need no bump.
+const CONFIG_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    #[serde(default)]
+    pub version: u32, // 0 for files written before versioning
     pub auto_backup: bool,
     pub show_whitelisted: bool,
     pub default_sort: String,
     pub log_level: String,
-    pub auto_export: Option<String>, // "json", "csv", "markdown", or None
+    #[serde(default = "default_log_format")]
+    pub log_format: String, // "text" or "json" (JSON Lines)
+    #[serde(default = "default_log_retention_days")]
+    pub log_retention_days: u32, // 0 keeps logs forever
+    pub auto_export: Option<String>, // "json", "csv", "markdown", "html", or None
+    #[serde(default = "default_backup_scope")]
+    pub backup_scope: String, // "all", "enabled", or "changed"
+    #[serde(default = "default_max_backups")]
+    pub max_backups: usize, // Oldest backups beyond this many are deleted; 0 keeps them all
+    #[serde(default = "default_lookup_order")]
+    pub lookup_order: Vec<String>, // Source order for single-entry CLI lookups
+    #[serde(default)]
+    pub resume_interrupted_scan: bool,
+    #[serde(default = "default_resume_max_age_minutes")]
+    pub resume_max_age_minutes: u64,
+    #[serde(default)]
+    pub footprint: FootprintWeights,
+    #[serde(default = "default_critical_patterns")]
+    pub critical_patterns: Vec<String>, // Name patterns that warn before disabling
+    #[serde(default = "default_min_severity")]
+    pub min_severity: String, // "none", "low", "medium", or "high"
+    #[serde(default)]
+    pub export_hashes: bool, // Add the SHA-256 of each executable to exports
+    #[serde(default = "default_merge_duplicates")]
+    pub merge_duplicates: bool, // Show one row per executable across sources
+    #[serde(default = "default_confirm_threshold")]
+    pub confirm_threshold: usize, // Batch removals above this many entries need the count typed
+    #[serde(default)]
+    pub dry_run: bool, // Log what actions would do instead of doing it
+    #[serde(default)]
+    pub scan_other_users: bool, // Also read Run keys of other accounts' loaded hives (needs admin)
+    #[serde(default)]
+    pub wmi_cross_check: bool, // Compare the scan with WMI's Win32_StartupCommand and add what it missed
+    #[serde(default)]
+    pub include_microsoft_tasks: bool, // Also list tasks under the scheduler's \Microsoft\ folder
+    #[serde(default)]
+    pub scan_cache_seconds: u64, // Reuse the last scan on launch while younger than this; 0 is off
+    #[serde(default)]
+    pub virustotal_api_key: Option<String>, // Enables VirusTotal lookups of the selected entry
+    #[serde(default = "default_watch_interval_seconds")]
+    pub watch_interval_seconds: u64, // Time between scans in watch mode
+    #[serde(default)]
+    pub pause_before_tui: bool, // Wait for Enter after the scan output before opening the TUI
+    #[serde(default)]
+    pub quick_toggle: bool, // The toggle key enables/disables without asking for 'y'
+    #[serde(default = "default_language")]
+    pub language: String, // TUI language: "auto" (the Windows locale), "en" or "de"
+    #[serde(default = "default_theme")]
+    pub theme: String, // TUI colors: "default", "light", "high-contrast" or "mono"
+    #[serde(flatten)]
+    pub unknown: Map<String, Value>, // Settings this version doesn't know, kept so saving doesn't drop them
+}
+
+/// Weights for the startup footprint score shown in the stats view:
+/// `score = entry * enabled entries + impact * estimated boot impact + flagged * flagged entries`.
+/// A score below `fair_threshold` rates Good, below `heavy_threshold` Fair, otherwise Heavy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FootprintWeights {
+    pub entry: f64,
+    pub impact: f64,
+    pub flagged: f64,
+    pub fair_threshold: f64,
+    pub heavy_threshold: f64,
+}
+
+impl Default for FootprintWeights {
+    fn default() -> Self {
+        Self {
+            entry: 1.0,
+            impact: 2.0,
+            flagged: 5.0,
+            fair_threshold: 40.0,
+            heavy_threshold: 80.0,
+        }
+    }
+}
+
+/// Accepted values for the string-typed settings, used to validate hand-edited files.
+pub fn allowed_values(field: &str) -> Option<&'static [&'static str]> {
+    match field {
+        "default_sort" => Some(&["name", "source", "status", "command", "signature", "impact", "risk"]),
+        "log_level" => Some(&["off", "error", "warn", "info", "debug", "trace"]),
+        "log_format" => Some(&["text", "json"]),
+        "auto_export" => Some(&["json", "csv", "markdown", "html"]),
+        "backup_scope" => Some(&["all", "enabled", "changed"]),
+        "min_severity" => Some(&["none", "low", "medium", "high"]),
+        "language" => Some(&["auto", "en", "de"]),
+        "theme" => Some(&["default", "light", "high-contrast", "mono"]),
+        _ => None,
+    }
+}
+
+/// On/off settings offered in the TUI's settings view.
+const BOOL_SETTINGS: &[&str] = &[
+    "auto_backup",
+    "show_whitelisted",
+    "export_hashes",
+    "merge_duplicates",
+    "dry_run",
+    "scan_other_users",
+    "wmi_cross_check",
+    "include_microsoft_tasks",
+    "resume_interrupted_scan",
+    "pause_before_tui",
+    "quick_toggle",
+];
+
+/// Settings the TUI can change, in display order. Numbers, lists and the
+/// footprint weights are still edited in config.json.
+pub const EDITABLE_SETTINGS: &[&str] = &[
+    "auto_backup",
+    "show_whitelisted",
+    "default_sort",
+    "min_severity",
+    "merge_duplicates",
+    "dry_run",
+    "export_hashes",
+    "auto_export",
+    "backup_scope",
+    "log_level",
+    "log_format",
+    "scan_other_users",
+    "wmi_cross_check",
+    "include_microsoft_tasks",
+    "resume_interrupted_scan",
+    "pause_before_tui",
+    "quick_toggle",
+    "language",
+    "theme",
+];
+
+/// Values an editable setting steps through, in order.
+fn setting_choices(key: &str) -> Option<Vec<&'static str>> {
+    if BOOL_SETTINGS.contains(&key) {
+        return Some(vec!["true", "false"]);
+    }
+    let allowed = allowed_values(key)?;
+    // auto_export is optional; "none" turns it off
+    if key == "auto_export" {
+        return Some(std::iter::once("none").chain(allowed.iter().copied()).collect());
+    }
+    Some(allowed.to_vec())
+}
+
+impl AppConfig {
+    /// Current value of an editable setting as text.
+    pub fn setting(&self, key: &str) -> Option<String> {
+        let value = match key {
+            "auto_backup" => self.auto_backup.to_string(),
+            "show_whitelisted" => self.show_whitelisted.to_string(),
+            "export_hashes" => self.export_hashes.to_string(),
+            "merge_duplicates" => self.merge_duplicates.to_string(),
+            "dry_run" => self.dry_run.to_string(),
+            "scan_other_users" => self.scan_other_users.to_string(),
+            "wmi_cross_check" => self.wmi_cross_check.to_string(),
+            "include_microsoft_tasks" => self.include_microsoft_tasks.to_string(),
+            "resume_interrupted_scan" => self.resume_interrupted_scan.to_string(),
+            "pause_before_tui" => self.pause_before_tui.to_string(),
+            "quick_toggle" => self.quick_toggle.to_string(),
+            "default_sort" => self.default_sort.clone(),
+            "min_severity" => self.min_severity.clone(),
+            "backup_scope" => self.backup_scope.clone(),
+            "log_level" => self.log_level.clone(),
+            "log_format" => self.log_format.clone(),
+            "auto_export" => self.auto_export.clone().unwrap_or_else(|| "none".to_string()),
+            "language" => self.language.clone(),
+            "theme" => self.theme.clone(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// The value after the current one, wrapping around.
+    pub fn next_setting_value(&self, key: &str) -> Option<String> {
+        let choices = setting_choices(key)?;
+        let current = self.setting(key)?;
+        let next = match choices.iter().position(|c| *c == current) {
+            Some(pos) => choices[(pos + 1) % choices.len()],
+            None => choices[0],
+        };
+        Some(next.to_string())
+    }
+
+    /// Sets an editable setting from its text form.
+    pub fn set_setting(&mut self, key: &str, value: &str) -> Result<()> {
+        let choices = setting_choices(key)
+            .ok_or_else(|| anyhow::anyhow!("'{}' can't be changed here", key))?;
+        if !choices.contains(&value) {
+            anyhow::bail!("Invalid value '{}' for {} (expected one of: {})", value, key, choices.join(", "));
+        }
+
+        let flag = value == "true";
+        match key {
+            "auto_backup" => self.auto_backup = flag,
+            "show_whitelisted" => self.show_whitelisted = flag,
+            "export_hashes" => self.export_hashes = flag,
+            "merge_duplicates" => self.merge_duplicates = flag,
+            "dry_run" => self.dry_run = flag,
+            "scan_other_users" => self.scan_other_users = flag,
+            "wmi_cross_check" => self.wmi_cross_check = flag,
+            "include_microsoft_tasks" => self.include_microsoft_tasks = flag,
+            "resume_interrupted_scan" => self.resume_interrupted_scan = flag,
+            "pause_before_tui" => self.pause_before_tui = flag,
+            "quick_toggle" => self.quick_toggle = flag,
+            "default_sort" => self.default_sort = value.to_string(),
+            "min_severity" => self.min_severity = value.to_string(),
+            "backup_scope" => self.backup_scope = value.to_string(),
+            "log_level" => self.log_level = value.to_string(),
+            "log_format" => self.log_format = value.to_string(),
+            "auto_export" => self.auto_export = (value != "none").then(|| value.to_string()),
+            "language" => self.language = value.to_string(),
+            "theme" => self.theme = value.to_string(),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn default_watch_interval_seconds() -> u64 {
+    60
+}
+
+fn default_language() -> String {
+    "auto".to_string()
+}
+
+fn default_theme() -> String {
+    "default".to_string()
+}
+
+fn default_confirm_threshold() -> usize {
+    10
+}
+
+fn default_merge_duplicates() -> bool {
+    true
+}
+
+fn default_log_format() -> String {
+    "text".to_string()
+}
+
+fn default_log_retention_days() -> u32 {
+    30
+}
+
+fn default_backup_scope() -> String {
+    "all".to_string()
+}
+
+fn default_max_backups() -> usize {
+    50
+}
+
+fn default_resume_max_age_minutes() -> u64 {
+    30
+}
+
+fn default_min_severity() -> String {
+    "none".to_string()
+}
+
+fn default_critical_patterns() -> Vec<String> {
+    [
+        "defender", "antivirus", "anti-virus", "antimalware", "security", "firewall",
+        "endpoint", "backup", "vpn",
+    ]
+    .iter()
+    .map(|p| p.to_string())
+    .collect()
+}
+
+fn default_lookup_order() -> Vec<String> {
+    // Cheapest sources first; Task Scheduler enumeration is by far the slowest
+    vec![
+        "registry".to_string(),
+        "startup_folder".to_string(),
+        "services".to_string(),
+        "task_scheduler".to_string(),
+    ]
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             auto_backup: true,
             show_whitelisted: false,
             default_sort: "name".to_string(),
             log_level: "info".to_string(),
+            log_format: default_log_format(),
+            log_retention_days: default_log_retention_days(),
             auto_export: None,
+            backup_scope: default_backup_scope(),
+            max_backups: default_max_backups(),
+            lookup_order: default_lookup_order(),
+            resume_interrupted_scan: false,
+            resume_max_age_minutes: default_resume_max_age_minutes(),
+            footprint: FootprintWeights::default(),
+            critical_patterns: default_critical_patterns(),
+            min_severity: default_min_severity(),
+            export_hashes: false,
+            merge_duplicates: default_merge_duplicates(),
+            confirm_threshold: default_confirm_threshold(),
+            dry_run: false,
+            scan_other_users: false,
+            wmi_cross_check: false,
+            include_microsoft_tasks: false,
+            scan_cache_seconds: 0,
+            virustotal_api_key: None,
+            watch_interval_seconds: default_watch_interval_seconds(),
+            pause_before_tui: false,
+            quick_toggle: false,
+            language: default_language(),
+            theme: default_theme(),
+            unknown: Map::new(),
         }
     }
 }
 
+impl VersionedSettings for AppConfig {
+    const FILE: &'static str = "config.json";
+    const VERSION: u32 = CONFIG_VERSION;
+}
+
+/// A settings file with a format version, loaded field by field so one bad
+/// or outdated value doesn't cost the rest of the file.
+pub trait VersionedSettings: Serialize + DeserializeOwned + Default {
+    const FILE: &'static str;
+    const VERSION: u32;
+
+    /// Rewrites the fields of a file written by an older version. Settings
+    /// added since need nothing here; missing ones get their defaults.
+    fn migrate(_fields: &mut Map<String, Value>, _from_version: u32) {}
+}
+
+/// Reads a settings file, keeping every valid field and falling back to the
+/// default for each missing or invalid one, which is logged. A file that
+/// isn't a JSON object at all is logged and the defaults are used, leaving
+/// the file alone.
+pub fn load_settings<T: VersionedSettings>(path: &Path) -> Result<T> {
+    match read_fields::<T>(path, true)? {
+        Some(fields) => settings_from_fields(fields),
+        None => Ok(T::default()),
+    }
+}
+
+/// The top-level fields of a settings file, migrated to the current
+/// version, or `None` (logged) when the file isn't a JSON object. With
+/// `upgrade`, an older file is rewritten with the migrated fields once, after
+/// a copy is saved to the backup directory.
+fn read_fields<T: VersionedSettings>(path: &Path, upgrade: bool) -> Result<Option<Map<String, Value>>> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let mut fields = match serde_json::from_str::<Value>(&content) {
+        Ok(Value::Object(fields)) => fields,
+        Ok(_) => {
+            warn_once(format!(
+                "{:?} is not a JSON object; using the defaults until it is fixed (see `deepboot doctor`)",
+                path
+            ));
+            return Ok(None);
+        }
+        Err(e) => {
+            warn_once(format!(
+                "{:?} is not valid JSON ({}); using the defaults until it is fixed (see `deepboot doctor`)",
+                path, e
+            ));
+            return Ok(None);
+        }
+    };
+
+    let version = fields.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version < T::VERSION {
+        T::migrate(&mut fields, version);
+        fields.insert("version".to_string(), Value::from(T::VERSION));
+        if upgrade {
+            let upgraded = BackupManager::new()
+                .and_then(|manager| manager.backup_file(path))
+                .and_then(|_| write_settings(path, T::FILE, &fields));
+            if let Err(e) = upgraded {
+                warn_once(format!("Failed to upgrade {:?}: {}", path, e));
+            }
+        }
+    }
+    Ok(Some(fields))
+}
+
+/// Lays `fields` over the defaults one at a time. Fields that don't
+/// deserialize keep their default, and each is logged.
+fn settings_from_fields<T: VersionedSettings>(fields: Map<String, Value>) -> Result<T> {
+    let mut merged = match serde_json::to_value(T::default())? {
+        Value::Object(defaults) => defaults,
+        _ => anyhow::bail!("Default {} is not a JSON object", T::FILE),
+    };
+
+    for (name, value) in fields {
+        let previous = merged.insert(name.clone(), value);
+        if let Err(e) = serde_json::from_value::<T>(Value::Object(merged.clone())) {
+            warn_once(format!(
+                "{}: ignoring '{}' ({}); using its default (see `deepboot doctor`)",
+                T::FILE, name, e
+            ));
+            match previous {
+                Some(previous) => merged.insert(name, previous),
+                None => merged.remove(&name),
+            };
+        }
+    }
+
+    serde_json::from_value(Value::Object(merged)).with_context(|| format!("Failed to build {}", T::FILE))
+}
+
+/// Writes a settings file.
+pub fn save_settings<T: VersionedSettings>(path: &Path, settings: &T) -> Result<()> {
+    write_settings(path, T::FILE, settings)
+}
+
+/// Writes `content` as pretty JSON. A file on disk that doesn't parse was
+/// never loaded, so a copy goes to the backup directory before it is replaced.
+fn write_settings(path: &Path, file: &str, content: &impl Serialize) -> Result<()> {
+    if let Ok(existing) = fs::read_to_string(path) {
+        if !matches!(serde_json::from_str::<Value>(&existing), Ok(Value::Object(_))) {
+            BackupManager::new()?.backup_file(path)?;
+        }
+    }
+    let content = serde_json::to_string_pretty(content).with_context(|| format!("Failed to serialize {}", file))?;
+    fs::write(path, content).with_context(|| format!("Failed to save {}", file))
+}
+
+/// Logs a settings problem the first time it comes up. Settings are loaded
+/// many times per run, and the warnings panel should list each problem once.
+fn warn_once(message: String) {
+    lazy_static::lazy_static! {
+        static ref WARNED: std::sync::Mutex<std::collections::HashSet<String>> = Default::default();
+    }
+    if WARNED.lock().map(|mut warned| warned.insert(message.clone())).unwrap_or(true) {
+        log::warn!("{}", message);
+    }
+}
+
+/// The machine-wide config every user inherits, under `%ProgramData%`.
+fn machine_config_path() -> Option<PathBuf> {
+    std::env::var_os("ProgramData").map(|dir| PathBuf::from(dir).join("deepboot").join("config.json"))
+}
+
+/// Field of the machine config listing the settings users can't override.
+const LOCKED_FIELD: &str = "locked_settings";
+
+/// The settings in effect: the defaults, overridden by the machine config in
+/// `%ProgramData%\deepboot\config.json` if there is one, overridden in turn
+/// by the user's `%APPDATA%\deepboot\config.json`, except for settings the
+/// machine config locks. Only the user's file is ever written.
 pub struct ConfigManager {
     config: AppConfig,
     config_path: PathBuf,
+    machine_path: Option<PathBuf>, // Set when a machine config was loaded
+    base: Option<Map<String, Value>>, // Defaults with the machine config applied
+    locked: Vec<String>,
 }
 
 impl ConfigManager {
@@ -42,22 +492,60 @@ impl ConfigManager {
 
         let config_path = config_dir.join("config.json");
 
-        let config = if config_path.exists() {
-            let content = fs::read_to_string(&config_path)
-                .context("Failed to read config file")?;
-            serde_json::from_str(&content).unwrap_or_else(|_| AppConfig::default())
+        // Administrators maintain the machine config, so it is never upgraded
+        let machine_path = machine_config_path().filter(|path| path.exists());
+        let mut machine = match &machine_path {
+            Some(path) => read_fields::<AppConfig>(path, false)?,
+            None => None,
+        };
+        let locked: Vec<String> = machine
+            .as_mut()
+            .and_then(|fields| fields.remove(LOCKED_FIELD))
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+
+        let user = if config_path.exists() {
+            read_fields::<AppConfig>(&config_path, true)?
         } else {
-            let default_config = AppConfig::default();
-            let content = serde_json::to_string_pretty(&default_config)
-                .context("Failed to serialize default config")?;
-            fs::write(&config_path, content)
-                .context("Failed to write default config")?;
-            default_config
+            // With a machine config, an empty user layer inherits all of it
+            if machine.is_none() {
+                save_settings(&config_path, &AppConfig::default())?;
+            }
+            None
+        };
+
+        let base = match &machine {
+            Some(fields) => match serde_json::to_value(settings_from_fields::<AppConfig>(fields.clone())?)? {
+                Value::Object(base) => Some(base),
+                _ => None,
+            },
+            None => None,
         };
 
+        let mut fields = machine.unwrap_or_default();
+        let mut overridden = Vec::new();
+        for (name, value) in user.unwrap_or_default() {
+            if locked.contains(&name) {
+                overridden.push(name);
+            } else {
+                fields.insert(name, value);
+            }
+        }
+        if !overridden.is_empty() {
+            warn_once(format!(
+                "{:?} locks {}; the values in {:?} are ignored",
+                machine_path.as_deref().unwrap_or(Path::new("")),
+                overridden.join(", "),
+                config_path
+            ));
+        }
+
         Ok(Self {
-            config,
+            config: settings_from_fields(fields)?,
             config_path,
+            machine_path,
+            base,
+            locked,
         })
     }
 
@@ -69,12 +557,52 @@ impl ConfigManager {
         &mut self.config
     }
 
+    pub fn path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// The machine-wide config in effect, if any.
+    pub fn machine_path(&self) -> Option<&Path> {
+        self.machine_path.as_deref()
+    }
+
+    /// Whether the machine config keeps users from changing `key`.
+    pub fn is_locked(&self, key: &str) -> bool {
+        self.locked.iter().any(|locked| locked == key)
+    }
+
+    /// Backs up the current config file and overwrites it with the defaults,
+    /// or with an empty user layer inheriting the machine config.
+    /// Returns the backup location, if there was a file to back up.
+    pub fn reset(&mut self, backup_manager: &BackupManager) -> Result<Option<PathBuf>> {
+        let backup_path = if self.config_path.exists() {
+            Some(backup_manager.backup_file(&self.config_path)?)
+        } else {
+            None
+        };
+
+        self.config = match &self.base {
+            Some(base) => serde_json::from_value(Value::Object(base.clone()))
+                .context("Failed to apply the machine config")?,
+            None => AppConfig::default(),
+        };
+        self.save()?;
+        Ok(backup_path)
+    }
+
+    /// Writes the user's config file. Under a machine config only the
+    /// settings that differ from it are written, so later changes to the
+    /// machine config still reach this user.
     pub fn save(&self) -> Result<()> {
-        let content = serde_json::to_string_pretty(&self.config)
-            .context("Failed to serialize config")?;
-        fs::write(&self.config_path, content)
-            .context("Failed to save config")?;
-        Ok(())
+        let Some(base) = &self.base else {
+            return save_settings(&self.config_path, &self.config);
+        };
+        let mut fields = match serde_json::to_value(&self.config).context("Failed to serialize config")? {
+            Value::Object(fields) => fields,
+            _ => anyhow::bail!("Config is not a JSON object"),
+        };
+        fields.retain(|name, value| name == "version" || (!self.is_locked(name) && base.get(name) != Some(value)));
+        write_settings(&self.config_path, AppConfig::FILE, &fields)
     }
 }
 
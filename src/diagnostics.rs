@@ -0,0 +1,115 @@
+use crate::models::{EntryFlag, StartupEntry};
+use crate::paths;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use windows::{
+    core::HSTRING,
+    Win32::Storage::FileSystem::GetDriveTypeW,
+    Win32::System::WindowsProgramming::{
+        DRIVE_CDROM, DRIVE_NO_ROOT_DIR, DRIVE_REMOVABLE, DRIVE_UNKNOWN,
+    },
+};
+
+// Extensions Windows will run directly; a file carrying one of these is expected to be a program
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "com", "scr", "pif", "dll", "cpl", "sys", "ocx", "bat", "cmd", "ps1", "vbs", "vbe", "js",
+    "jse", "wsf", "hta", "msi", "lnk",
+];
+
+// Extensions used as bait in names like "invoice.pdf.exe". Only these count as
+// a first extension so legitimate multi-dot names ("app.v2.exe") aren't flagged.
+const DECOY_EXTENSIONS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "rtf", "txt", "csv", "jpg", "jpeg", "png",
+    "gif", "bmp", "mp3", "mp4", "avi", "mov", "wav", "zip", "rar", "7z", "htm", "html",
+];
+
+pub struct Diagnostics;
+
+impl Diagnostics {
+    // Flags computed here; others are set by the scanners and kept as-is
+    const COMPUTED: &'static [EntryFlag] = &[
+        EntryFlag::RemovableDrive,
+        EntryFlag::UnknownDrive,
+        EntryFlag::DoubleExtension,
+        EntryFlag::MismatchedExtension,
+        EntryFlag::MissingTarget,
+    ];
+
+    /// Recomputes the diagnostic flags of every entry.
+    pub fn annotate(entries: &mut [StartupEntry]) {
+        for entry in entries.iter_mut() {
+            let computed = Self::check(entry);
+            entry.flags.retain(|flag| !Self::COMPUTED.contains(flag));
+            entry.flags.extend(computed);
+        }
+    }
+
+    fn check(entry: &StartupEntry) -> Vec<EntryFlag> {
+        let mut flags = Vec::new();
+
+        if let Some(executable) = entry.target_path() {
+            match Self::check_drive(&executable) {
+                Some(flag) => flags.push(flag),
+                // A missing drive already explains a missing file
+                None if !entry.target_exists() => flags.push(EntryFlag::MissingTarget),
+                None => {}
+            }
+            if let Some(flag) = Self::check_extension(&executable) {
+                flags.push(flag);
+            }
+        }
+
+        flags
+    }
+
+    /// Flags targets on removable media or on drives that are not currently
+    /// present; such entries silently fail when the drive is missing.
+    fn check_drive(path: &Path) -> Option<EntryFlag> {
+        let root = paths::drive_root(path)?;
+        let drive_type = unsafe { GetDriveTypeW(&HSTRING::from(root.as_str())) };
+
+        match drive_type {
+            DRIVE_REMOVABLE | DRIVE_CDROM => Some(EntryFlag::RemovableDrive),
+            DRIVE_UNKNOWN | DRIVE_NO_ROOT_DIR => Some(EntryFlag::UnknownDrive),
+            _ => None,
+        }
+    }
+
+    /// Flags deceptive file names: an executable extension stacked on a
+    /// document-like one, or executable content behind a non-executable
+    /// extension. The content check only reads the first two bytes.
+    fn check_extension(path: &Path) -> Option<EntryFlag> {
+        let file_name = path.file_name()?.to_string_lossy().to_lowercase();
+        let parts: Vec<&str> = file_name.split('.').collect();
+
+        if let [.., stem, decoy, extension] = parts.as_slice() {
+            if !stem.is_empty()
+                && DECOY_EXTENSIONS.contains(decoy)
+                && EXECUTABLE_EXTENSIONS.contains(extension)
+            {
+                return Some(EntryFlag::DoubleExtension);
+            }
+        }
+
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if EXECUTABLE_EXTENSIONS.contains(&extension.as_str()) {
+            return None;
+        }
+
+        // "MZ" starts every PE image
+        let mut header = [0u8; 2];
+        let is_pe = File::open(path)
+            .and_then(|mut file| file.read_exact(&mut header))
+            .map(|_| &header == b"MZ")
+            .unwrap_or(false);
+        if is_pe {
+            Some(EntryFlag::MismatchedExtension)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use crate::backup::BackupManager;
+use crate::config::{self, AppConfig, ConfigManager};
+use crate::models::StartupEntry;
+use crate::whitelist::{self, WhitelistCategory, WhitelistConfig, WhitelistManager};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A problem found in one of the settings files.
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub file: &'static str,
+    pub message: String,
+}
+
+/// Result of a doctor run: the issues found plus the repaired file contents.
+#[derive(Debug, Default)]
+pub struct DoctorReport {
+    pub issues: Vec<Issue>,
+    config_fix: Option<Value>,
+    whitelist_fix: Option<WhitelistConfig>,
+}
+
+pub struct Doctor {
+    config_path: PathBuf,
+    whitelist_path: PathBuf,
+    layered: bool, // A machine config supplies what the user's config leaves out
+}
+
+impl Doctor {
+    pub fn new() -> Result<Self> {
+        let config_manager = ConfigManager::new()?;
+        Ok(Self {
+            config_path: config_manager.path().to_path_buf(),
+            whitelist_path: WhitelistManager::new()?.path().to_path_buf(),
+            layered: config_manager.machine_path().is_some(),
+        })
+    }
+
+    /// Checks both settings files. `entries` is the current scan, used to find
+    /// whitelist keys that no longer match any startup entry.
+    pub fn diagnose(&self, entries: &[StartupEntry]) -> Result<DoctorReport> {
+        let mut report = DoctorReport::default();
+        report.config_fix = Self::check_config(&self.config_path, self.layered, &mut report.issues)?;
+        report.whitelist_fix =
+            Self::check_whitelist(&self.whitelist_path, entries, &mut report.issues)?;
+        Ok(report)
+    }
+
+    /// Backs up and rewrites every file that has issues. Returns the number of
+    /// files rewritten.
+    pub fn apply(&self, report: &DoctorReport, backup_manager: &BackupManager) -> Result<usize> {
+        let mut fixed = 0;
+
+        if let Some(ref config) = report.config_fix {
+            let content = serde_json::to_string_pretty(config)
+                .context("Failed to serialize repaired config")?;
+            Self::rewrite(&self.config_path, &content, backup_manager)?;
+            fixed += 1;
+        }
+
+        if let Some(ref whitelist) = report.whitelist_fix {
+            let content = serde_json::to_string_pretty(whitelist)
+                .context("Failed to serialize repaired whitelist")?;
+            Self::rewrite(&self.whitelist_path, &content, backup_manager)?;
+            fixed += 1;
+        }
+
+        Ok(fixed)
+    }
+
+    fn rewrite(path: &Path, content: &str, backup_manager: &BackupManager) -> Result<()> {
+        if path.exists() {
+            backup_manager.backup_file(path)?;
+        }
+        fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    fn read_json(path: &Path, file: &'static str, issues: &mut Vec<Issue>) -> Result<Option<Value>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        match serde_json::from_str::<Value>(&content) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => {
+                issues.push(Issue {
+                    file,
+                    message: format!("File is not valid JSON ({}); defaults are used instead", e),
+                });
+                Ok(None)
+            }
+        }
+    }
+
+    /// With `layered`, the file only holds the user's overrides of the machine
+    /// config, so missing fields are expected and the repair keeps it sparse.
+    fn check_config(path: &Path, layered: bool, issues: &mut Vec<Issue>) -> Result<Option<Value>> {
+        const FILE: &str = "config.json";
+        let before = issues.len();
+
+        let defaults = match serde_json::to_value(AppConfig::default())? {
+            Value::Object(map) => map,
+            _ => unreachable!("AppConfig serializes to an object"),
+        };
+        let start = if layered { serde_json::Map::new() } else { defaults.clone() };
+
+        let value = match Self::read_json(path, FILE, issues)? {
+            Some(value) => value,
+            None if issues.len() > before => return Ok(Some(Value::Object(start))),
+            None => return Ok(None),
+        };
+
+        let fields = match value {
+            Value::Object(fields) => fields,
+            _ => {
+                issues.push(Issue {
+                    file: FILE,
+                    message: "Top level is not a JSON object; defaults are used instead".to_string(),
+                });
+                return Ok(Some(Value::Object(start)));
+            }
+        };
+
+        let mut repaired = start;
+        for (name, value) in &fields {
+            let Some(default) = defaults.get(name) else {
+                issues.push(Issue {
+                    file: FILE,
+                    message: format!("Unknown field '{}' is ignored", name),
+                });
+                continue;
+            };
+
+            if !Self::same_kind(default, value) {
+                issues.push(Issue {
+                    file: FILE,
+                    message: format!("Field '{}' has the wrong type ({})", name, value),
+                });
+                continue;
+            }
+
+            if let (Some(allowed), Some(text)) = (config::allowed_values(name), value.as_str()) {
+                if !allowed.contains(&text) {
+                    issues.push(Issue {
+                        file: FILE,
+                        message: format!(
+                            "Field '{}' has invalid value '{}' (expected one of: {})",
+                            name,
+                            text,
+                            allowed.join(", ")
+                        ),
+                    });
+                    continue;
+                }
+            }
+
+            repaired.insert(name.clone(), value.clone());
+        }
+
+        for name in defaults.keys().filter(|_| !layered) {
+            if !fields.contains_key(name) {
+                issues.push(Issue {
+                    file: FILE,
+                    message: format!("Missing field '{}'", name),
+                });
+            }
+        }
+
+        if issues.len() == before {
+            return Ok(None);
+        }
+
+        if !layered {
+            serde_json::from_value::<AppConfig>(Value::Object(repaired.clone()))
+                .context("Failed to build repaired config")?;
+        }
+        Ok(Some(Value::Object(repaired)))
+    }
+
+    fn same_kind(default: &Value, value: &Value) -> bool {
+        match default {
+            // Optional settings default to null and accept a string
+            Value::Null => value.is_null() || value.is_string(),
+            Value::Bool(_) => value.is_boolean(),
+            Value::Number(_) => value.is_number(),
+            Value::String(_) => value.is_string(),
+            Value::Array(_) => value.is_array(),
+            Value::Object(_) => value.is_object(),
+        }
+    }
+
+    fn check_whitelist(
+        path: &Path,
+        entries: &[StartupEntry],
+        issues: &mut Vec<Issue>,
+    ) -> Result<Option<WhitelistConfig>> {
+        const FILE: &str = "whitelist.json";
+        let before = issues.len();
+
+        let value = match Self::read_json(path, FILE, issues)? {
+            Some(value) => value,
+            None if issues.len() > before => return Ok(Some(WhitelistConfig::default())),
+            None => return Ok(None),
+        };
+
+        let fields = match value {
+            Value::Object(fields) => fields,
+            _ => {
+                issues.push(Issue {
+                    file: FILE,
+                    message: "Top level is not a JSON object; defaults are used instead".to_string(),
+                });
+                return Ok(Some(WhitelistConfig::default()));
+            }
+        };
+
+        for name in fields.keys() {
+            if name != "version" && !WhitelistCategory::ALL.iter().any(|c| c.field_name() == name) {
+                issues.push(Issue {
+                    file: FILE,
+                    message: format!("Unknown field '{}' is ignored", name),
+                });
+            }
+        }
+
+        let defaults = WhitelistConfig::default();
+        let live_keys = Self::live_keys(entries);
+        let mut repaired = WhitelistConfig {
+            safe_processes: HashSet::new(),
+            safe_services: HashSet::new(),
+            safe_tasks: HashSet::new(),
+            ..WhitelistConfig::default()
+        };
+
+        for &category in WhitelistCategory::ALL {
+            let field = category.field_name();
+            let items = match fields.get(field) {
+                Some(Value::Array(items)) => items.clone(),
+                Some(_) => {
+                    issues.push(Issue {
+                        file: FILE,
+                        message: format!("Field '{}' is not a list", field),
+                    });
+                    continue;
+                }
+                None => {
+                    issues.push(Issue {
+                        file: FILE,
+                        message: format!("Missing field '{}'", field),
+                    });
+                    continue;
+                }
+            };
+
+            Self::check_whitelist_keys(category, &items, &defaults, &live_keys, &mut repaired, issues);
+        }
+
+        if issues.len() == before {
+            return Ok(None);
+        }
+
+        Ok(Some(repaired))
+    }
+
+    fn check_whitelist_keys(
+        category: WhitelistCategory,
+        items: &[Value],
+        defaults: &WhitelistConfig,
+        live_keys: &WhitelistConfig,
+        repaired: &mut WhitelistConfig,
+        issues: &mut Vec<Issue>,
+    ) {
+        const FILE: &str = "whitelist.json";
+        let field = category.field_name();
+        let mut seen = HashSet::new();
+
+        for item in items {
+            let Some(raw) = item.as_str() else {
+                issues.push(Issue {
+                    file: FILE,
+                    message: format!("Non-text value {} in '{}'", item, field),
+                });
+                continue;
+            };
+
+            let key = raw.trim().to_lowercase();
+            if key.is_empty() {
+                issues.push(Issue {
+                    file: FILE,
+                    message: format!("Empty key in '{}'", field),
+                });
+                continue;
+            }
+
+            if !seen.insert(key.clone()) {
+                issues.push(Issue {
+                    file: FILE,
+                    message: format!("Duplicate key '{}' in '{}'", raw, field),
+                });
+                continue;
+            }
+
+            // Patterns ignore case; plain keys are compared as stored
+            if raw != key && !whitelist::is_pattern(raw) {
+                issues.push(Issue {
+                    file: FILE,
+                    message: format!(
+                        "Key '{}' in '{}' is not lowercase and never matches",
+                        raw, field
+                    ),
+                });
+            }
+
+            // Built-in keys are kept even when nothing currently matches them
+            let live = live_keys.keys(category).iter().any(|name| whitelist::key_matches(&key, name));
+            if !defaults.keys(category).contains(&key) && !live {
+                issues.push(Issue {
+                    file: FILE,
+                    message: format!(
+                        "Key '{}' in '{}' no longer matches any startup entry",
+                        key, field
+                    ),
+                });
+                continue;
+            }
+
+            repaired.keys_mut(category).insert(key);
+        }
+    }
+
+    /// The whitelist keys the scanned entries would match, per category.
+    fn live_keys(entries: &[StartupEntry]) -> WhitelistConfig {
+        let mut live = WhitelistConfig {
+            safe_processes: HashSet::new(),
+            safe_services: HashSet::new(),
+            safe_tasks: HashSet::new(),
+            ..WhitelistConfig::default()
+        };
+
+        for entry in entries {
+            // Process keys are checked against every entry's command
+            if let Some(process) = WhitelistManager::extract_process_name(&entry.command.to_lowercase()) {
+                live.safe_processes.insert(process);
+            }
+            if let Some((category, key)) = WhitelistManager::key_for(entry) {
+                live.keys_mut(category).insert(key);
+            }
+        }
+
+        live
+    }
+
+    pub fn print_report(report: &DoctorReport) {
+        for file in ["config.json", "whitelist.json"] {
+            let issues: Vec<&Issue> = report.issues.iter().filter(|i| i.file == file).collect();
+            if issues.is_empty() {
+                println!("{}: OK", file);
+                continue;
+            }
+
+            println!("{}: {} issue(s)", file, issues.len());
+            for issue in issues {
+                println!("  - {}", issue.message);
+            }
+        }
+    }
+}
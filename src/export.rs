@@ -1,13 +1,111 @@
-use crate::models::StartupEntry;
+use crate::file_info::{self, FileInfo};
+use crate::models::{EntryFlag, StartupEntry, StartupSource};
 use anyhow::{Context, Result};
 use chrono::Local;
+use serde::Serialize;
 use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// An entry as written to JSON, optionally with its executable's hash.
+#[derive(Serialize)]
+struct ExportedEntry<'a> {
+    #[serde(flatten)]
+    entry: &'a StartupEntry,
+    // Left out unless hashing was requested; `Some(None)` writes `null`
+    // for executables that can't be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha256: Option<Option<String>>,
+}
+
+/// How the disable decisions in an imported export map onto this machine.
+#[derive(Debug, Default)]
+pub struct ImportDiff {
+    pub to_disable: Vec<StartupEntry>, // Current entries the import has disabled
+    pub already_disabled: Vec<StartupEntry>,
+    pub skipped: Vec<StartupEntry>, // Imported entries not installed here
+}
+
+/// A command that differs between two exports of the same entry.
+#[derive(Debug, Serialize)]
+pub struct CommandChange {
+    pub name: String,
+    pub source: StartupSource,
+    pub old_command: String,
+    pub new_command: String,
+}
+
+/// What changed between two exports. Entries are the same when source and
+/// (case-insensitive) name match; an entry can be both re-enabled or
+/// disabled and have a changed command.
+#[derive(Debug, Default, Serialize)]
+pub struct ExportDiff {
+    pub added: Vec<StartupEntry>,
+    pub removed: Vec<StartupEntry>,
+    pub disabled: Vec<StartupEntry>, // Enabled in the old export, disabled in the new
+    pub enabled: Vec<StartupEntry>,  // Disabled in the old export, enabled in the new
+    pub command_changed: Vec<CommandChange>,
+}
+
+impl ExportDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.disabled.is_empty()
+            && self.enabled.is_empty()
+            && self.command_changed.is_empty()
+    }
+
+    /// One line per change, grouped by kind.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for entry in &self.added {
+            lines.push(format!("  + {} [{}] added: {}", entry.name, entry.source, entry.command));
+        }
+        for entry in &self.removed {
+            lines.push(format!("  - {} [{}] removed", entry.name, entry.source));
+        }
+        for entry in &self.disabled {
+            lines.push(format!("  ~ {} [{}] enabled -> disabled", entry.name, entry.source));
+        }
+        for entry in &self.enabled {
+            lines.push(format!("  ~ {} [{}] disabled -> enabled", entry.name, entry.source));
+        }
+        for change in &self.command_changed {
+            lines.push(format!("  * {} [{}] command changed", change.name, change.source));
+            lines.push(format!("      was: {}", change.old_command));
+            lines.push(format!("      now: {}", change.new_command));
+        }
+        lines
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} added, {} removed, {} disabled, {} enabled, {} with a changed command",
+            self.added.len(),
+            self.removed.len(),
+            self.disabled.len(),
+            self.enabled.len(),
+            self.command_changed.len()
+        )
+    }
+}
 
 pub struct Exporter;
 
 impl Exporter {
-    pub fn export_json(entries: &[StartupEntry], path: Option<PathBuf>) -> Result<PathBuf> {
+    /// SHA-256 of the entry's executable, or `None` when it can't be
+    /// resolved or read.
+    pub fn entry_hash(entry: &StartupEntry) -> Option<String> {
+        let path = entry.target_path()?;
+        if !path.is_file() {
+            return None;
+        }
+        file_info::sha256_file(&path)
+            .map_err(|e| log::warn!("Failed to hash {:?}: {}", path, e))
+            .ok()
+    }
+
+    pub fn export_json(entries: &[StartupEntry], path: Option<PathBuf>, with_hashes: bool) -> Result<PathBuf> {
         let file_path = path.unwrap_or_else(|| {
             let timestamp = Local::now().format("%Y%m%d_%H%M%S");
             PathBuf::from(format!("deepboot_export_{}.json", timestamp))
@@ -16,13 +114,83 @@ impl Exporter {
         let file = File::create(&file_path)
             .with_context(|| format!("Failed to create file: {:?}", file_path))?;
 
-        serde_json::to_writer_pretty(file, entries)
+        let exported: Vec<ExportedEntry> = entries
+            .iter()
+            .map(|entry| ExportedEntry {
+                entry,
+                sha256: with_hashes.then(|| Self::entry_hash(entry)),
+            })
+            .collect();
+
+        serde_json::to_writer_pretty(file, &exported)
             .context("Failed to write JSON data")?;
 
         Ok(file_path)
     }
 
-    pub fn export_csv(entries: &[StartupEntry], path: Option<PathBuf>) -> Result<PathBuf> {
+    /// Reads entries back from a file written by `export_json`.
+    pub fn import_json(path: &Path) -> Result<Vec<StartupEntry>> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open file: {:?}", path))?;
+
+        serde_json::from_reader(file)
+            .with_context(|| format!("Failed to parse exported entries from {:?}", path))
+    }
+
+    /// Compares two exports of the same machine, e.g. yesterday's and
+    /// today's. Entries sharing a source and name are paired in file order.
+    pub fn diff_exports(old: &[StartupEntry], new: &[StartupEntry]) -> ExportDiff {
+        let mut diff = ExportDiff::default();
+        let mut unmatched: Vec<&StartupEntry> = new.iter().collect();
+
+        for before in old {
+            let found = unmatched
+                .iter()
+                .position(|e| e.source == before.source && e.name.eq_ignore_ascii_case(&before.name));
+            let Some(after) = found.map(|pos| unmatched.remove(pos)) else {
+                diff.removed.push(before.clone());
+                continue;
+            };
+
+            match (before.enabled, after.enabled) {
+                (true, false) => diff.disabled.push(after.clone()),
+                (false, true) => diff.enabled.push(after.clone()),
+                _ => {}
+            }
+            if before.command != after.command {
+                diff.command_changed.push(CommandChange {
+                    name: after.name.clone(),
+                    source: after.source.clone(),
+                    old_command: before.command.clone(),
+                    new_command: after.command.clone(),
+                });
+            }
+        }
+
+        diff.added = unmatched.into_iter().cloned().collect();
+        diff
+    }
+
+    /// Matches the disabled entries of an import against the current scan by
+    /// source and (case-insensitive) name. Entries enabled in the import carry
+    /// no decision and are ignored.
+    pub fn diff_import(imported: &[StartupEntry], current: &[StartupEntry]) -> ImportDiff {
+        let mut diff = ImportDiff::default();
+
+        for entry in imported.iter().filter(|e| !e.enabled) {
+            let found = current
+                .iter()
+                .find(|c| c.source == entry.source && c.name.eq_ignore_ascii_case(&entry.name));
+            match found {
+                Some(current) if current.enabled => diff.to_disable.push(current.clone()),
+                Some(current) => diff.already_disabled.push(current.clone()),
+                None => diff.skipped.push(entry.clone()),
+            }
+        }
+        diff
+    }
+
+    pub fn export_csv(entries: &[StartupEntry], path: Option<PathBuf>, with_hashes: bool) -> Result<PathBuf> {
         let file_path = path.unwrap_or_else(|| {
             let timestamp = Local::now().format("%Y%m%d_%H%M%S");
             PathBuf::from(format!("deepboot_export_{}.csv", timestamp))
@@ -32,20 +200,28 @@ impl Exporter {
             .with_context(|| format!("Failed to create CSV file: {:?}", file_path))?;
 
         // Write header
+        let mut header = vec!["Name", "Command", "Source", "Enabled", "Description"];
+        if with_hashes {
+            header.push("SHA256");
+        }
         writer
-            .write_record(&["Name", "Command", "Source", "Enabled", "Description"])
+            .write_record(&header)
             .context("Failed to write CSV header")?;
 
         // Write entries
         for entry in entries {
+            let mut record = vec![
+                entry.name.clone(),
+                entry.command.clone(),
+                entry.source.to_string(),
+                entry.enabled.to_string(),
+                entry.description.clone().unwrap_or_default(),
+            ];
+            if with_hashes {
+                record.push(Self::entry_hash(entry).unwrap_or_default());
+            }
             writer
-                .write_record(&[
-                    &entry.name,
-                    &entry.command,
-                    &entry.source.to_string(),
-                    &entry.enabled.to_string(),
-                    entry.description.as_deref().unwrap_or(""),
-                ])
+                .write_record(&record)
                 .context("Failed to write CSV record")?;
         }
 
@@ -54,7 +230,7 @@ impl Exporter {
         Ok(file_path)
     }
 
-    pub fn export_markdown(entries: &[StartupEntry], path: Option<PathBuf>) -> Result<PathBuf> {
+    pub fn export_markdown(entries: &[StartupEntry], path: Option<PathBuf>, with_hashes: bool) -> Result<PathBuf> {
         let file_path = path.unwrap_or_else(|| {
             let timestamp = Local::now().format("%Y%m%d_%H%M%S");
             PathBuf::from(format!("deepboot_export_{}.md", timestamp))
@@ -65,18 +241,30 @@ impl Exporter {
         content.push_str(&format!("Generated: {}\n\n", Local::now().format("%Y-%m-%d %H:%M:%S")));
         content.push_str(&format!("Total Entries: {}\n\n", entries.len()));
         content.push_str("## Startup Entries\n\n");
-        content.push_str("| Name | Command | Source | Enabled | Description |\n");
-        content.push_str("|------|---------|--------|---------|-------------|\n");
+        if with_hashes {
+            content.push_str("| Name | Command | Source | Enabled | Description | SHA-256 |\n");
+            content.push_str("|------|---------|--------|---------|-------------|---------|\n");
+        } else {
+            content.push_str("| Name | Command | Source | Enabled | Description |\n");
+            content.push_str("|------|---------|--------|---------|-------------|\n");
+        }
 
         for entry in entries {
             content.push_str(&format!(
-                "| {} | {} | {} | {} | {} |\n",
+                "| {} | {} | {} | {} | {} |",
                 entry.name,
                 entry.command,
                 entry.source,
                 if entry.enabled { "Yes" } else { "No" },
                 entry.description.as_deref().unwrap_or("")
             ));
+            if with_hashes {
+                content.push_str(&format!(
+                    " {} |",
+                    Self::entry_hash(entry).as_deref().unwrap_or("n/a")
+                ));
+            }
+            content.push('\n');
         }
 
         std::fs::write(&file_path, content)
@@ -84,5 +272,224 @@ impl Exporter {
 
         Ok(file_path)
     }
+
+    /// Writes a self-contained HTML report: one sortable table per source,
+    /// with inline styles and script so it can be mailed as a single file.
+    pub fn export_html(entries: &[StartupEntry], path: Option<PathBuf>, with_hashes: bool) -> Result<PathBuf> {
+        let file_path = path.unwrap_or_else(|| {
+            let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+            PathBuf::from(format!("deepboot_export_{}.html", timestamp))
+        });
+
+        let enabled = entries.iter().filter(|e| e.enabled).count();
+        let mut content = String::new();
+        content.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        content.push_str("<title>DeepBoot Scan Report</title>\n");
+        content.push_str(HTML_STYLE);
+        content.push_str("</head>\n<body>\n<h1>DeepBoot Scan Report</h1>\n");
+        content.push_str(&format!(
+            "<p class=\"meta\">Generated: {}</p>\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S")
+        ));
+        content.push_str(&format!(
+            "<p class=\"summary\">Total Entries: <b>{}</b> &middot; Enabled: <b>{}</b> &middot; Disabled: <b>{}</b></p>\n",
+            entries.len(),
+            enabled,
+            entries.len() - enabled
+        ));
+
+        for source in StartupSource::ALL {
+            let group: Vec<&StartupEntry> = entries.iter().filter(|e| e.source == *source).collect();
+            if group.is_empty() {
+                continue;
+            }
+
+            content.push_str(&format!("<h2>{} ({})</h2>\n", escape_html(&source.to_string()), group.len()));
+            content.push_str("<table>\n<thead><tr>");
+            let mut columns = vec!["Name", "Command", "Enabled", "Description"];
+            if with_hashes {
+                columns.push("SHA-256");
+            }
+            for column in columns {
+                content.push_str(&format!("<th onclick=\"sortTable(this)\">{}</th>", column));
+            }
+            content.push_str("</tr></thead>\n<tbody>\n");
+
+            for entry in group {
+                content.push_str(&format!(
+                    "<tr class=\"{}\"><td>{}</td><td><code>{}</code></td><td>{}</td><td>{}</td>",
+                    if entry.enabled { "enabled" } else { "disabled" },
+                    escape_html(&entry.name),
+                    escape_html(&entry.command),
+                    if entry.enabled { "Yes" } else { "No" },
+                    escape_html(entry.description.as_deref().unwrap_or(""))
+                ));
+                if with_hashes {
+                    content.push_str(&format!(
+                        "<td><code>{}</code></td>",
+                        Self::entry_hash(entry).as_deref().unwrap_or("n/a")
+                    ));
+                }
+                content.push_str("</tr>\n");
+            }
+            content.push_str("</tbody>\n</table>\n");
+        }
+
+        content.push_str(HTML_SCRIPT);
+        content.push_str("</body>\n</html>\n");
+
+        std::fs::write(&file_path, content)
+            .with_context(|| format!("Failed to write HTML file: {:?}", file_path))?;
+
+        Ok(file_path)
+    }
+
+    /// Exports in the named format ("json", "csv", "markdown" or "html").
+    pub fn export_as(
+        format: &str,
+        entries: &[StartupEntry],
+        path: Option<PathBuf>,
+        with_hashes: bool,
+    ) -> Result<PathBuf> {
+        match format {
+            "json" => Self::export_json(entries, path, with_hashes),
+            "csv" => Self::export_csv(entries, path, with_hashes),
+            "markdown" => Self::export_markdown(entries, path, with_hashes),
+            "html" => Self::export_html(entries, path, with_hashes),
+            other => anyhow::bail!("Unknown export format '{}'", other),
+        }
+    }
+
+    /// Writes a Markdown incident report covering only flagged entries, with
+    /// the signals behind each flag, file details and a recommended action.
+    pub fn export_incident_report(entries: &[StartupEntry], path: Option<PathBuf>) -> Result<PathBuf> {
+        let file_path = path.unwrap_or_else(|| {
+            let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+            PathBuf::from(format!("deepboot_incident_{}.md", timestamp))
+        });
+
+        let flagged: Vec<&StartupEntry> = entries.iter().filter(|e| !e.flags.is_empty()).collect();
+
+        let mut content = String::new();
+        content.push_str("# DeepBoot Incident Report\n\n");
+        content.push_str(&format!("Generated: {}\n\n", Local::now().format("%Y-%m-%d %H:%M:%S")));
+        content.push_str(&format!(
+            "Flagged entries: {} of {} scanned\n\n",
+            flagged.len(),
+            entries.len()
+        ));
+
+        for (i, entry) in flagged.iter().enumerate() {
+            content.push_str(&format!("## {}. {}\n\n", i + 1, entry.name));
+            content.push_str(&format!("- **Source:** {}\n", entry.source));
+            content.push_str(&format!(
+                "- **Status:** {}\n",
+                if entry.enabled { "Enabled" } else { "Disabled" }
+            ));
+            content.push_str(&format!("- **Command:** `{}`\n", entry.command));
+            if let Some(ref description) = entry.description {
+                content.push_str(&format!("- **Description:** {}\n", description));
+            }
+
+            match entry.target_path() {
+                Some(executable) => {
+                    let info = FileInfo::inspect(&executable);
+                    content.push_str(&format!("- **Executable:** `{}`", info.path.display()));
+                    content.push_str(if info.exists { "\n" } else { " (not found)\n" });
+                    if let Some(size) = info.size {
+                        content.push_str(&format!("- **Size:** {} bytes\n", size));
+                    }
+                    if let Some(modified) = info.modified {
+                        content.push_str(&format!("- **Modified:** {}\n", modified.format("%Y-%m-%d %H:%M:%S")));
+                    }
+                    if let Some(ref sha256) = info.sha256 {
+                        content.push_str(&format!("- **SHA-256:** `{}`\n", sha256));
+                    }
+                    content.push_str(&format!(
+                        "- **Publisher:** {}\n",
+                        info.company.as_deref().unwrap_or("Unknown")
+                    ));
+                }
+                None => content.push_str("- **Executable:** could not be resolved\n"),
+            }
+
+            content.push_str("\n### Signals\n\n");
+            for flag in &entry.flags {
+                content.push_str(&format!("- **{}:** {}\n", flag, flag.explanation()));
+            }
+            if let Some(ref classification) = entry.classification {
+                content.push_str(&format!("- **Classification:** {}\n", classification));
+            }
+
+            content.push_str(&format!(
+                "\n### Recommended action\n\n{}\n\n",
+                Self::recommended_action(entry)
+            ));
+        }
+
+        std::fs::write(&file_path, content)
+            .with_context(|| format!("Failed to write incident report: {:?}", file_path))?;
+
+        Ok(file_path)
+    }
+
+    fn recommended_action(entry: &StartupEntry) -> &'static str {
+        if entry.has_flag(EntryFlag::ImpersonatesSystemService) {
+            "Investigate before anything else: check the file's hash and publisher against a trusted source. \
+             If it is not expected, disable the service, quarantine the binary and scan the machine."
+        } else if entry.has_flag(EntryFlag::DoubleExtension)
+            || entry.has_flag(EntryFlag::MismatchedExtension)
+        {
+            "Treat as likely malicious: disable the entry, quarantine the file and check its hash \
+             against a malware database before running it again."
+        } else if entry.has_flag(EntryFlag::RemovableDrive) {
+            "Confirm the removable drive is supposed to launch software at boot; otherwise disable the entry."
+        } else if entry.has_flag(EntryFlag::MissingTarget) {
+            "The program is gone. Remove the leftover entry unless it is about to be reinstalled."
+        } else {
+            "The target is unreachable. If the drive is gone for good, remove the entry; otherwise reconnect it."
+        }
+    }
 }
 
+const HTML_STYLE: &str = r#"<style>
+body { font-family: "Segoe UI", Arial, sans-serif; margin: 2em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+h2 { margin-top: 1.6em; border-bottom: 1px solid #ccc; padding-bottom: 0.2em; }
+.meta { color: #666; margin-top: 0; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9em; }
+th, td { border: 1px solid #ddd; padding: 6px 8px; text-align: left; vertical-align: top; }
+th { background: #f0f0f0; cursor: pointer; user-select: none; }
+th:hover { background: #e0e0e0; }
+tr.enabled td { background: #eef8ee; }
+tr.disabled td { background: #fbeeee; color: #777; }
+code { word-break: break-all; }
+</style>
+"#;
+
+// Clicking a header sorts its table by that column, clicking again reverses
+const HTML_SCRIPT: &str = r#"<script>
+function sortTable(header) {
+  var table = header.closest("table");
+  var body = table.tBodies[0];
+  var column = header.cellIndex;
+  var ascending = header.dataset.order !== "asc";
+  var rows = Array.prototype.slice.call(body.rows);
+  rows.sort(function (a, b) {
+    var x = a.cells[column].textContent.toLowerCase();
+    var y = b.cells[column].textContent.toLowerCase();
+    return ascending ? x.localeCompare(y) : y.localeCompare(x);
+  });
+  rows.forEach(function (row) { body.appendChild(row); });
+  Array.prototype.forEach.call(table.tHead.rows[0].cells, function (th) { delete th.dataset.order; });
+  header.dataset.order = ascending ? "asc" : "desc";
+}
+</script>
+"#;
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,128 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::fs;
+use std::path::{Path, PathBuf};
+use windows::{
+    core::{HSTRING, PCWSTR},
+    Win32::Security::Cryptography::{BCryptHash, BCRYPT_SHA256_ALG_HANDLE},
+    Win32::Storage::FileSystem::{GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW},
+};
+
+/// Facts about an entry's executable on disk, used to make reports actionable.
+#[derive(Debug, Clone)]
+pub struct FileInfo {
+    pub path: PathBuf,
+    pub exists: bool,
+    pub size: Option<u64>,
+    pub modified: Option<DateTime<Local>>,
+    pub sha256: Option<String>,
+    pub company: Option<String>,
+}
+
+impl FileInfo {
+    /// Collects what can be read about `path`; missing pieces are left empty.
+    pub fn inspect(path: &Path) -> Self {
+        let mut info = Self::inspect_unhashed(path);
+        if info.exists {
+            info.sha256 = sha256_file(path)
+                .map_err(|e| log::warn!("Failed to hash {:?}: {}", path, e))
+                .ok();
+        }
+        info
+    }
+
+    /// Like `inspect`, but leaves `sha256` empty, since hashing a large
+    /// file takes a while.
+    pub fn inspect_unhashed(path: &Path) -> Self {
+        let metadata = fs::metadata(path).ok();
+        let exists = metadata.as_ref().map(|m| m.is_file()).unwrap_or(false);
+
+        Self {
+            path: path.to_path_buf(),
+            exists,
+            size: metadata.as_ref().map(|m| m.len()),
+            modified: metadata
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .map(DateTime::<Local>::from),
+            sha256: None,
+            company: if exists { company_name(path) } else { None },
+        }
+    }
+}
+
+/// Hex-encoded SHA-256 of a file's contents.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let data = fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let mut digest = [0u8; 32];
+
+    unsafe { BCryptHash(BCRYPT_SHA256_ALG_HANDLE, None, &data, &mut digest) }
+        .ok()
+        .context("Failed to compute SHA-256")?;
+
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// The CompanyName from the file's version resource, if it has one.
+pub fn company_name(path: &Path) -> Option<String> {
+    let file_name = HSTRING::from(path.as_os_str());
+
+    unsafe {
+        let size = GetFileVersionInfoSizeW(&file_name, None);
+        if size == 0 {
+            return None;
+        }
+
+        let mut data = vec![0u8; size as usize];
+        GetFileVersionInfoW(&file_name, 0, size, data.as_mut_ptr() as *mut _).ok()?;
+
+        // The first translation tells which string table to read
+        let translation = query_value(&data, "\\VarFileInfo\\Translation")?;
+        if translation.len() < 4 {
+            return None;
+        }
+        let language = u16::from_le_bytes([translation[0], translation[1]]);
+        let code_page = u16::from_le_bytes([translation[2], translation[3]]);
+
+        let company = query_value(
+            &data,
+            &format!("\\StringFileInfo\\{:04x}{:04x}\\CompanyName", language, code_page),
+        )?;
+        let wide: Vec<u16> = company
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .take_while(|&c| c != 0)
+            .collect();
+
+        let company = String::from_utf16_lossy(&wide).trim().to_string();
+        if company.is_empty() {
+            None
+        } else {
+            Some(company)
+        }
+    }
+}
+
+unsafe fn query_value<'a>(data: &'a [u8], sub_block: &str) -> Option<&'a [u8]> {
+    let sub_block = HSTRING::from(sub_block);
+    let mut buffer = std::ptr::null_mut();
+    let mut len = 0u32;
+
+    if !VerQueryValueW(
+        data.as_ptr() as *const _,
+        PCWSTR(sub_block.as_ptr()),
+        &mut buffer,
+        &mut len,
+    )
+    .as_bool()
+        || buffer.is_null()
+    {
+        return None;
+    }
+
+    // String values report their length in characters, binary ones in bytes;
+    // reading whole UTF-16 units is safe for both as the data is NUL-terminated
+    let start = buffer as usize - data.as_ptr() as usize;
+    let end = (start + len as usize * 2).min(data.len());
+    data.get(start..end)
+}
@@ -1,20 +1,47 @@
-use crate::models::{StartupEntry, StartupSource};
+use crate::models::{EntryFlag, Severity, StartupEntry, StartupSource};
+use crate::risk::RiskLevel;
+use crate::signature;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use regex::{Regex, RegexBuilder};
+
+/// Minimum fuzzy score per search character. Skim awards about 16 per
+/// matched character and subtracts for gaps, so this drops matches whose
+/// characters are scattered across the whole string.
+const FUZZY_MIN_SCORE_PER_CHAR: i64 = 8;
 
 #[derive(Debug, Clone)]
 pub struct Filter {
     pub search_term: Option<String>,
+    /// Rank search results by fuzzy score instead of requiring a substring.
+    pub fuzzy: bool,
+    /// Case-insensitive pattern that replaces the search term when set.
+    pub regex: Option<Regex>,
     pub source_filter: Option<Vec<StartupSource>>,
     pub enabled_only: Option<bool>,
     pub disabled_only: Option<bool>,
+    pub flag_filter: Option<EntryFlag>,
+    pub unsigned_only: Option<bool>,
+    /// Floor on how worrying an entry's flags are.
+    pub min_severity: Severity,
+    /// Floor on the signature and location heuristic. Independent of
+    /// `min_severity`: an entry is shown only if it meets both.
+    pub min_risk: RiskLevel,
 }
 
 impl Default for Filter {
     fn default() -> Self {
         Self {
             search_term: None,
+            fuzzy: true,
+            regex: None,
             source_filter: None,
             enabled_only: None,
             disabled_only: None,
+            flag_filter: None,
+            unsigned_only: None,
+            min_severity: Severity::None,
+            min_risk: RiskLevel::Low,
         }
     }
 }
@@ -29,6 +56,24 @@ impl Filter {
         self
     }
 
+    pub fn with_fuzzy(mut self, fuzzy: bool) -> Self {
+        self.fuzzy = fuzzy;
+        self
+    }
+
+    /// Matches name, command and description against a regular expression,
+    /// ignoring case. Fails when the pattern doesn't compile.
+    pub fn with_regex(mut self, pattern: &str) -> Result<Self, regex::Error> {
+        self.regex = Some(RegexBuilder::new(pattern).case_insensitive(true).build()?);
+        Ok(self)
+    }
+
+    /// Whether `apply` returns entries ranked by match quality, which a
+    /// later sort would throw away.
+    pub fn ranks_results(&self) -> bool {
+        self.fuzzy && self.regex.is_none() && self.search_term.is_some()
+    }
+
     pub fn with_source(mut self, sources: Vec<StartupSource>) -> Self {
         self.source_filter = Some(sources);
         self
@@ -46,12 +91,60 @@ impl Filter {
         self
     }
 
+    /// Whether entries from `source` pass the source filter.
+    pub fn includes_source(&self, source: &StartupSource) -> bool {
+        self.source_filter
+            .as_ref()
+            .is_none_or(|sources| sources.contains(source))
+    }
+
+    /// Adds or drops one source. Selecting every source clears the filter.
+    pub fn toggle_source(&mut self, source: &StartupSource) {
+        let mut sources = self
+            .source_filter
+            .take()
+            .unwrap_or_else(|| StartupSource::ALL.to_vec());
+        match sources.iter().position(|s| s == source) {
+            Some(pos) => {
+                sources.remove(pos);
+            }
+            None => sources.push(source.clone()),
+        }
+        if sources.len() < StartupSource::ALL.len() {
+            self.source_filter = Some(sources);
+        }
+    }
+
+    /// Steps through all entries, enabled only and disabled only.
+    pub fn cycle_status(&mut self) {
+        (self.enabled_only, self.disabled_only) = match (self.enabled_only, self.disabled_only) {
+            (Some(true), _) => (None, Some(true)),
+            (_, Some(true)) => (None, None),
+            _ => (Some(true), None),
+        };
+    }
+
     pub fn apply(&self, entries: &[StartupEntry]) -> Vec<StartupEntry> {
-        entries
-            .iter()
-            .filter(|entry| {
-                // Search term filter
-                if let Some(ref term) = self.search_term {
+        let matches = entries.iter().filter(|entry| self.matches(entry));
+
+        if let Some(ref regex) = self.regex {
+            return matches
+                .filter(|entry| {
+                    regex.is_match(&entry.name)
+                        || regex.is_match(&entry.command)
+                        || entry.description.as_ref().is_some_and(|d| regex.is_match(d))
+                })
+                .cloned()
+                .collect();
+        }
+
+        let Some(ref term) = self.search_term else {
+            return matches.cloned().collect();
+        };
+
+        if !self.fuzzy {
+            return matches
+                .filter(|entry| {
                     let name_match = entry.name.to_lowercase().contains(term);
                     let command_match = entry.command.to_lowercase().contains(term);
                     let desc_match = entry
@@ -59,42 +152,135 @@ impl Filter {
                         .as_ref()
                         .map(|d| d.to_lowercase().contains(term))
                         .unwrap_or(false);
-                    if !name_match && !command_match && !desc_match {
-                        return false;
-                    }
-                }
-
-                // Source filter
-                if let Some(ref sources) = self.source_filter {
-                    if !sources.contains(&entry.source) {
-                        return false;
-                    }
-                }
-
-                // Enabled/Disabled filter
-                if let Some(true) = self.enabled_only {
-                    if !entry.enabled {
-                        return false;
-                    }
-                }
-
-                if let Some(true) = self.disabled_only {
-                    if entry.enabled {
-                        return false;
-                    }
-                }
-
-                true
+                    name_match || command_match || desc_match
+                })
+                .cloned()
+                .collect();
+        }
+
+        // Best score across name, command and description, highest first
+        let matcher = SkimMatcherV2::default();
+        let min_score = term.chars().count() as i64 * FUZZY_MIN_SCORE_PER_CHAR;
+        let mut scored: Vec<(i64, &StartupEntry)> = matches
+            .filter_map(|entry| {
+                [Some(&entry.name), Some(&entry.command), entry.description.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|text| matcher.fuzzy_match(text, term))
+                    .max()
+                    .filter(|score| *score >= min_score)
+                    .map(|score| (score, entry))
             })
-            .cloned()
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, entry)| entry.clone()).collect()
+    }
+
+    /// Byte ranges of `text` matched by the search, for highlighting: every
+    /// regex match, every substring occurrence, or the characters a fuzzy
+    /// match used. Empty when there is no search or `text` doesn't match.
+    pub fn match_ranges(&self, text: &str) -> Vec<(usize, usize)> {
+        if let Some(ref regex) = self.regex {
+            return regex
+                .find_iter(text)
+                .filter(|m| !m.is_empty())
+                .map(|m| (m.start(), m.end()))
+                .collect();
+        }
+
+        let Some(ref term) = self.search_term else {
+            return Vec::new();
+        };
+        if term.is_empty() {
+            return Vec::new();
+        }
+
+        if !self.fuzzy {
+            // Offsets only carry over when lowercasing keeps every byte in place
+            let lower = text.to_lowercase();
+            if lower.len() != text.len() {
+                return Vec::new();
+            }
+            return lower
+                .match_indices(term.as_str())
+                .map(|(start, m)| (start, start + m.len()))
+                .collect();
+        }
+
+        let min_score = term.chars().count() as i64 * FUZZY_MIN_SCORE_PER_CHAR;
+        let Some((score, indices)) = SkimMatcherV2::default().fuzzy_indices(text, term) else {
+            return Vec::new();
+        };
+        if score < min_score {
+            return Vec::new();
+        }
+        // The matcher reports character positions
+        text.char_indices()
+            .enumerate()
+            .filter(|(i, _)| indices.contains(i))
+            .map(|(_, (start, c))| (start, start + c.len_utf8()))
             .collect()
     }
 
+    /// Every criterion except the search term.
+    fn matches(&self, entry: &StartupEntry) -> bool {
+        // Source filter
+        if let Some(ref sources) = self.source_filter {
+            if !sources.contains(&entry.source) {
+                return false;
+            }
+        }
+
+        // Enabled/Disabled filter
+        if let Some(true) = self.enabled_only {
+            if !entry.enabled {
+                return false;
+            }
+        }
+
+        if let Some(true) = self.disabled_only {
+            if entry.enabled {
+                return false;
+            }
+        }
+
+        // Diagnostic flag filter; acknowledged entries no longer count
+        if let Some(flag) = self.flag_filter {
+            if !entry.has_flag(flag) || entry.acknowledged {
+                return false;
+            }
+        }
+
+        // Signature filter; only checks files once another criterion let them through
+        if let Some(true) = self.unsigned_only {
+            if signature::entry_status(entry).is_signed() {
+                return false;
+            }
+        }
+
+        // Severity floor
+        if entry.severity() < self.min_severity {
+            return false;
+        }
+
+        // Risk floor; entries not yet assessed count as Low
+        if entry.risk.as_ref().map_or(RiskLevel::Low, |r| r.level) < self.min_risk {
+            return false;
+        }
+
+        true
+    }
+
     pub fn clear(&mut self) {
         self.search_term = None;
+        self.regex = None;
         self.source_filter = None;
         self.enabled_only = None;
         self.disabled_only = None;
+        self.flag_filter = None;
+        self.unsigned_only = None;
+        self.min_severity = Severity::None;
+        self.min_risk = RiskLevel::Low;
     }
 }
 
@@ -112,6 +298,19 @@ pub fn sort_entries(entries: &mut [StartupEntry], sort_by: SortBy) {
         SortBy::Command => {
             entries.sort_by(|a, b| a.command.cmp(&b.command));
         }
+        SortBy::Impact => {
+            entries.sort_by_key(|e| std::cmp::Reverse(e.impact)); // Highest first
+        }
+        SortBy::Risk => {
+            entries.sort_by_key(|e| std::cmp::Reverse(e.risk.as_ref().map(|r| r.level))); // Highest first
+        }
+        SortBy::Signature => {
+            // Invalid and unsigned first, then signed entries by signer
+            entries.sort_by_cached_key(|e| {
+                let status = signature::entry_status(e);
+                (status.rank(), status.to_string().to_lowercase())
+            });
+        }
     }
 }
 
@@ -121,5 +320,100 @@ pub enum SortBy {
     Source,
     Status,
     Command,
+    Signature,
+    Impact,
+    Risk,
 }
 
+impl SortBy {
+    /// Parses a `default_sort` config value, falling back to sorting by name.
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "source" => SortBy::Source,
+            "status" => SortBy::Status,
+            "command" => SortBy::Command,
+            "signature" => SortBy::Signature,
+            "impact" => SortBy::Impact,
+            "risk" => SortBy::Risk,
+            _ => SortBy::Name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    None,
+    Source,
+    Directory,
+}
+
+impl GroupBy {
+    pub fn next(self) -> Self {
+        match self {
+            GroupBy::None => GroupBy::Source,
+            GroupBy::Source => GroupBy::Directory,
+            GroupBy::Directory => GroupBy::None,
+        }
+    }
+}
+
+/// Label of the group an entry belongs to, or `None` when grouping is off.
+pub fn group_label(entry: &StartupEntry, group_by: GroupBy) -> Option<String> {
+    match group_by {
+        GroupBy::None => None,
+        GroupBy::Source => Some(entry.source.to_string()),
+        GroupBy::Directory => Some(
+            entry.target_path()
+                .and_then(|path| path.parent().map(|dir| dir.display().to_string()))
+                .filter(|dir| !dir.is_empty())
+                .unwrap_or_else(|| "<unknown directory>".to_string()),
+        ),
+    }
+}
+
+/// Clusters already-sorted entries by group, keeping the sort order inside each group.
+pub fn group_entries(entries: &mut [StartupEntry], group_by: GroupBy) {
+    if group_by != GroupBy::None {
+        entries.sort_by_cached_key(|e| group_label(e, group_by).map(|label| label.to_lowercase()));
+    }
+}
+
+/// Folds entries that launch the same executable from different sources
+/// into the first of them, which lists the others in `duplicates`. Entries
+/// whose target can't be resolved are left alone.
+pub fn merge_duplicates(entries: Vec<StartupEntry>) -> Vec<StartupEntry> {
+    let mut merged: Vec<StartupEntry> = Vec::with_capacity(entries.len());
+    let mut rows: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+
+    for entry in entries {
+        let Some(key) = entry.target_path().map(|p| p.to_string_lossy().to_lowercase()) else {
+            merged.push(entry);
+            continue;
+        };
+        match rows.get(&key) {
+            Some(&row)
+                if merged[row].source != entry.source
+                    && merged[row].duplicates.iter().all(|d| d.source != entry.source) =>
+            {
+                merged[row].duplicates.push(entry);
+            }
+            _ => {
+                rows.entry(key).or_insert(merged.len());
+                merged.push(entry);
+            }
+        }
+    }
+    merged
+}
+
+/// Undoes `merge_duplicates`, giving every source its own entry again.
+pub fn flatten_duplicates(entries: &[StartupEntry]) -> Vec<StartupEntry> {
+    entries
+        .iter()
+        .flat_map(|entry| {
+            let mut row = entry.clone();
+            let duplicates = std::mem::take(&mut row.duplicates);
+            std::iter::once(row).chain(duplicates)
+        })
+        .collect()
+}
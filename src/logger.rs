@@ -1,12 +1,225 @@
+use crate::config::ConfigManager;
 use anyhow::{Context, Result};
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SecondsFormat};
+use serde::{Deserialize, Serialize};
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
+const LOG_FILE_PREFIX: &str = "deepboot_";
+const LOG_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Size at which a day's log rolls over to `deepboot_YYYYMMDD_N.log`.
+const MAX_LOG_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// How records are written: free-form text lines or one JSON object per line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ActionLogger {
-    log_file_path: std::path::PathBuf,
+    log_dir: PathBuf,
+    format: LogFormat,
+}
+
+/// Something worth a log record, independent of the output format.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum LogEvent {
+    Action {
+        action: String,
+        entry: String,
+        success: bool,
+        error: Option<String>,
+    },
+    Scan {
+        source: String,
+        count: usize,
+    },
+    Batch {
+        action: String,
+        total: usize,
+        successful: usize,
+        failed: usize,
+    },
+    Export {
+        format: String,
+        path: String,
+        count: usize,
+    },
+    DryRun {
+        action: String,
+        entry: String,
+        operation: String,
+    },
+    Detected {
+        change: String,
+        entry: String,
+        source: String,
+        command: String,
+    },
+}
+
+impl LogEvent {
+    /// The record as written in text mode, after the timestamp.
+    fn text(&self) -> String {
+        match self {
+            LogEvent::Action { action, entry, success, error } => {
+                let mut text = format!(
+                    "{} - Entry: '{}' - Status: {}",
+                    action,
+                    entry,
+                    if *success { "SUCCESS" } else { "FAILED" }
+                );
+                if let Some(err) = error {
+                    text.push_str(&format!(" - Error: {}", err));
+                }
+                text
+            }
+            LogEvent::Scan { source, count } => {
+                format!("SCAN - Source: {} - Found: {} entries", source, count)
+            }
+            LogEvent::Batch { action, total, successful, failed } => format!(
+                "BATCH {} - Total: {} - Successful: {} - Failed: {}",
+                action, total, successful, failed
+            ),
+            LogEvent::Export { format, path, count } => {
+                format!("EXPORT {} - Path: {} - Entries: {}", format, path, count)
+            }
+            LogEvent::DryRun { action, entry, operation } => {
+                format!("DRY-RUN {} - Entry: '{}' - Would: {}", action, entry, operation)
+            }
+            LogEvent::Detected { change, entry, source, command } => format!(
+                "WATCH {} - Entry: '{}' - Source: {} - Command: {}",
+                change, entry, source, command
+            ),
+        }
+    }
+}
+
+/// A record as written in JSON Lines mode.
+#[derive(Serialize, Deserialize)]
+struct JsonLogLine {
+    timestamp: String, // RFC 3339 with the local offset
+    #[serde(flatten)]
+    event: LogEvent,
+}
+
+/// A single parsed line from one of the daily log files.
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub timestamp: NaiveDateTime,
+    /// First word of the record: the action name, `SCAN`, `BATCH`, `EXPORT`,
+    /// `DRY-RUN` or `WATCH`.
+    pub action: String,
+    pub entry_name: Option<String>,
+    /// `None` for records without a per-entry status (scans, batch summaries).
+    pub success: Option<bool>,
+    pub message: String,
+}
+
+impl LogRecord {
+    pub fn parse(line: &str) -> Option<Self> {
+        // JSON records are read through their text form so both parse alike
+        if line.trim_start().starts_with('{') {
+            let record: JsonLogLine = serde_json::from_str(line).ok()?;
+            let timestamp = DateTime::parse_from_rfc3339(&record.timestamp).ok()?;
+            return Self::parse(&format!(
+                "[{}] {}",
+                timestamp.naive_local().format(LOG_TIMESTAMP_FORMAT),
+                record.event.text()
+            ));
+        }
+
+        let rest = line.trim_end().strip_prefix('[')?;
+        let (timestamp, message) = rest.split_once("] ")?;
+        let timestamp = NaiveDateTime::parse_from_str(timestamp, LOG_TIMESTAMP_FORMAT).ok()?;
+        let action = message.split_whitespace().next()?.to_string();
+
+        let entry_name = message
+            .split_once(" - Entry: '")
+            .and_then(|(_, r)| {
+                r.split_once("' - Status: ")
+                    .or_else(|| r.split_once("' - Would: "))
+                    .or_else(|| r.split_once("' - Source: "))
+            })
+            .map(|(name, _)| name.to_string());
+
+        let success = if message.contains(" - Status: SUCCESS") {
+            Some(true)
+        } else if message.contains(" - Status: FAILED") {
+            Some(false)
+        } else {
+            None
+        };
+
+        Some(Self {
+            timestamp,
+            action,
+            entry_name,
+            success,
+            message: message.to_string(),
+        })
+    }
+}
+
+/// Filters applied when reading records back from the log directory.
+#[derive(Debug, Clone, Default)]
+pub struct LogQuery {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub action: Option<String>,
+    pub success: Option<bool>,
+    pub entry_name: Option<String>,
+}
+
+impl LogQuery {
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        if let Some(ref action) = self.action {
+            if !record.action.eq_ignore_ascii_case(action) {
+                return false;
+            }
+        }
+
+        if let Some(success) = self.success {
+            if record.success != Some(success) {
+                return false;
+            }
+        }
+
+        if let Some(ref name) = self.entry_name {
+            let name = name.to_lowercase();
+            let matches = record
+                .entry_name
+                .as_ref()
+                .map(|n| n.to_lowercase().contains(&name))
+                .unwrap_or(false);
+            if !matches {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn includes_date(&self, date: NaiveDate) -> bool {
+        self.since.map(|since| date >= since).unwrap_or(true)
+            && self.until.map(|until| date <= until).unwrap_or(true)
+    }
 }
 
 impl ActionLogger {
@@ -21,12 +234,108 @@ impl ActionLogger {
                 .context("Failed to create log directory")?;
         }
 
-        let log_file_path = log_dir.join(format!("deepboot_{}.log", 
-            Local::now().format("%Y%m%d")));
+        // Logging must keep working with an unreadable config, so fall back to defaults
+        let config = ConfigManager::new().ok().map(|manager| manager.get().clone());
+        let format = config
+            .as_ref()
+            .and_then(|config| LogFormat::from_key(&config.log_format))
+            .unwrap_or(LogFormat::Text);
 
-        Ok(Self {
-            log_file_path,
-        })
+        let logger = Self { log_dir, format };
+
+        let retention_days = config.map(|config| config.log_retention_days).unwrap_or(30);
+        if retention_days > 0 {
+            if let Err(e) = logger.prune(retention_days) {
+                log::warn!("Failed to prune old logs: {}", e);
+            }
+        }
+
+        Ok(logger)
+    }
+
+    /// Deletes log files dated more than `retention_days` days ago.
+    pub fn prune(&self, retention_days: u32) -> Result<usize> {
+        let cutoff = Local::now().date_naive() - chrono::Duration::days(retention_days as i64);
+        let mut removed = 0;
+
+        for (date, path) in self.list_log_files()? {
+            if date >= cutoff {
+                break;
+            }
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to delete log file: {:?}", path))?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// Today's log file, moving on to the next numbered part once one is full.
+    fn current_log_file(&self) -> PathBuf {
+        let base = format!("{}{}", LOG_FILE_PREFIX, Local::now().format("%Y%m%d"));
+        let mut part = 0;
+        loop {
+            let path = if part == 0 {
+                self.log_dir.join(format!("{}.log", base))
+            } else {
+                self.log_dir.join(format!("{}_{}.log", base, part))
+            };
+            let full = std::fs::metadata(&path)
+                .map(|m| m.len() >= MAX_LOG_FILE_SIZE)
+                .unwrap_or(false);
+            if !full {
+                return path;
+            }
+            part += 1;
+        }
+    }
+
+    pub fn log_dir(&self) -> &Path {
+        &self.log_dir
+    }
+
+    /// Lists the daily log files with the date encoded in their name, oldest
+    /// first; a day's rolled-over parts follow in order.
+    pub fn list_log_files(&self) -> Result<Vec<(NaiveDate, PathBuf)>> {
+        let mut files: Vec<(NaiveDate, u32, PathBuf)> = std::fs::read_dir(&self.log_dir)
+            .context("Failed to read log directory")?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let stem = path.file_stem()?.to_str()?;
+                let name = stem.strip_prefix(LOG_FILE_PREFIX)?;
+                let (date, part) = match name.split_once('_') {
+                    Some((date, part)) => (date, part.parse().ok()?),
+                    None => (name, 0),
+                };
+                let date = NaiveDate::parse_from_str(date, "%Y%m%d").ok()?;
+                Some((date, part, path))
+            })
+            .collect();
+
+        files.sort();
+        Ok(files.into_iter().map(|(date, _, path)| (date, path)).collect())
+    }
+
+    /// Reads and merges every daily log in the query's date range, newest record first.
+    pub fn read_logs(&self, query: &LogQuery) -> Result<Vec<LogRecord>> {
+        let mut records = Vec::new();
+
+        for (date, path) in self.list_log_files()? {
+            if !query.includes_date(date) {
+                continue;
+            }
+
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read log file: {:?}", path))?;
+            records.extend(
+                content
+                    .lines()
+                    .filter_map(LogRecord::parse)
+                    .filter(|record| query.matches(record)),
+            );
+        }
+
+        records.sort_by_key(|record| std::cmp::Reverse(record.timestamp));
+        Ok(records)
     }
 
     fn write_log(&self, message: &str) -> Result<()> {
@@ -38,7 +347,7 @@ impl ActionLogger {
         let mut file = OpenOptions::new()
             .create(true)
             .append(true)
-            .open(&self.log_file_path)
+            .open(self.current_log_file())
             .context("Failed to open log file")?;
         
         file.write_all(message.as_bytes())
@@ -49,39 +358,128 @@ impl ActionLogger {
     }
 
     pub fn log_action(&self, action: &str, entry_name: &str, success: bool, error: Option<&str>) -> Result<()> {
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-        let status = if success { "SUCCESS" } else { "FAILED" };
-        
-        let mut log_entry = format!(
-            "[{}] {} - Entry: '{}' - Status: {}",
-            timestamp, action, entry_name, status
-        );
+        self.write_event(LogEvent::Action {
+            action: action.to_string(),
+            entry: entry_name.to_string(),
+            success,
+            error: error.map(str::to_string),
+        })
+    }
 
-        if let Some(err) = error {
-            log_entry.push_str(&format!(" - Error: {}", err));
-        }
+    pub fn log_scan(&self, source: &str, count: usize) -> Result<()> {
+        self.write_event(LogEvent::Scan {
+            source: source.to_string(),
+            count,
+        })
+    }
+
+    pub fn log_export(&self, format: &str, path: &Path, count: usize) -> Result<()> {
+        self.write_event(LogEvent::Export {
+            format: format.to_string(),
+            path: path.display().to_string(),
+            count,
+        })
+    }
 
-        log_entry.push('\n');
-        self.write_log(&log_entry)
+    /// Records an action that was only simulated, with the operation it
+    /// would have performed.
+    pub fn log_dry_run(&self, action: &str, entry_name: &str, operation: &str) -> Result<()> {
+        self.write_event(LogEvent::DryRun {
+            action: action.to_string(),
+            entry: entry_name.to_string(),
+            operation: operation.to_string(),
+        })
     }
 
-    pub fn log_scan(&self, source: &str, count: usize) -> Result<()> {
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-        let log_entry = format!(
-            "[{}] SCAN - Source: {} - Found: {} entries\n",
-            timestamp, source, count
-        );
-        self.write_log(&log_entry)
+    /// Records an entry that watch mode saw appear or get enabled.
+    pub fn log_detected(&self, change: &str, entry_name: &str, source: &str, command: &str) -> Result<()> {
+        self.write_event(LogEvent::Detected {
+            change: change.to_string(),
+            entry: entry_name.to_string(),
+            source: source.to_string(),
+            command: command.to_string(),
+        })
     }
 
     pub fn log_batch_action(&self, action: &str, count: usize, success_count: usize) -> Result<()> {
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-        let log_entry = format!(
-            "[{}] BATCH {} - Total: {} - Successful: {} - Failed: {}\n",
-            timestamp, action, count, success_count, count - success_count
-        );
-        self.write_log(&log_entry)
+        self.write_event(LogEvent::Batch {
+            action: action.to_string(),
+            total: count,
+            successful: success_count,
+            failed: count - success_count,
+        })
+    }
+
+    fn write_event(&self, event: LogEvent) -> Result<()> {
+        let now = Local::now();
+        let line = match self.format {
+            LogFormat::Text => format!("[{}] {}\n", now.format(LOG_TIMESTAMP_FORMAT), event.text()),
+            LogFormat::Json => {
+                let line = JsonLogLine {
+                    timestamp: now.to_rfc3339_opts(SecondsFormat::Secs, false),
+                    event,
+                };
+                format!(
+                    "{}\n",
+                    serde_json::to_string(&line).context("Failed to serialize log record")?
+                )
+            }
+        };
+        self.write_log(&line)
     }
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A log directory of its own under the system temp dir, removed on drop.
+    struct TempLogDir(PathBuf);
+
+    impl TempLogDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("deepboot_{}_{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        /// Creates a log file dated `days_ago` days back, or one of that day's
+        /// rolled-over parts.
+        fn add_log(&self, days_ago: i64, part: u32) -> PathBuf {
+            let date = (Local::now().date_naive() - chrono::Duration::days(days_ago)).format("%Y%m%d");
+            let name = match part {
+                0 => format!("{}{}.log", LOG_FILE_PREFIX, date),
+                part => format!("{}{}_{}.log", LOG_FILE_PREFIX, date, part),
+            };
+            let path = self.0.join(name);
+            std::fs::write(&path, "").unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempLogDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn prune_deletes_only_logs_older_than_the_retention() {
+        let dir = TempLogDir::new("prune_test");
+        let logger = ActionLogger { log_dir: dir.0.clone(), format: LogFormat::Text };
+
+        let kept = [dir.add_log(0, 0), dir.add_log(29, 0), dir.add_log(30, 0), dir.add_log(30, 1)];
+        let removed = [dir.add_log(31, 0), dir.add_log(31, 1), dir.add_log(400, 0)];
+        // Files that aren't daily logs are never touched
+        let unrelated = dir.0.join("notes.txt");
+        std::fs::write(&unrelated, "").unwrap();
+
+        assert_eq!(logger.prune(30).unwrap(), removed.len());
+        assert!(kept.iter().all(|path| path.exists()));
+        assert!(removed.iter().all(|path| !path.exists()));
+        assert!(unrelated.exists());
+    }
+}
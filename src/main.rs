@@ -1,17 +1,40 @@
+mod acknowledgments;
 mod actions;
 mod backup;
+mod boot_impact;
 mod batch;
+mod cli;
+mod clipboard;
+mod com;
 mod config;
+mod diagnostics;
+mod doctor;
 mod export;
+mod file_info;
 mod filter;
 mod logger;
 mod models;
+mod monitor;
+mod paths;
+mod privileges;
+mod profile;
 mod registry;
+mod risk;
+mod scanner;
 mod services;
+mod signature;
+mod startup_folder;
 mod stats;
+mod strings;
+mod system_services;
 mod task_scheduler;
+mod theme;
 mod tui;
+mod verifier;
+mod virustotal;
+mod warnings;
 mod whitelist;
+mod wmi;
 
 use anyhow::{Context, Result};
 use crossterm::{
@@ -21,114 +44,179 @@ use crossterm::{
 };
 // Action and StartupEntry are used in other modules, not directly here
 use ratatui::prelude::*;
-use std::io;
+use std::io::{self, IsTerminal};
+use std::process::ExitCode;
 use tui::App;
 
-fn main() -> Result<()> {
-    // Initialize logger
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
-        .init();
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::from(cli::ExitStatus::of(&e).code())
+        }
+    }
+}
 
-    // Load configuration
-    let config_manager = config::ConfigManager::new()?;
+fn run() -> Result<()> {
+    // Initialize logger; warnings are also kept for the TUI
+    warnings::init();
+
+    // Non-interactive subcommands run and exit without scanning
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let launch_options = cli::LaunchOptions::extract(&mut args)?;
+    if let Some(command) = cli::CliCommand::parse(&args)? {
+        return command.run(&launch_options);
+    }
+
+    // Load configuration; command-line options override it for this run only
+    let mut config_manager = config::ConfigManager::new()?;
+    launch_options.apply(config_manager.get_mut());
     let config = config_manager.get();
+    strings::set_language(&config.language);
+    theme::set_theme(&config.theme);
+    let quiet = launch_options.quiet;
+    // The startup output, kept for the TUI's stats view
+    let mut scan_summary = Vec::new();
 
-    println!("DeepBoot Pro - Advanced Startup Manager");
-    println!("Scanning startup entries...");
-    println!("This may take a few moments...\n");
+    if !quiet {
+        println!("DeepBoot Pro - Advanced Startup Manager");
+    }
 
     // Initialize logger
     let action_logger = logger::ActionLogger::new()?;
 
-    // Scan all startup locations
-    let mut all_entries = Vec::new();
-
-    // Scan Task Scheduler
-    println!("Scanning Task Scheduler...");
-    match task_scheduler::TaskSchedulerScanner::scan() {
-        Ok(entries) => {
-            println!("  Found {} entries", entries.len());
-            let _ = action_logger.log_scan("Task Scheduler", entries.len());
-            all_entries.extend(entries);
-        }
-        Err(e) => {
-            eprintln!("  Warning: Failed to scan Task Scheduler: {}", e);
+    // Scan all startup locations, resuming an interrupted scan if enabled
+    let scan_progress = if config.resume_interrupted_scan {
+        match scanner::ScanProgress::new(config.resume_max_age_minutes) {
+            Ok(progress) => Some(progress),
+            Err(e) => {
+                warn(format!("Scan progress unavailable: {}", e));
+                None
+            }
         }
-    }
-
-    // Scan Registry
-    println!("Scanning Registry...");
-    match registry::RegistryScanner::scan_all() {
-        Ok(entries) => {
-            println!("  Found {} entries", entries.len());
-            let _ = action_logger.log_scan("Registry", entries.len());
-            all_entries.extend(entries);
+    } else {
+        None
+    };
+    let scan_options = scanner::ScanOptions::from_config(config);
+    let cached = if config.scan_cache_seconds > 0 && !launch_options.no_cache {
+        scanner::ScanCache::load(config.scan_cache_seconds, &scan_options)
+    } else {
+        None
+    };
+    let mut all_entries = match cached {
+        Some((timestamp, entries)) => {
+            report(
+                &mut scan_summary,
+                quiet,
+                format!(
+                    "  Loaded {} entries from cache (scanned {})",
+                    entries.len(),
+                    timestamp.format("%Y-%m-%d %H:%M:%S")
+                ),
+            );
+            entries
         }
-        Err(e) => {
-            eprintln!("  Warning: Failed to scan Registry: {}", e);
+        None => {
+            let entries = scan_with_progress(
+                &action_logger,
+                scan_progress.as_ref(),
+                &scan_options,
+                quiet,
+                &mut scan_summary,
+            )?;
+            if config.scan_cache_seconds > 0 {
+                if let Err(e) = scanner::ScanCache::save(&entries, &scan_options) {
+                    warn(format!("Failed to cache scan: {}", e));
+                }
+            }
+            entries
         }
-    }
+    };
 
-    // Scan Services
-    println!("Scanning Services...");
-    match services::ServicesScanner::scan() {
-        Ok(entries) => {
-            println!("  Found {} entries", entries.len());
-            let _ = action_logger.log_scan("Services", entries.len());
-            all_entries.extend(entries);
-        }
-        Err(e) => {
-            eprintln!("  Warning: Failed to scan Services: {}", e);
-        }
+    // Flag entries whose target location looks suspicious
+    diagnostics::Diagnostics::annotate(&mut all_entries);
+
+    // Rate how much each entry slowed recent sign-ins
+    boot_impact::BootImpact::annotate(&mut all_entries);
+
+    // Risk ratings need each executable's signature; the TUI verifies
+    // them in the background instead of holding up startup
+
+    // Mark flagged entries the user has already reviewed
+    match acknowledgments::AcknowledgmentStore::new() {
+        Ok(store) => store.annotate(&mut all_entries),
+        Err(e) => warn(format!("Failed to load acknowledgments: {}", e)),
     }
 
-    // Apply whitelist filter if configured
+    // Apply whitelist filter if configured; the TUI keeps every entry so the
+    // filter can be toggled during the session
     let whitelist_manager = whitelist::WhitelistManager::new()?;
-    if !config.show_whitelisted {
-        let original_count = all_entries.len();
-        all_entries = whitelist_manager.filter_whitelisted(all_entries);
-        if original_count != all_entries.len() {
-            println!("  Filtered {} whitelisted entries", original_count - all_entries.len());
+    let visible_entries = if config.show_whitelisted {
+        all_entries.clone()
+    } else {
+        let visible = whitelist_manager.filter_whitelisted(all_entries.clone());
+        if visible.len() != all_entries.len() {
+            report(
+                &mut scan_summary,
+                quiet,
+                format!("  Filtered {} whitelisted entries", all_entries.len() - visible.len()),
+            );
         }
-    }
+        visible
+    };
 
     // Create backup if configured
     if config.auto_backup {
         let backup_manager = backup::BackupManager::new()?;
-        match backup_manager.create_backup(&all_entries) {
-            Ok(path) => {
-                println!("  Backup created: {:?}", path);
+        match backup_manager.create_auto_backup(&visible_entries, &config.backup_scope, config.max_backups) {
+            Ok(Some(path)) => {
+                report(&mut scan_summary, quiet, format!("  Backup created: {:?}", path));
+            }
+            Ok(None) => {
+                report(&mut scan_summary, quiet, "  No changes since the last backup, skipping".to_string());
             }
             Err(e) => {
-                eprintln!("  Warning: Failed to create backup: {}", e);
+                warn(format!("Failed to create backup: {}", e));
             }
         }
     }
 
+    // Export the scan if configured
+    if let Some(ref format) = config.auto_export {
+        match export::Exporter::export_as(format, &visible_entries, None, config.export_hashes) {
+            Ok(path) => report(&mut scan_summary, quiet, format!("  Exported to: {:?}", path)),
+            Err(e) => warn(format!("Failed to export scan: {}", e)),
+        }
+    }
+
     // Generate statistics
-    let stats = stats::ScanStatistics::from_entries(&all_entries);
-    println!("\n{}", stats.get_summary());
+    if !quiet {
+        let stats = stats::ScanStatistics::from_entries(&visible_entries, &config.footprint, &whitelist_manager)
+            .with_hidden_whitelisted(all_entries.len() - visible_entries.len());
+        println!("\n{}", stats.get_summary());
+
+        println!("\nTotal entries found: {}", visible_entries.len());
+    }
 
-    println!("\nTotal entries found: {}", all_entries.len());
-    
     if all_entries.is_empty() {
-        println!("No startup entries found. Exiting...");
+        if !quiet {
+            println!("No startup entries found. Exiting...");
+        }
         return Ok(());
     }
-    
-    println!("Press Enter to continue to the TUI...");
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
+    // Wait so the summary can be read if asked to, unless there is nobody
+    // to press Enter: piped or redirected stdin would block or skip it anyway.
+    // Otherwise the stats view shows it.
+    if config.pause_before_tui && !quiet && io::stdin().is_terminal() {
+        println!("Press Enter to continue to the TUI...");
 
-    // Setup terminal
-    enable_raw_mode().context("Failed to enable raw mode")?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
-        .context("Failed to enter alternate screen")?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+    }
 
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend).context("Failed to create terminal")?;
+    let mut terminal = setup_terminal()?;
 
     // Create app with all the managers
     // Note: We need to pass config_manager as mutable, but App will handle it
@@ -137,12 +225,32 @@ fn main() -> Result<()> {
         whitelist_manager,
         action_logger,
         config_manager,
-    );
+    )
+    .with_scan_summary(scan_summary)
+    .with_warnings(warnings::take());
 
     // Run the TUI
     let result = tui::run_app(&mut terminal, app);
 
-    // Restore terminal
+    restore_terminal(&mut terminal)?;
+    result
+}
+
+/// Hands the terminal to the TUI. Log output is held back from stderr until
+/// `restore_terminal`; warnings still reach the TUI's warnings view.
+fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
+    warnings::set_tui_active(true);
+    enable_raw_mode().context("Failed to enable raw mode")?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
+        .context("Failed to enter alternate screen")?;
+
+    let backend = CrosstermBackend::new(stdout);
+    Terminal::new(backend).context("Failed to create terminal")
+}
+
+fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+    warnings::set_tui_active(false);
     disable_raw_mode().context("Failed to disable raw mode")?;
     execute!(
         terminal.backend_mut(),
@@ -150,9 +258,67 @@ fn main() -> Result<()> {
         DisableMouseCapture
     )
     .context("Failed to leave alternate screen")?;
-    terminal.show_cursor().context("Failed to show cursor")?;
+    terminal.show_cursor().context("Failed to show cursor")
+}
 
-    result
+/// Scans behind the TUI's progress screen, then prints the scan's updates
+/// to the console along with the rest of the startup output. When `quiet`
+/// or stdout is no terminal, there is no progress screen and updates are
+/// printed as they come; `quiet` prints only the sources that failed.
+/// Every update but the start of each source is added to `summary`.
+fn scan_with_progress(
+    action_logger: &logger::ActionLogger,
+    progress: Option<&scanner::ScanProgress>,
+    options: &scanner::ScanOptions,
+    quiet: bool,
+    summary: &mut Vec<String>,
+) -> Result<Vec<models::StartupEntry>> {
+    if quiet || !io::stdout().is_terminal() {
+        return Ok(scanner::Scanner::scan_with(action_logger, progress, options, |update| {
+            if !matches!(update, scanner::ScanUpdate::Waiting) {
+                record_update(&update, quiet, summary);
+            }
+        }));
+    }
+
+    let mut terminal = setup_terminal()?;
+    let scanned = tui::run_scan(&mut terminal, action_logger, progress, options);
+    restore_terminal(&mut terminal)?;
+
+    let (entries, updates) = scanned?;
+    for update in &updates {
+        record_update(update, quiet, summary);
+    }
+    Ok(entries)
+}
+
+/// Prints a scan update (only failures when `quiet`) and keeps it for the
+/// stats view; failures are also kept for the warnings view.
+fn record_update(update: &scanner::ScanUpdate, quiet: bool, summary: &mut Vec<String>) {
+    if let scanner::ScanUpdate::Failed(source, e) = update {
+        warnings::push(format!("Failed to scan {}: {}", source, e));
+        update.print();
+    } else if !quiet {
+        update.print();
+    }
+    if !matches!(update, scanner::ScanUpdate::Started(_)) {
+        summary.push(update.to_string().trim().to_string());
+    }
+}
+
+/// Prints a warning and keeps it for the TUI's warnings panel.
+fn warn(message: String) {
+    eprintln!("  Warning: {}", message);
+    warnings::push(message);
+}
+
+/// Prints a line of the startup output, unless `quiet`, and keeps it for
+/// the stats view, since the console is hidden once the TUI opens.
+fn report(summary: &mut Vec<String>, quiet: bool, line: String) {
+    if !quiet {
+        println!("{}", line);
+    }
+    summary.push(line.trim().to_string());
 }
 
 
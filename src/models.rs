@@ -1,7 +1,13 @@
+use crate::paths;
+use crate::risk::Risk;
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum StartupSource {
     TaskScheduler,
     RegistryRun,
@@ -9,6 +15,19 @@ pub enum StartupSource {
     RegistryRunServices,
     RegistryWow6432Node,
     Service,
+    StartupFolder,
+}
+
+impl StartupSource {
+    pub const ALL: &'static [StartupSource] = &[
+        StartupSource::TaskScheduler,
+        StartupSource::RegistryRun,
+        StartupSource::RegistryRunOnce,
+        StartupSource::RegistryRunServices,
+        StartupSource::RegistryWow6432Node,
+        StartupSource::Service,
+        StartupSource::StartupFolder,
+    ];
 }
 
 impl fmt::Display for StartupSource {
@@ -20,17 +39,46 @@ impl fmt::Display for StartupSource {
             StartupSource::RegistryRunServices => write!(f, "Registry (RunServices)"),
             StartupSource::RegistryWow6432Node => write!(f, "Registry (WoW6432Node)"),
             StartupSource::Service => write!(f, "Service"),
+            StartupSource::StartupFolder => write!(f, "Startup Folder"),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StartupEntry {
+    #[serde(default)]
+    pub id: u64, // Unique within a scan, set by `assign_ids`
     pub name: String,
     pub command: String,
     pub source: StartupSource,
     pub enabled: bool,
     pub description: Option<String>,
+    #[serde(default)]
+    pub flags: Vec<EntryFlag>,
+    #[serde(default)]
+    pub scope: EntryScope,
+    #[serde(default)]
+    pub classification: Option<String>, // Why a service was judged third-party
+    #[serde(default)]
+    pub acknowledged: bool, // Flags reviewed and accepted by the user
+    #[serde(default)]
+    pub hosted_dll: Option<String>, // ServiceDll of a svchost-hosted service
+    #[serde(default)]
+    pub delayed_start: bool, // Service set to Automatic (Delayed Start)
+    #[serde(default)]
+    pub user_sid: Option<String>, // HKEY_USERS hive of another account the entry was read from
+    #[serde(default)]
+    pub user_name: Option<String>, // Account name of that SID, when it resolves
+    #[serde(default)]
+    pub raw_command: Option<String>, // Registry value before %VAR% expansion, when it differs
+    #[serde(default)]
+    pub impact: Option<StartupImpact>, // Measured boot impact, None until estimated
+    #[serde(default)]
+    pub risk: Option<Risk>, // Advisory rating, None until assessed
+    #[serde(default)]
+    pub task: Option<TaskInfo>, // Triggers and account of a scheduled task
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub duplicates: Vec<StartupEntry>, // Same executable from other sources, merged into this row
 }
 
 impl StartupEntry {
@@ -41,18 +89,313 @@ impl StartupEntry {
         enabled: bool,
     ) -> Self {
         Self {
+            id: 0,
             name,
             command,
             source,
             enabled,
             description: None,
+            flags: Vec::new(),
+            scope: EntryScope::Machine,
+            classification: None,
+            acknowledged: false,
+            hosted_dll: None,
+            delayed_start: false,
+            user_sid: None,
+            user_name: None,
+            raw_command: None,
+            impact: None,
+            risk: None,
+            task: None,
+            duplicates: Vec::new(),
         }
     }
 
+    /// Gives every entry an id derived from where it lives and what it runs,
+    /// so the same entry gets the same id on every scan. Entries that would
+    /// share one (identical values in two places the hash can't tell apart)
+    /// take the next free id in scan order.
+    pub fn assign_ids(entries: &mut [StartupEntry]) {
+        let mut used = HashSet::new();
+        for entry in entries.iter_mut() {
+            let mut hasher = DefaultHasher::new();
+            (&entry.source, entry.scope, &entry.user_sid, &entry.name, &entry.command).hash(&mut hasher);
+            let mut id = hasher.finish();
+            while !used.insert(id) {
+                id = id.wrapping_add(1);
+            }
+            entry.id = id;
+        }
+    }
+
+    pub fn with_scope(mut self, scope: EntryScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Marks the entry as read from another account's loaded hive.
+    pub fn with_user(mut self, sid: String, name: Option<String>) -> Self {
+        self.scope = EntryScope::OtherUser;
+        self.user_sid = Some(sid);
+        self.user_name = name;
+        self
+    }
+
+    /// Keeps the unexpanded value a registry command was read from, so
+    /// writes can store it unchanged.
+    pub fn with_raw_command(mut self, raw: String) -> Self {
+        if raw != self.command {
+            self.raw_command = Some(raw);
+        }
+        self
+    }
+
+    /// The command as stored at its source, before environment expansion.
+    pub fn stored_command(&self) -> &str {
+        self.raw_command.as_deref().unwrap_or(&self.command)
+    }
+
+    pub fn with_task(mut self, task: TaskInfo) -> Self {
+        self.task = Some(task);
+        self
+    }
+
     pub fn with_description(mut self, description: String) -> Self {
         self.description = Some(description);
         self
     }
+
+    pub fn has_flag(&self, flag: EntryFlag) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    /// The file that actually runs: the service DLL for svchost-hosted
+    /// services, the DLL passed to rundll32, otherwise the executable in the
+    /// command line.
+    pub fn target_path(&self) -> Option<PathBuf> {
+        match self.hosted_dll {
+            Some(ref dll) => Some(PathBuf::from(dll)),
+            None => paths::resolve_target(&self.command),
+        }
+    }
+
+    /// Whether the target still exists on disk. Entries whose target can't
+    /// be determined count as existing.
+    pub fn target_exists(&self) -> bool {
+        self.target_path()
+            .is_none_or(|path| paths::target_exists(&path))
+    }
+
+    /// The highest severity among the entry's flags.
+    pub fn severity(&self) -> Severity {
+        self.flags
+            .iter()
+            .map(EntryFlag::severity)
+            .max()
+            .unwrap_or(Severity::None)
+    }
+
+    /// Whether changing this entry needs an elevated process.
+    pub fn requires_admin(&self) -> bool {
+        self.scope != EntryScope::User
+    }
+}
+
+/// Who an entry applies to, which decides the rights needed to modify it.
+/// Services, tasks and HKLM keys are machine-wide; HKCU keys are per user.
+/// Keys in other accounts' hives under HKEY_USERS need an administrator.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum EntryScope {
+    User,
+    #[default]
+    Machine,
+    OtherUser,
+}
+
+impl EntryScope {
+    pub const ALL: &'static [EntryScope] = &[EntryScope::User, EntryScope::Machine, EntryScope::OtherUser];
+}
+
+impl fmt::Display for EntryScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryScope::User => write!(f, "Current user"),
+            EntryScope::Machine => write!(f, "All users"),
+            EntryScope::OtherUser => write!(f, "Another user"),
+        }
+    }
+}
+
+/// When a scheduled task starts and whose account it runs under.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaskInfo {
+    pub path: String, // Full scheduler path, e.g. \Vendor\Updater
+    pub triggers: Vec<String>, // The startup triggers it has, e.g. "At logon"
+    pub run_as: Option<String>, // Principal user or group
+    pub highest_privileges: bool, // Runs elevated ("Run with highest privileges")
+}
+
+impl fmt::Display for TaskInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.triggers.join(", "))?;
+        if let Some(ref account) = self.run_as {
+            write!(f, ", runs as {}", account)?;
+        }
+        if self.highest_privileges {
+            write!(f, " with highest privileges")?;
+        }
+        Ok(())
+    }
+}
+
+/// How much an entry slows down sign-in, rated like Task Manager's
+/// "Startup impact" column from the CPU time and disk I/O Windows recorded.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StartupImpact {
+    Unknown,
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for StartupImpact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartupImpact::Unknown => write!(f, "Unknown"),
+            StartupImpact::Low => write!(f, "Low"),
+            StartupImpact::Medium => write!(f, "Medium"),
+            StartupImpact::High => write!(f, "High"),
+        }
+    }
+}
+
+/// Diagnostic findings attached to an entry after scanning.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum EntryFlag {
+    RemovableDrive,
+    UnknownDrive,
+    ImpersonatesSystemService,
+    DoubleExtension,
+    MismatchedExtension,
+    MissingTarget,
+}
+
+impl EntryFlag {
+    pub const ALL: &'static [EntryFlag] = &[
+        EntryFlag::RemovableDrive,
+        EntryFlag::UnknownDrive,
+        EntryFlag::ImpersonatesSystemService,
+        EntryFlag::DoubleExtension,
+        EntryFlag::MismatchedExtension,
+        EntryFlag::MissingTarget,
+    ];
+
+    /// Short marker shown next to the entry in the list view.
+    pub fn indicator(&self) -> &'static str {
+        match self {
+            EntryFlag::RemovableDrive => "⏏",
+            EntryFlag::UnknownDrive => "?",
+            EntryFlag::ImpersonatesSystemService => "!",
+            EntryFlag::DoubleExtension => "»",
+            EntryFlag::MismatchedExtension => "≠",
+            EntryFlag::MissingTarget => "⚠",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            EntryFlag::UnknownDrive => Severity::Low,
+            EntryFlag::RemovableDrive | EntryFlag::MissingTarget => Severity::Medium,
+            EntryFlag::ImpersonatesSystemService
+            | EntryFlag::DoubleExtension
+            | EntryFlag::MismatchedExtension => Severity::High,
+        }
+    }
+
+    /// One-sentence explanation of why the flag matters, used in reports.
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            EntryFlag::RemovableDrive => {
+                "The target is on removable media, so it fails or runs whatever is plugged in at boot."
+            }
+            EntryFlag::UnknownDrive => {
+                "The target's drive is missing or of unknown type, so the entry likely fails at boot."
+            }
+            EntryFlag::ImpersonatesSystemService => {
+                "The service presents itself as part of Windows but its binary does not match a genuine system service."
+            }
+            EntryFlag::DoubleExtension => {
+                "The file name hides an executable behind a document-like extension (e.g. invoice.pdf.exe)."
+            }
+            EntryFlag::MismatchedExtension => {
+                "The file is a Windows executable but its extension claims otherwise."
+            }
+            EntryFlag::MissingTarget => {
+                "The file the entry launches no longer exists, so it is left over from an uninstalled program."
+            }
+        }
+    }
+}
+
+impl fmt::Display for EntryFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryFlag::RemovableDrive => write!(f, "Removable drive"),
+            EntryFlag::UnknownDrive => write!(f, "Unknown drive"),
+            EntryFlag::ImpersonatesSystemService => write!(f, "Impersonates a system service"),
+            EntryFlag::DoubleExtension => write!(f, "Double extension"),
+            EntryFlag::MismatchedExtension => write!(f, "Mismatched extension"),
+            EntryFlag::MissingTarget => write!(f, "Missing target"),
+        }
+    }
+}
+
+/// How worrying an entry's flags are; entries without flags are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    pub const ALL: &'static [Severity] = &[Severity::None, Severity::Low, Severity::Medium, Severity::High];
+
+    /// Identifier used in the config file and on the command line.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Severity::None => "none",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Severity::ALL.iter().copied().find(|s| s.key().eq_ignore_ascii_case(key))
+    }
+
+    /// The next floor when cycling, wrapping back to showing everything.
+    pub fn next(&self) -> Self {
+        match self {
+            Severity::None => Severity::Low,
+            Severity::Low => Severity::Medium,
+            Severity::Medium => Severity::High,
+            Severity::High => Severity::None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::None => write!(f, "None"),
+            Severity::Low => write!(f, "Low"),
+            Severity::Medium => write!(f, "Medium"),
+            Severity::High => write!(f, "High"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -72,3 +415,18 @@ impl fmt::Display for Action {
     }
 }
 
+/// Settings files that can be reset to their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetTarget {
+    Config,
+    Whitelist,
+}
+
+impl fmt::Display for ResetTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResetTarget::Config => write!(f, "config"),
+            ResetTarget::Whitelist => write!(f, "whitelist"),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::logger::ActionLogger;
+use crate::models::StartupEntry;
+use crate::scanner::{ScanOptions, ScanSource, Scanner};
+use chrono::Local;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::Write;
+use std::time::Duration;
+use windows::{
+    core::{w, HSTRING, PCWSTR},
+    Win32::Security::PSID,
+    Win32::System::EventLog::{DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_WARNING_TYPE},
+};
+
+/// Event ID of the Application log records written for new entries.
+const WATCH_EVENT_ID: u32 = 1000;
+
+/// What happened to an entry between two scans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Enabled,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added => write!(f, "Added"),
+            Change::Enabled => write!(f, "Enabled"),
+        }
+    }
+}
+
+/// Entries in `current` that are new since `previous`, or were disabled
+/// then and are enabled now. Entries are the same when name, source and
+/// command match, so a changed command counts as a new entry.
+pub fn changes(previous: &[StartupEntry], current: &[StartupEntry]) -> Vec<(Change, StartupEntry)> {
+    let known: HashMap<(&str, String, &str), bool> = previous.iter().map(|e| (identity(e), e.enabled)).collect();
+
+    current
+        .iter()
+        .filter_map(|entry| match known.get(&identity(entry)) {
+            None => Some((Change::Added, entry.clone())),
+            Some(false) if entry.enabled => Some((Change::Enabled, entry.clone())),
+            Some(_) => None,
+        })
+        .collect()
+}
+
+/// The entries to compare the next scan against: `current`, plus the
+/// `previous` entries of the sources that failed to scan this time. Without
+/// them, everything those sources report once they scan again would look new.
+pub fn carry_forward(
+    previous: &[StartupEntry],
+    mut current: Vec<StartupEntry>,
+    failed: &[ScanSource],
+) -> Vec<StartupEntry> {
+    let mut seen: HashSet<(&str, String, &str)> = current.iter().map(identity).collect();
+    let carried: Vec<StartupEntry> = previous
+        .iter()
+        .filter(|entry| failed.iter().any(|source| source.covers(entry)))
+        .filter(|entry| seen.insert(identity(entry)))
+        .cloned()
+        .collect();
+    current.extend(carried);
+    current
+}
+
+/// What makes two scans' entries the same startup entry.
+fn identity(entry: &StartupEntry) -> (&str, String, &str) {
+    (entry.name.as_str(), entry.source.to_string(), entry.command.as_str())
+}
+
+/// Rescans on an interval and reports startup entries that appear or get
+/// enabled in between.
+pub struct Monitor {
+    interval: Duration,
+    beep: bool,
+    event_log: bool,
+    logger: ActionLogger,
+    options: ScanOptions,
+}
+
+impl Monitor {
+    pub fn new(interval: Duration, beep: bool, event_log: bool, logger: ActionLogger, options: ScanOptions) -> Self {
+        Self { interval, beep, event_log, logger, options }
+    }
+
+    /// Runs until the process is interrupted. Every cycle scans on fresh
+    /// threads, and the COM-based scanners pair their COM initialization
+    /// with its teardown on those threads, so nothing builds up over time.
+    pub fn run(&self) -> ! {
+        println!(
+            "Watching startup entries every {} seconds. Press Ctrl+C to stop.",
+            self.interval.as_secs()
+        );
+        // Sources missing from the baseline set theirs by their first good scan
+        let (mut previous, mut unscanned) = Scanner::rescan(&self.options);
+        println!("Baseline: {} entries", previous.len());
+
+        loop {
+            std::thread::sleep(self.interval);
+            let (current, failed) = Scanner::rescan(&self.options);
+            for (change, entry) in changes(&previous, &current) {
+                if !unscanned.iter().any(|source| source.covers(&entry)) {
+                    self.report(change, &entry);
+                }
+            }
+            unscanned.retain(|source| failed.contains(source));
+            previous = carry_forward(&previous, current, &failed);
+        }
+    }
+
+    fn report(&self, change: Change, entry: &StartupEntry) {
+        let message = format!(
+            "{} startup entry '{}' [{}]: {}",
+            change, entry.name, entry.source, entry.command
+        );
+        println!("[{}] {}", Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+
+        if self.beep {
+            print!("\x07");
+            let _ = std::io::stdout().flush();
+        }
+        let _ = self.logger.log_detected(
+            &change.to_string(),
+            &entry.name,
+            &entry.source.to_string(),
+            &entry.command,
+        );
+        if self.event_log {
+            if let Err(e) = write_event_log(&message) {
+                log::warn!("Failed to write to the event log: {}", e);
+            }
+        }
+    }
+}
+
+/// Writes a warning to the Application event log under the source name
+/// "DeepBoot". The source isn't registered, so Event Viewer notes that the
+/// description is missing but still shows the message text.
+fn write_event_log(message: &str) -> windows::core::Result<()> {
+    let text = HSTRING::from(message);
+    unsafe {
+        let source = RegisterEventSourceW(PCWSTR::null(), w!("DeepBoot"))?;
+        let result = ReportEventW(
+            source,
+            EVENTLOG_WARNING_TYPE,
+            0,
+            WATCH_EVENT_ID,
+            PSID::default(),
+            0,
+            Some(&[PCWSTR(text.as_ptr())]),
+            None,
+        );
+        let _ = DeregisterEventSource(source);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StartupSource;
+
+    fn entry(name: &str, source: StartupSource, enabled: bool) -> StartupEntry {
+        StartupEntry::new(name.to_string(), format!("C:\\Tools\\{}.exe", name), source, enabled)
+    }
+
+    fn names(changes: &[(Change, StartupEntry)]) -> Vec<(Change, &str)> {
+        changes.iter().map(|(change, entry)| (*change, entry.name.as_str())).collect()
+    }
+
+    #[test]
+    fn a_failed_source_does_not_report_its_entries_as_new() {
+        let baseline = vec![
+            entry("Updater", StartupSource::RegistryRun, true),
+            entry("Cleanup", StartupSource::TaskScheduler, true),
+            entry("Backup", StartupSource::TaskScheduler, false),
+        ];
+
+        // Task Scheduler fails: its entries are missing, but nothing is new
+        let cycle = vec![entry("Updater", StartupSource::RegistryRun, true)];
+        assert!(changes(&baseline, &cycle).is_empty());
+        let previous = carry_forward(&baseline, cycle, &[ScanSource::TaskScheduler]);
+        assert_eq!(previous.len(), baseline.len());
+
+        // Once it scans again, only what really changed is reported
+        let cycle = vec![
+            entry("Updater", StartupSource::RegistryRun, true),
+            entry("Cleanup", StartupSource::TaskScheduler, true),
+            entry("Backup", StartupSource::TaskScheduler, true),
+            entry("Miner", StartupSource::TaskScheduler, true),
+        ];
+        assert_eq!(
+            names(&changes(&previous, &cycle)),
+            vec![(Change::Enabled, "Backup"), (Change::Added, "Miner")]
+        );
+    }
+
+    #[test]
+    fn only_the_failed_sources_are_carried_forward() {
+        let previous = vec![
+            entry("Updater", StartupSource::RegistryRun, true),
+            entry("Agent", StartupSource::Service, true),
+        ];
+        // Removed from the registry while the services scan failed
+        let carried = carry_forward(&previous, Vec::new(), &[ScanSource::Services]);
+        assert_eq!(names(&changes(&carried, &previous)), vec![(Change::Added, "Updater")]);
+    }
+
+    #[test]
+    fn carried_entries_are_not_duplicated() {
+        let previous = vec![entry("Cleanup", StartupSource::TaskScheduler, true)];
+        // WMI can still report an entry whose own scanner failed
+        let carried = carry_forward(&previous, previous.clone(), &[ScanSource::TaskScheduler]);
+        assert_eq!(carried.len(), 1);
+    }
+}
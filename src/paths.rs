@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use windows::core::HSTRING;
+use windows::Win32::System::Environment::ExpandEnvironmentStringsW;
+
+// Extensions that mark the end of the executable part of an unquoted command line
+const EXECUTABLE_EXTENSIONS: &[&str] = &[".exe", ".com", ".bat", ".cmd", ".scr", ".dll"];
+
+/// Extracts the executable path from a startup command line and expands any
+/// `%VAR%` references in it. Returns `None` for empty commands.
+pub fn resolve_executable(command: &str) -> Option<PathBuf> {
+    let (executable, _) = split_command(command)?;
+    let expanded = expand_env_vars(executable.trim());
+    if expanded.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(expanded))
+    }
+}
+
+/// Like `resolve_executable`, but for host programs whose real target is an
+/// argument returns that instead: `rundll32.exe foo.dll,Entry` runs `foo.dll`.
+pub fn resolve_target(command: &str) -> Option<PathBuf> {
+    let executable = resolve_executable(command)?;
+    let is_rundll = executable
+        .file_stem()
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("rundll32"));
+    if !is_rundll {
+        return Some(executable);
+    }
+
+    let (_, arguments) = split_command(command)?;
+    let arguments = arguments.trim();
+    let dll = match arguments.strip_prefix('"') {
+        Some(rest) => rest.split('"').next().unwrap_or(rest),
+        None => arguments.split(',').next().unwrap_or(arguments),
+    };
+    let dll = expand_env_vars(dll.trim());
+    if dll.is_empty() {
+        Some(executable)
+    } else {
+        Some(PathBuf::from(dll))
+    }
+}
+
+/// Whether a resolved target is present. Bare names like `rundll32.exe` are
+/// looked up on `PATH` the way Windows would; a bare name without an
+/// executable extension (e.g. a placeholder like "Unknown") can't be judged
+/// and counts as present.
+pub fn target_exists(path: &Path) -> bool {
+    if path.is_absolute() || path.parent().is_some_and(|p| !p.as_os_str().is_empty()) {
+        return path.exists();
+    }
+
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase()));
+    if !extension.is_some_and(|ext| EXECUTABLE_EXTENSIONS.contains(&ext.as_str())) {
+        return true;
+    }
+
+    std::env::var_os("PATH")
+        .map(|dirs| std::env::split_paths(&dirs).any(|dir| dir.join(path).is_file()))
+        .unwrap_or(false)
+}
+
+/// The file a resolved target refers to on disk: the path itself when it
+/// includes a directory, otherwise the first match on `PATH`. `None` when
+/// the file doesn't exist.
+pub fn locate(path: &Path) -> Option<PathBuf> {
+    if path.is_absolute() || path.parent().is_some_and(|p| !p.as_os_str().is_empty()) {
+        return path.exists().then(|| path.to_path_buf());
+    }
+    let dirs = std::env::var_os("PATH")?;
+    std::env::split_paths(&dirs)
+        .map(|dir| dir.join(path))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Splits a command line into its executable part (quotes stripped, not yet
+/// expanded) and the rest.
+pub fn split_command(command: &str) -> Option<(&str, &str)> {
+    let command = command.trim();
+    if command.is_empty() {
+        return None;
+    }
+
+    if let Some(rest) = command.strip_prefix('"') {
+        // "C:\Program Files\App\app.exe" --args
+        return Some(rest.split_once('"').unwrap_or((rest, "")));
+    }
+
+    let executable = {
+        // Unquoted paths may contain spaces, so prefer cutting after a known
+        // executable extension and fall back to the first whitespace.
+        let lower = command.to_lowercase();
+        EXECUTABLE_EXTENSIONS
+            .iter()
+            .filter_map(|ext| {
+                lower.match_indices(ext).map(|(pos, _)| pos + ext.len()).find(|&end| {
+                    command[end..].is_empty() || command[end..].starts_with(char::is_whitespace)
+                })
+            })
+            .min()
+            .map(|end| &command[..end])
+            .unwrap_or_else(|| command.split_whitespace().next().unwrap_or(command))
+    };
+
+    Some((executable, &command[executable.len()..]))
+}
+
+/// Expands `%VAR%` references the way Windows does for `REG_EXPAND_SZ`
+/// values, via `ExpandEnvironmentStringsW`. Unknown variables are left
+/// untouched.
+pub fn expand_env_vars(value: &str) -> String {
+    if !value.contains('%') {
+        return value.to_string();
+    }
+    expand_with_api(value).unwrap_or_else(|| expand_with_std_env(value))
+}
+
+fn expand_with_api(value: &str) -> Option<String> {
+    let source = HSTRING::from(value);
+    unsafe {
+        // The first call reports the buffer size needed, terminator included
+        let needed = ExpandEnvironmentStringsW(&source, None);
+        if needed == 0 {
+            return None;
+        }
+        let mut buffer = vec![0u16; needed as usize];
+        let written = ExpandEnvironmentStringsW(&source, Some(&mut buffer));
+        if written == 0 || written > needed {
+            return None;
+        }
+        Some(String::from_utf16_lossy(&buffer[..written as usize - 1]))
+    }
+}
+
+/// Fallback expansion from the process environment, for when the API fails.
+fn expand_with_std_env(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('%') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('%') {
+            Some(end) => {
+                let name = &after[..end];
+                match std::env::var(name) {
+                    Ok(val) if !name.is_empty() => result.push_str(&val),
+                    _ => {
+                        result.push('%');
+                        result.push_str(name);
+                        result.push('%');
+                    }
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Returns the `X:\` root of a drive-letter path, or `None` for relative and UNC paths.
+pub fn drive_root(path: &Path) -> Option<String> {
+    let path = path.to_str()?;
+    let mut chars = path.chars();
+    let letter = chars.next()?;
+    if letter.is_ascii_alphabetic() && chars.next() == Some(':') {
+        Some(format!("{}:\\", letter.to_ascii_uppercase()))
+    } else {
+        None
+    }
+}
+
+/// Opens `path` in Explorer. With `select`, the parent folder is opened with
+/// the file highlighted instead.
+pub fn open_in_explorer(path: &Path, select: bool) -> Result<()> {
+    let mut command = Command::new("explorer");
+    if select {
+        command.arg(format!("/select,{}", path.display()));
+    } else {
+        command.arg(path);
+    }
+    // Explorer's exit code is meaningless, so don't wait on it
+    command
+        .spawn()
+        .with_context(|| format!("Failed to open {:?} in Explorer", path))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_system_root() {
+        let root = std::env::var("SystemRoot").unwrap();
+        assert_eq!(
+            expand_env_vars("%SystemRoot%\\System32\\svchost.exe -k netsvcs"),
+            format!("{}\\System32\\svchost.exe -k netsvcs", root)
+        );
+    }
+
+    #[test]
+    fn leaves_unknown_variables_untouched() {
+        assert_eq!(expand_env_vars("%DEEPBOOT_NO_SUCH_VAR%\\app.exe"), "%DEEPBOOT_NO_SUCH_VAR%\\app.exe");
+        assert_eq!(expand_env_vars("C:\\Tools\\app.exe"), "C:\\Tools\\app.exe");
+    }
+
+    #[test]
+    fn fallback_expands_like_the_api() {
+        std::env::set_var("DEEPBOOT_TEST_ROOT", "C:\\Windows");
+        assert_eq!(
+            expand_with_std_env("%DEEPBOOT_TEST_ROOT%\\System32\\%DEEPBOOT_NO_SUCH_VAR%\\app.exe"),
+            "C:\\Windows\\System32\\%DEEPBOOT_NO_SUCH_VAR%\\app.exe"
+        );
+        // An unpaired % is kept as it is
+        assert_eq!(expand_with_std_env("50% off"), "50% off");
+    }
+}
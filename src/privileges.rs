@@ -0,0 +1,96 @@
+use std::mem::size_of;
+use windows::{
+    core::{HSTRING, PCWSTR, PWSTR},
+    Win32::{
+        Foundation::{CloseHandle, LocalFree, HANDLE, HLOCAL},
+        Security::Authorization::{ConvertSidToStringSidW, ConvertStringSidToSidW},
+        Security::{
+            GetTokenInformation, LookupAccountSidW, TokenElevation, TokenUser, PSID, SID_NAME_USE,
+            TOKEN_ELEVATION, TOKEN_QUERY, TOKEN_USER,
+        },
+        System::Threading::{GetCurrentProcess, OpenProcessToken},
+    },
+};
+
+/// Whether the current process runs with an elevated (administrator) token.
+/// Any failure to query the token is treated as not elevated.
+pub fn is_elevated() -> bool {
+    unsafe {
+        let mut token = HANDLE::default();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).is_err() {
+            return false;
+        }
+
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut returned = 0u32;
+        let result = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut _ as *mut _),
+            size_of::<TOKEN_ELEVATION>() as u32,
+            &mut returned,
+        );
+        let _ = CloseHandle(token);
+
+        result.is_ok() && elevation.TokenIsElevated != 0
+    }
+}
+
+/// String form (`S-1-5-21-...`) of the SID of the account running DeepBoot.
+pub fn current_user_sid() -> Option<String> {
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).ok()?;
+
+        // The first call only reports the size TOKEN_USER and its SID need
+        let mut needed = 0u32;
+        let _ = GetTokenInformation(token, TokenUser, None, 0, &mut needed);
+        let mut buffer = vec![0u64; (needed as usize).div_ceil(8)];
+        let result = GetTokenInformation(
+            token,
+            TokenUser,
+            Some(buffer.as_mut_ptr() as *mut _),
+            (buffer.len() * 8) as u32,
+            &mut needed,
+        );
+        let _ = CloseHandle(token);
+        result.ok()?;
+
+        let user = &*(buffer.as_ptr() as *const TOKEN_USER);
+        let mut string_sid = PWSTR::null();
+        ConvertSidToStringSidW(user.User.Sid, &mut string_sid).ok()?;
+        let sid = string_sid.to_string().ok();
+        let _ = LocalFree(HLOCAL(string_sid.0 as _));
+        sid
+    }
+}
+
+/// `DOMAIN\name` of the account a string SID belongs to, or `None` when the
+/// SID doesn't resolve (e.g. a deleted account).
+pub fn account_name(sid: &str) -> Option<String> {
+    unsafe {
+        let mut psid = PSID::default();
+        ConvertStringSidToSidW(&HSTRING::from(sid), &mut psid).ok()?;
+
+        let mut name = [0u16; 256];
+        let mut domain = [0u16; 256];
+        let mut name_len = name.len() as u32;
+        let mut domain_len = domain.len() as u32;
+        let mut sid_use = SID_NAME_USE::default();
+        let result = LookupAccountSidW(
+            PCWSTR::null(),
+            psid,
+            PWSTR(name.as_mut_ptr()),
+            &mut name_len,
+            PWSTR(domain.as_mut_ptr()),
+            &mut domain_len,
+            &mut sid_use,
+        );
+        let _ = LocalFree(HLOCAL(psid.0));
+        result.ok()?;
+
+        let name = String::from_utf16_lossy(&name[..name_len as usize]);
+        let domain = String::from_utf16_lossy(&domain[..domain_len as usize]);
+        Some(if domain.is_empty() { name } else { format!("{}\\{}", domain, name) })
+    }
+}
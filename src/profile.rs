@@ -0,0 +1,149 @@
+use crate::backup::{Backup, BackupManager};
+use crate::batch::{BatchProcessor, BatchResult};
+use crate::logger::ActionLogger;
+use crate::models::{Action, StartupEntry};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+const PROFILE_SCOPE: &str = "profile";
+
+/// Differences between a profile and the entries installed now.
+#[derive(Debug, Default)]
+pub struct ProfileDiff {
+    pub to_enable: Vec<StartupEntry>,
+    pub to_disable: Vec<StartupEntry>,
+    pub unchanged: usize,
+    pub missing: Vec<StartupEntry>, // Profile entries not installed here
+}
+
+impl ProfileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.to_enable.is_empty() && self.to_disable.is_empty()
+    }
+
+    /// One line per change, then the entries that can't be matched.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for entry in &self.to_enable {
+            lines.push(format!("  + {} [{}] will be enabled", entry.name, entry.source));
+        }
+        for entry in &self.to_disable {
+            lines.push(format!("  - {} [{}] will be disabled", entry.name, entry.source));
+        }
+        for entry in &self.missing {
+            lines.push(format!("  ? {} [{}] skipped: not found on this machine", entry.name, entry.source));
+        }
+        lines
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} to enable, {} to disable, {} already match, {} not installed",
+            self.to_enable.len(),
+            self.to_disable.len(),
+            self.unchanged,
+            self.missing.len()
+        )
+    }
+}
+
+/// Named snapshots of every entry's enabled state, stored in the backup
+/// format under `%APPDATA%\deepboot\profiles\<name>.json`.
+pub struct ProfileManager {
+    profile_dir: PathBuf,
+}
+
+impl ProfileManager {
+    pub fn new() -> Result<Self> {
+        let profile_dir = dirs::data_dir()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get data directory"))?
+            .join("deepboot")
+            .join("profiles");
+
+        if !profile_dir.exists() {
+            fs::create_dir_all(&profile_dir)
+                .context("Failed to create profile directory")?;
+        }
+
+        Ok(Self { profile_dir })
+    }
+
+    /// Profile names become file names, so keep them to letters, digits,
+    /// `-` and `_`.
+    pub fn validate_name(name: &str) -> Result<()> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            anyhow::bail!("Invalid profile name '{}': use letters, digits, '-' and '_'", name);
+        }
+        Ok(())
+    }
+
+    fn profile_path(&self, name: &str) -> PathBuf {
+        self.profile_dir.join(format!("{}.json", name))
+    }
+
+    /// Saves the entries' current state, replacing a profile of the same name.
+    pub fn save(&self, name: &str, entries: &[StartupEntry]) -> Result<PathBuf> {
+        Self::validate_name(name)?;
+        let path = self.profile_path(name);
+        let content = serde_json::to_string_pretty(&BackupManager::snapshot(entries, PROFILE_SCOPE))
+            .context("Failed to serialize profile")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write profile {:?}", path))?;
+        Ok(path)
+    }
+
+    pub fn load(&self, name: &str) -> Result<Backup> {
+        Self::validate_name(name)?;
+        let path = self.profile_path(name);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("No profile named '{}'", name))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse profile {:?}", path))
+    }
+
+    /// Profile names, alphabetically.
+    pub fn list(&self) -> Result<Vec<String>> {
+        let mut names: Vec<String> = fs::read_dir(&self.profile_dir)
+            .context("Failed to read profile directory")?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                Some(path.file_stem()?.to_string_lossy().to_string())
+            })
+            .collect();
+        names.sort_by_key(|name| name.to_lowercase());
+        Ok(names)
+    }
+
+    /// Matches profile entries to installed ones by source and name
+    /// (ignoring case) and collects the ones whose state differs.
+    pub fn diff(profile: &Backup, current: &[StartupEntry]) -> ProfileDiff {
+        let mut diff = ProfileDiff::default();
+
+        for saved in profile.entries.iter().map(|e| &e.entry) {
+            let found = current
+                .iter()
+                .find(|c| c.source == saved.source && c.name.eq_ignore_ascii_case(&saved.name));
+            match found {
+                Some(current) if current.enabled == saved.enabled => diff.unchanged += 1,
+                Some(current) if saved.enabled => diff.to_enable.push(current.clone()),
+                Some(current) => diff.to_disable.push(current.clone()),
+                None => diff.missing.push(saved.clone()),
+            }
+        }
+        diff
+    }
+
+    /// Disables and enables entries to match the profile. Returns the
+    /// results as (disable, enable).
+    pub fn apply(diff: &ProfileDiff, logger: Option<ActionLogger>, dry_run: bool) -> (BatchResult, BatchResult) {
+        let processor = BatchProcessor::new(logger);
+        (
+            processor.process_batch(&diff.to_disable, Action::Disable, dry_run),
+            processor.process_batch(&diff.to_enable, Action::Enable, dry_run),
+        )
+    }
+}
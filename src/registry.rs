@@ -1,12 +1,67 @@
-use crate::models::{StartupEntry, StartupSource};
+use crate::models::{EntryScope, StartupEntry, StartupSource};
+use crate::paths;
 use anyhow::{Context, Result};
+use std::time::{SystemTime, UNIX_EPOCH};
 use winreg::enums::*;
-use winreg::{RegKey, HKEY};
+use winreg::{RegKey, RegValue, HKEY};
+
+/// Explorer's record of which Run values the user switched off. Each value is
+/// a 12-byte blob named after the Run value it controls.
+const STARTUP_APPROVED_RUN: &str =
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\StartupApproved\\Run";
+const STARTUP_APPROVED_RUN32: &str =
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\StartupApproved\\Run32";
+
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+const RUN_ONCE_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\RunOnce";
+
+/// Regedit's own settings. It opens at the key named by `LastKey`, so
+/// writing that first makes it start at a given key.
+const REGEDIT_APPLET_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Applets\\Regedit";
+
+/// SID prefixes of real accounts under HKEY_USERS: local and domain users,
+/// and Azure AD users. Service accounts (S-1-5-18/19/20) and `.DEFAULT` are
+/// left out.
+const USER_SID_PREFIXES: &[&str] = &["S-1-5-21-", "S-1-12-1-"];
+
+const APPROVAL_ENABLED: u8 = 0x02;
+const APPROVAL_DISABLED: u8 = 0x03;
+
+/// Seconds between 1601-01-01 (the FILETIME epoch) and 1970-01-01.
+const FILETIME_UNIX_OFFSET: u64 = 11_644_473_600;
+
+/// Builds a StartupApproved blob: the state byte, three zero bytes and the
+/// FILETIME the entry was disabled at (zero for enabled entries), as Task
+/// Manager writes it.
+pub fn encode_approval(enabled: bool) -> Vec<u8> {
+    let mut blob = vec![0u8; 12];
+    if enabled {
+        blob[0] = APPROVAL_ENABLED;
+    } else {
+        blob[0] = APPROVAL_DISABLED;
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let filetime = (since_epoch.as_secs() + FILETIME_UNIX_OFFSET) * 10_000_000
+            + u64::from(since_epoch.subsec_nanos()) / 100;
+        blob[4..].copy_from_slice(&filetime.to_le_bytes());
+    }
+    blob
+}
+
+/// Reads the state out of a StartupApproved blob. Windows uses even state
+/// bytes (0x02, 0x06) for enabled and odd ones (0x01, 0x03, 0x07) for disabled;
+/// an empty blob counts as enabled, as it does for Explorer.
+pub fn decode_approval(blob: &[u8]) -> bool {
+    blob.first().is_none_or(|state| state & 0x01 == 0)
+}
 
 pub struct RegistryScanner;
 
 impl RegistryScanner {
-    pub fn scan_all() -> Result<Vec<StartupEntry>> {
+    /// Reads every Run-style key; `scan_other_users` adds the Run and
+    /// RunOnce keys of other accounts' loaded hives.
+    pub fn scan_all(scan_other_users: bool) -> Result<Vec<StartupEntry>> {
         let mut entries = Vec::new();
 
         // HKCU\Software\Microsoft\Windows\CurrentVersion\Run
@@ -27,44 +82,88 @@ impl RegistryScanner {
         // HKLM\Software\Wow6432Node\Microsoft\Windows\CurrentVersion\Run
         entries.extend(Self::scan_wow6432_node()?);
 
+        // HKU\<SID>\Software\Microsoft\Windows\CurrentVersion\Run and RunOnce
+        if scan_other_users {
+            entries.extend(Self::scan_other_users());
+        }
+
         Ok(entries)
     }
 
     fn scan_run_key(hkey: HKEY, source: StartupSource) -> Result<Vec<StartupEntry>> {
-        let mut entries = Vec::new();
-        let base_path = match hkey {
-            HKEY_CURRENT_USER => "Software\\Microsoft\\Windows\\CurrentVersion",
-            HKEY_LOCAL_MACHINE => "Software\\Microsoft\\Windows\\CurrentVersion",
-            _ => return Ok(entries),
+        let scope = match hkey {
+            HKEY_CURRENT_USER => EntryScope::User,
+            HKEY_LOCAL_MACHINE => EntryScope::Machine,
+            _ => return Ok(Vec::new()),
         };
 
-        let subkey_name = match source {
-            StartupSource::RegistryRun => "Run",
-            StartupSource::RegistryRunOnce => "RunOnce",
-            _ => return Ok(entries),
-        };
+        Ok(Self::read_run_key(&RegKey::predef(hkey), "", &source)
+            .into_iter()
+            .map(|entry| entry.with_scope(scope))
+            .collect())
+    }
 
-        let hkey_root = match hkey {
-            HKEY_CURRENT_USER => RegKey::predef(HKEY_CURRENT_USER),
-            HKEY_LOCAL_MACHINE => RegKey::predef(HKEY_LOCAL_MACHINE),
-            _ => return Ok(entries),
-        };
+    /// Run and RunOnce values of every other account whose hive is loaded
+    /// under HKEY_USERS. The current user is already read through HKCU.
+    /// Reading other hives needs an administrator; without one they are
+    /// silently skipped.
+    fn scan_other_users() -> Vec<StartupEntry> {
+        let users = RegKey::predef(HKEY_USERS);
+        let current = crate::privileges::current_user_sid();
+        let mut entries = Vec::new();
 
-        if let Ok(subkey) = hkey_root.open_subkey(base_path) {
-            if let Ok(run_key) = subkey.open_subkey(subkey_name) {
-                for (name, value) in run_key.enum_values().flatten() {
-                    let command = value.to_string();
-                    entries.push(StartupEntry::new(
-                        name,
-                        command,
-                        source.clone(),
-                        true,
-                    ));
-                }
+        for sid in users.enum_keys().flatten() {
+            if !USER_SID_PREFIXES.iter().any(|prefix| sid.starts_with(prefix))
+                || sid.ends_with("_Classes")
+                || current.as_deref() == Some(sid.as_str())
+            {
+                continue;
+            }
+
+            let name = crate::privileges::account_name(&sid);
+            let prefix = format!("{}\\", sid);
+            for source in [StartupSource::RegistryRun, StartupSource::RegistryRunOnce] {
+                entries.extend(
+                    Self::read_run_key(&users, &prefix, &source)
+                        .into_iter()
+                        .map(|entry| entry.with_user(sid.clone(), name.clone())),
+                );
             }
         }
 
-        Ok(entries)
+        entries
+    }
+
+    /// Values of the source's Run or RunOnce key below `root\prefix`, with
+    /// their enabled state from the StartupApproved key of the same hive.
+    fn read_run_key(root: &RegKey, prefix: &str, source: &StartupSource) -> Vec<StartupEntry> {
+        let Some(path) = Self::run_key_path(source) else {
+            return Vec::new();
+        };
+        let Ok(run_key) = root.open_subkey(format!("{}{}", prefix, path)) else {
+            return Vec::new();
+        };
+        let approved = Self::approval_key(source)
+            .and_then(|path| root.open_subkey(format!("{}{}", prefix, path)).ok());
+
+        run_key
+            .enum_values()
+            .flatten()
+            .map(|(name, value)| {
+                let raw = value.to_string();
+                let enabled = Self::is_approved(approved.as_ref(), &name);
+                StartupEntry::new(name, paths::expand_env_vars(&raw), source.clone(), enabled)
+                    .with_raw_command(raw)
+            })
+            .collect()
+    }
+
+    fn run_key_path(source: &StartupSource) -> Option<&'static str> {
+        match source {
+            StartupSource::RegistryRun => Some(RUN_KEY),
+            StartupSource::RegistryRunOnce => Some(RUN_ONCE_KEY),
+            _ => None,
+        }
     }
 
     fn scan_run_services() -> Result<Vec<StartupEntry>> {
@@ -74,13 +173,16 @@ impl RegistryScanner {
 
         if let Ok(run_services) = hklm.open_subkey(path) {
             for (name, value) in run_services.enum_values().flatten() {
-                let command = value.to_string();
-                entries.push(StartupEntry::new(
-                    name,
-                    command,
-                    StartupSource::RegistryRunServices,
-                    true,
-                ));
+                let raw = value.to_string();
+                entries.push(
+                    StartupEntry::new(
+                        name,
+                        paths::expand_env_vars(&raw),
+                        StartupSource::RegistryRunServices,
+                        true,
+                    )
+                    .with_raw_command(raw),
+                );
             }
         }
 
@@ -92,145 +194,428 @@ impl RegistryScanner {
         let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
         let path = "Software\\Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\Run";
 
+        let approved = hklm.open_subkey(STARTUP_APPROVED_RUN32).ok();
+
         if let Ok(wow_key) = hklm.open_subkey(path) {
             for (name, value) in wow_key.enum_values().flatten() {
-                let command = value.to_string();
-                entries.push(StartupEntry::new(
-                    name,
-                    command,
-                    StartupSource::RegistryWow6432Node,
-                    true,
-                ));
+                let raw = value.to_string();
+                let enabled = Self::is_approved(approved.as_ref(), &name);
+                entries.push(
+                    StartupEntry::new(
+                        name,
+                        paths::expand_env_vars(&raw),
+                        StartupSource::RegistryWow6432Node,
+                        enabled,
+                    )
+                    .with_raw_command(raw),
+                );
             }
         }
 
         Ok(entries)
     }
 
-    pub fn disable_entry(entry: &StartupEntry) -> Result<()> {
-        let (hkey, base_path, subkey_name) = match entry.source {
-            StartupSource::RegistryRun | StartupSource::RegistryRunOnce => {
-                // Try HKCU first, then HKLM
-                if Self::entry_exists_in_hkey(HKEY_CURRENT_USER, &entry.source, &entry.name)? {
-                    (
-                        RegKey::predef(HKEY_CURRENT_USER),
-                        "Software\\Microsoft\\Windows\\CurrentVersion",
-                        match entry.source {
-                            StartupSource::RegistryRun => "Run",
-                            StartupSource::RegistryRunOnce => "RunOnce",
-                            _ => return Ok(()),
-                        },
-                    )
-                } else {
-                    (
-                        RegKey::predef(HKEY_LOCAL_MACHINE),
-                        "Software\\Microsoft\\Windows\\CurrentVersion",
-                        match entry.source {
-                            StartupSource::RegistryRun => "Run",
-                            StartupSource::RegistryRunOnce => "RunOnce",
-                            _ => return Ok(()),
-                        },
-                    )
-                }
-            }
-            StartupSource::RegistryRunServices => (
-                RegKey::predef(HKEY_LOCAL_MACHINE),
-                "Software\\Microsoft\\Windows\\CurrentVersion",
-                "RunServices",
-            ),
-            StartupSource::RegistryWow6432Node => (
-                RegKey::predef(HKEY_LOCAL_MACHINE),
-                "Software\\Wow6432Node\\Microsoft\\Windows\\CurrentVersion",
-                "Run",
-            ),
-            _ => return Ok(()),
+    /// StartupApproved key tracking a source's values, if Explorer keeps one.
+    /// RunOnce and RunServices values have no enabled state.
+    fn approval_key(source: &StartupSource) -> Option<&'static str> {
+        match source {
+            StartupSource::RegistryRun => Some(STARTUP_APPROVED_RUN),
+            StartupSource::RegistryWow6432Node => Some(STARTUP_APPROVED_RUN32),
+            _ => None,
+        }
+    }
+
+    fn is_approved(approved: Option<&RegKey>, name: &str) -> bool {
+        approved
+            .and_then(|key| key.get_raw_value(name).ok())
+            .is_none_or(|value| decode_approval(&value.bytes))
+    }
+
+    /// The StartupApproved key for an entry lives in the same hive as its value.
+    fn approval_location(entry: &StartupEntry) -> Result<Option<(HKEY, String)>> {
+        let Some(path) = Self::approval_key(&entry.source) else {
+            return Ok(None);
         };
+        Ok(Self::value_location(entry)?.map(|(hkey, _)| (hkey, Self::in_user_hive(entry, path))))
+    }
 
-        let base = hkey
-            .open_subkey_with_flags(base_path, KEY_WRITE)
-            .context("Failed to open registry key for writing")?;
-        let mut run_key = base
-            .open_subkey_with_flags(subkey_name, KEY_WRITE)
-            .context("Failed to open Run subkey")?;
+    /// Prefixes `path` with the SID of the entry's hive when it was read from
+    /// another account under HKEY_USERS.
+    fn in_user_hive(entry: &StartupEntry, path: &str) -> String {
+        match entry.user_sid {
+            Some(ref sid) => format!("{}\\{}", sid, path),
+            None => path.to_string(),
+        }
+    }
 
-        // Disable by deleting the value (we can't rename in winreg 0.52)
-        // The entry will be removed, which effectively disables it
-        run_key.delete_value(&entry.name).context("Failed to disable entry")
+    fn write_approval(hkey: HKEY, path: &str, name: &str, enabled: bool) -> Result<()> {
+        let (approved, _) = RegKey::predef(hkey)
+            .create_subkey(path)
+            .context("Failed to open StartupApproved key")?;
+        approved
+            .set_raw_value(
+                name,
+                &RegValue {
+                    bytes: encode_approval(enabled),
+                    vtype: REG_BINARY,
+                },
+            )
+            .context("Failed to update StartupApproved value")
     }
 
-    pub fn remove_entry(entry: &StartupEntry) -> Result<()> {
-        let (hkey, base_path, subkey_name) = match entry.source {
+    /// Resolves the hive and key holding an entry's value. Values read from
+    /// another account stay in that account's HKEY_USERS hive; other
+    /// Run/RunOnce values are in HKCU for `EntryScope::User` and in HKLM
+    /// otherwise, as the scan recorded them. Returns `None` for entries that
+    /// don't come from the registry.
+    fn value_location(entry: &StartupEntry) -> Result<Option<(HKEY, String)>> {
+        let location = match entry.source {
+            StartupSource::RegistryRun | StartupSource::RegistryRunOnce if entry.user_sid.is_some() => {
+                let path = Self::run_key_path(&entry.source).unwrap_or(RUN_KEY);
+                (HKEY_USERS, Self::in_user_hive(entry, path))
+            }
             StartupSource::RegistryRun | StartupSource::RegistryRunOnce => {
-                if Self::entry_exists_in_hkey(HKEY_CURRENT_USER, &entry.source, &entry.name)? {
-                    (
-                        RegKey::predef(HKEY_CURRENT_USER),
-                        "Software\\Microsoft\\Windows\\CurrentVersion",
-                        match entry.source {
-                            StartupSource::RegistryRun => "Run",
-                            StartupSource::RegistryRunOnce => "RunOnce",
-                            _ => return Ok(()),
-                        },
-                    )
-                } else {
-                    (
-                        RegKey::predef(HKEY_LOCAL_MACHINE),
-                        "Software\\Microsoft\\Windows\\CurrentVersion",
-                        match entry.source {
-                            StartupSource::RegistryRun => "Run",
-                            StartupSource::RegistryRunOnce => "RunOnce",
-                            _ => return Ok(()),
-                        },
-                    )
-                }
+                let hkey = match entry.scope {
+                    EntryScope::User => HKEY_CURRENT_USER,
+                    _ => HKEY_LOCAL_MACHINE,
+                };
+                let path = Self::run_key_path(&entry.source).unwrap_or(RUN_KEY);
+                (hkey, path.to_string())
             }
             StartupSource::RegistryRunServices => (
-                RegKey::predef(HKEY_LOCAL_MACHINE),
-                "Software\\Microsoft\\Windows\\CurrentVersion",
-                "RunServices",
+                HKEY_LOCAL_MACHINE,
+                "Software\\Microsoft\\Windows\\CurrentVersion\\RunServices".to_string(),
             ),
             StartupSource::RegistryWow6432Node => (
-                RegKey::predef(HKEY_LOCAL_MACHINE),
-                "Software\\Wow6432Node\\Microsoft\\Windows\\CurrentVersion",
-                "Run",
+                HKEY_LOCAL_MACHINE,
+                "Software\\Wow6432Node\\Microsoft\\Windows\\CurrentVersion\\Run".to_string(),
             ),
-            _ => return Ok(()),
+            _ => return Ok(None),
         };
+        Ok(Some(location))
+    }
 
-        let base = hkey
-            .open_subkey_with_flags(base_path, KEY_WRITE)
-            .context("Failed to open registry key for writing")?;
-        let mut run_key = base
-            .open_subkey_with_flags(subkey_name, KEY_WRITE)
-            .context("Failed to open Run subkey")?;
-
-        run_key.delete_value(&entry.name).context("Failed to remove entry")
-    }
-
-    fn entry_exists_in_hkey(
-        hkey: HKEY,
-        source: &StartupSource,
-        name: &str,
-    ) -> Result<bool> {
-        let hkey_root = match hkey {
-            HKEY_CURRENT_USER => RegKey::predef(HKEY_CURRENT_USER),
-            HKEY_LOCAL_MACHINE => RegKey::predef(HKEY_LOCAL_MACHINE),
-            _ => return Ok(false),
+    /// Opens the key holding the entry's value for reading and writing;
+    /// enabling checks whether the value is still there before rewriting it.
+    fn open_for_writing(entry: &StartupEntry) -> Result<Option<RegKey>> {
+        let Some((hkey, path)) = Self::value_location(entry)? else {
+            return Ok(None);
         };
+        Self::open_key_for_writing(hkey, &path).map(Some)
+    }
+
+    fn open_key_for_writing(hkey: HKEY, path: &str) -> Result<RegKey> {
+        RegKey::predef(hkey)
+            .open_subkey_with_flags(path, KEY_READ | KEY_WRITE)
+            .context("Failed to open Run key for writing")
+    }
 
-        let base_path = "Software\\Microsoft\\Windows\\CurrentVersion";
-        let subkey_name = match source {
-            StartupSource::RegistryRun => "Run",
-            StartupSource::RegistryRunOnce => "RunOnce",
-            _ => return Ok(false),
+    /// Full path of the key holding the entry's value, e.g. `HKCU\Software\...\Run`.
+    pub fn key_path(entry: &StartupEntry) -> Result<String> {
+        let (hkey, path) = Self::value_location(entry)?
+            .ok_or_else(|| anyhow::anyhow!("'{}' is not a registry entry", entry.name))?;
+        Ok(format!("{}\\{}", Self::hive_name(hkey), path))
+    }
+
+    /// Full path of the StartupApproved key that records whether the entry is
+    /// enabled, or `None` when disabling has to delete the value instead.
+    pub fn approval_key_path(entry: &StartupEntry) -> Result<Option<String>> {
+        Ok(Self::approval_location(entry)?
+            .map(|(hkey, path)| format!("{}\\{}", Self::hive_name(hkey), path)))
+    }
+
+    /// Writes a backed-up value back under `key_path` (as returned by
+    /// `key_path`) and restores the enabled state it had.
+    pub fn restore_entry(entry: &StartupEntry, key_path: &str) -> Result<()> {
+        let (hive, path) = key_path
+            .split_once('\\')
+            .ok_or_else(|| anyhow::anyhow!("Invalid registry path '{}'", key_path))?;
+        let hkey = match hive {
+            "HKCU" => HKEY_CURRENT_USER,
+            "HKLM" => HKEY_LOCAL_MACHINE,
+            "HKU" => HKEY_USERS,
+            _ => anyhow::bail!("Unsupported registry hive '{}'", hive),
         };
 
-        if let Ok(subkey) = hkey_root.open_subkey(base_path) {
-            if let Ok(run_key) = subkey.open_subkey(subkey_name) {
-                return Ok(run_key.get_value::<String, _>(name).is_ok());
+        let (run_key, _) = RegKey::predef(hkey)
+            .create_subkey(path)
+            .with_context(|| format!("Failed to open {}", key_path))?;
+        Self::write_command(&run_key, entry).context("Failed to restore registry value")?;
+
+        match Self::approval_key(&entry.source) {
+            Some(approval) => {
+                Self::write_approval(hkey, &Self::in_user_hive(entry, approval), &entry.name, entry.enabled)
             }
+            None => Ok(()),
         }
+    }
 
-        Ok(false)
+    /// Writes the entry's value as it was read: commands with `%VAR%`
+    /// references go back as `REG_EXPAND_SZ` so they keep following the
+    /// environment.
+    fn write_command(run_key: &RegKey, entry: &StartupEntry) -> std::io::Result<()> {
+        match &entry.raw_command {
+            Some(raw) if raw.contains('%') => {
+                let bytes = raw
+                    .encode_utf16()
+                    .chain(std::iter::once(0))
+                    .flat_map(u16::to_le_bytes)
+                    .collect();
+                run_key.set_raw_value(
+                    &entry.name,
+                    &RegValue {
+                        bytes,
+                        vtype: REG_EXPAND_SZ,
+                    },
+                )
+            }
+            _ => run_key.set_value(&entry.name, &entry.stored_command()),
+        }
+    }
+
+    /// Opens the Registry Editor at the key holding the entry's value and
+    /// returns that key's path. `/m` starts a new window even when regedit
+    /// is already open, since a running one ignores `LastKey`. Starting it
+    /// through `start` lets Windows ask for elevation like Explorer would.
+    pub fn open_in_regedit(entry: &StartupEntry) -> Result<String> {
+        let (hkey, path) = Self::value_location(entry)?
+            .ok_or_else(|| anyhow::anyhow!("'{}' is not a registry entry", entry.name))?;
+        let root = match hkey {
+            HKEY_CURRENT_USER => "HKEY_CURRENT_USER",
+            HKEY_USERS => "HKEY_USERS",
+            _ => "HKEY_LOCAL_MACHINE",
+        };
+
+        let (applet, _) = RegKey::predef(HKEY_CURRENT_USER)
+            .create_subkey(REGEDIT_APPLET_KEY)
+            .context("Failed to open regedit's settings")?;
+        applet
+            .set_value("LastKey", &format!("Computer\\{}\\{}", root, path))
+            .context("Failed to set regedit's last key")?;
+
+        std::process::Command::new("cmd")
+            .args(["/C", "start", "", "regedit.exe", "/m"])
+            .spawn()
+            .context("Failed to start regedit")?;
+        Ok(format!("{}\\{}", Self::hive_name(hkey), path))
+    }
+
+    fn hive_name(hkey: HKEY) -> &'static str {
+        match hkey {
+            HKEY_CURRENT_USER => "HKCU",
+            HKEY_USERS => "HKU",
+            _ => "HKLM",
+        }
+    }
+
+    /// Marks Run values disabled in StartupApproved so the value survives and
+    /// can be re-enabled. RunOnce and RunServices values have no such switch
+    /// and are deleted.
+    pub fn disable_entry(entry: &StartupEntry) -> Result<()> {
+        let Some((hkey, path)) = Self::value_location(entry)? else {
+            return Ok(());
+        };
+        Self::disable_at(entry, (hkey, &path), Self::approval_location(entry)?)
+    }
+
+    /// Disables the entry's value in the key at `run`, through the
+    /// StartupApproved key at `approval` if it has one.
+    fn disable_at(entry: &StartupEntry, run: (HKEY, &str), approval: Option<(HKEY, String)>) -> Result<()> {
+        match approval {
+            Some((hkey, path)) => Self::write_approval(hkey, &path, &entry.name, false),
+            None => Self::open_key_for_writing(run.0, run.1)?
+                .delete_value(&entry.name)
+                .context("Failed to disable entry"),
+        }
+    }
+
+    /// Flips the StartupApproved blob back to enabled, re-creating the value
+    /// from the stored command first if an older version deleted it.
+    pub fn enable_entry(entry: &StartupEntry) -> Result<()> {
+        let Some((hkey, path)) = Self::value_location(entry)? else {
+            return Ok(());
+        };
+        Self::enable_at(entry, (hkey, &path), Self::approval_location(entry)?)
+    }
+
+    /// Enables the entry's value in the key at `run`, like `disable_at`.
+    fn enable_at(entry: &StartupEntry, run: (HKEY, &str), approval: Option<(HKEY, String)>) -> Result<()> {
+        let run_key = Self::open_key_for_writing(run.0, run.1)?;
+        if run_key.get_raw_value(&entry.name).is_err() {
+            Self::write_command(&run_key, entry).context("Failed to enable entry")?;
+        }
+        match approval {
+            Some((hkey, path)) => Self::write_approval(hkey, &path, &entry.name, true),
+            None => Ok(()),
+        }
+    }
+
+    /// Creates a Run or RunOnce value in HKCU (`EntryScope::User`) or HKLM
+    /// (`EntryScope::Machine`). An existing value of that name is only
+    /// replaced with `force`. Run values are also marked enabled in
+    /// StartupApproved, so a stale blob can't keep the new one switched off.
+    pub fn add_entry(name: &str, command: &str, source: &StartupSource, scope: EntryScope, force: bool) -> Result<()> {
+        let path = Self::run_key_path(source)
+            .ok_or_else(|| anyhow::anyhow!("Entries can't be added to {}", source))?;
+        let hkey = match scope {
+            EntryScope::User => HKEY_CURRENT_USER,
+            EntryScope::Machine => HKEY_LOCAL_MACHINE,
+            EntryScope::OtherUser => anyhow::bail!("Entries can only be added for the current user or all users"),
+        };
+
+        let (run_key, _) = RegKey::predef(hkey)
+            .create_subkey(path)
+            .with_context(|| format!("Failed to open {}\\{}", Self::hive_name(hkey), path))?;
+        if !force && run_key.get_raw_value(name).is_ok() {
+            anyhow::bail!(
+                "'{}' already exists in {}\\{} (use --force to overwrite it)",
+                name,
+                Self::hive_name(hkey),
+                path
+            );
+        }
+
+        let entry = StartupEntry::new(name.to_string(), paths::expand_env_vars(command), source.clone(), true)
+            .with_raw_command(command.to_string())
+            .with_scope(scope);
+        Self::write_command(&run_key, &entry).context("Failed to write registry value")?;
+
+        match Self::approval_key(source) {
+            Some(approval) => Self::write_approval(hkey, approval, name, true),
+            None => Ok(()),
+        }
+    }
+
+    pub fn remove_entry(entry: &StartupEntry) -> Result<()> {
+        let Some(run_key) = Self::open_for_writing(entry)? else {
+            return Ok(());
+        };
+        // Resolved first: once the value is gone its hive can't be looked up
+        let approval = Self::approval_location(entry)?;
+
+        run_key.delete_value(&entry.name).context("Failed to remove entry")?;
+
+        // Drop the matching StartupApproved blob so it can't outlive the value
+        if let Some((hkey, path)) = approval {
+            if let Ok(approved) = RegKey::predef(hkey).open_subkey_with_flags(&path, KEY_WRITE) {
+                let _ = approved.delete_value(&entry.name);
+            }
+        }
+        Ok(())
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stand-ins for a Run key and its StartupApproved key under a scratch
+    /// HKCU key, so the tests never create a real startup entry. The whole
+    /// scratch key is deleted on drop, even when an assertion fails halfway.
+    struct ScratchKeys {
+        root: String,
+        run: String,
+        approval: String,
+    }
+
+    impl ScratchKeys {
+        fn new(name: &str) -> Self {
+            let root = format!("Software\\DeepBootTest-{}-{}", name, std::process::id());
+            let keys = Self {
+                run: format!("{}\\Run", root),
+                approval: format!("{}\\StartupApproved\\Run", root),
+                root,
+            };
+            RegKey::predef(HKEY_CURRENT_USER).create_subkey(&keys.run).unwrap();
+            keys
+        }
+
+        fn run(&self) -> (HKEY, &str) {
+            (HKEY_CURRENT_USER, &self.run)
+        }
+
+        fn approval(&self) -> Option<(HKEY, String)> {
+            Some((HKEY_CURRENT_USER, self.approval.clone()))
+        }
+
+        fn run_key(&self) -> RegKey {
+            RegKey::predef(HKEY_CURRENT_USER).open_subkey(&self.run).unwrap()
+        }
+
+        fn approval_state(&self, name: &str) -> Option<bool> {
+            RegKey::predef(HKEY_CURRENT_USER)
+                .open_subkey(&self.approval)
+                .and_then(|key| key.get_raw_value(name))
+                .ok()
+                .map(|value| decode_approval(&value.bytes))
+        }
+    }
+
+    impl Drop for ScratchKeys {
+        fn drop(&mut self) {
+            let _ = RegKey::predef(HKEY_CURRENT_USER).delete_subkey_all(&self.root);
+        }
+    }
+
+    /// A Run entry whose stored command differs from the value in the key,
+    /// which shows whether enabling rewrote the value.
+    fn mock_entry() -> StartupEntry {
+        StartupEntry::new("Updater".to_string(), "cmd.exe /c rem".to_string(), StartupSource::RegistryRun, true)
+            .with_scope(EntryScope::User)
+    }
+
+    #[test]
+    fn encodes_the_state_byte() {
+        let enabled = encode_approval(true);
+        assert_eq!(enabled.len(), 12);
+        assert_eq!(enabled[0], 0x02);
+        assert!(enabled[1..].iter().all(|byte| *byte == 0));
+
+        let disabled = encode_approval(false);
+        assert_eq!(disabled.len(), 12);
+        assert_eq!(disabled[0], 0x03);
+        assert_eq!(&disabled[1..4], &[0, 0, 0]);
+        // The FILETIME of the moment it was disabled, well after 1601
+        assert!(u64::from_le_bytes(disabled[4..].try_into().unwrap()) > FILETIME_UNIX_OFFSET * 10_000_000);
+    }
+
+    #[test]
+    fn decodes_even_states_as_enabled() {
+        assert!(decode_approval(&encode_approval(true)));
+        assert!(!decode_approval(&encode_approval(false)));
+        assert!(decode_approval(&[0x06, 0, 0, 0]));
+        assert!(!decode_approval(&[0x01]));
+        assert!(!decode_approval(&[0x07, 0, 0, 0]));
+        assert!(decode_approval(&[]));
+    }
+
+    #[test]
+    fn disable_then_enable_keeps_the_existing_value() {
+        let keys = ScratchKeys::new("approval");
+        let entry = mock_entry();
+        keys.run_key().set_value(&entry.name, &"cmd.exe /c exit").unwrap();
+
+        RegistryScanner::disable_at(&entry, keys.run(), keys.approval()).unwrap();
+        assert_eq!(keys.approval_state(&entry.name), Some(false));
+        assert!(keys.run_key().get_raw_value(&entry.name).is_ok());
+
+        RegistryScanner::enable_at(&entry, keys.run(), keys.approval()).unwrap();
+        assert_eq!(keys.approval_state(&entry.name), Some(true));
+        assert_eq!(keys.run_key().get_value::<String, _>(&entry.name).unwrap(), "cmd.exe /c exit");
+    }
+
+    #[test]
+    fn enabling_recreates_a_deleted_value() {
+        let keys = ScratchKeys::new("no_approval");
+        let entry = mock_entry();
+        keys.run_key().set_value(&entry.name, &"cmd.exe /c exit").unwrap();
+
+        // Without a StartupApproved key, disabling deletes the value
+        RegistryScanner::disable_at(&entry, keys.run(), None).unwrap();
+        assert!(keys.run_key().get_raw_value(&entry.name).is_err());
+
+        RegistryScanner::enable_at(&entry, keys.run(), None).unwrap();
+        assert_eq!(keys.run_key().get_value::<String, _>(&entry.name).unwrap(), "cmd.exe /c rem");
+    }
+}
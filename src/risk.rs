@@ -0,0 +1,147 @@
+use crate::models::StartupEntry;
+use crate::signature::{self, SignatureStatus};
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+// The heuristic is advisory: it points at entries worth a closer look, it
+// doesn't say an entry is malicious. Each signal adds points:
+//   invalid signature                             3
+//   unsigned executable                           2
+//   file placed or changed in the last 14 days    1
+//   runs from a user-writable folder              1
+// 3 or more points rate High, 2 Medium, less Low. Entries whose file is
+// missing rate Low; the orphan flag covers them.
+const INVALID_SIGNATURE_POINTS: u32 = 3;
+const UNSIGNED_POINTS: u32 = 2;
+const RECENT_POINTS: u32 = 1;
+const USER_WRITABLE_POINTS: u32 = 1;
+const HIGH_POINTS: u32 = 3;
+const MEDIUM_POINTS: u32 = 2;
+
+/// Files created or modified within this many days count as recently added.
+const RECENT_DAYS: i64 = 14;
+
+/// Folders any user can write to, where installed programs rarely live.
+const USER_WRITABLE_DIRS: &[&str] = &["\\appdata\\", "\\temp\\", "\\tmp\\", "\\downloads\\", "\\users\\public\\"];
+
+/// How much an entry deserves a closer look, from Low to High.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    /// The next floor when cycling, wrapping back to showing everything.
+    pub fn next(&self) -> Self {
+        match self {
+            RiskLevel::Low => RiskLevel::Medium,
+            RiskLevel::Medium => RiskLevel::High,
+            RiskLevel::High => RiskLevel::Low,
+        }
+    }
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskLevel::Low => write!(f, "Low"),
+            RiskLevel::Medium => write!(f, "Medium"),
+            RiskLevel::High => write!(f, "High"),
+        }
+    }
+}
+
+/// An entry's risk rating and the signals behind it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Risk {
+    pub level: RiskLevel,
+    pub reasons: Vec<String>,
+}
+
+impl Risk {
+    /// Sets the risk of every entry. Verifies each executable's signature,
+    /// so this is as slow as sorting by signature the first time.
+    pub fn annotate(entries: &mut [StartupEntry]) {
+        for entry in entries.iter_mut() {
+            entry.risk = Some(Self::assess(entry));
+        }
+    }
+
+    pub fn assess(entry: &StartupEntry) -> Self {
+        Self::with_signature(entry, signature::entry_status(entry))
+    }
+
+    /// Like `assess`, but only from an already verified signature: `None`
+    /// while the signature is still being checked in the background.
+    pub fn assess_cached(entry: &StartupEntry) -> Option<Self> {
+        signature::peek_entry_status(entry).map(|status| Self::with_signature(entry, status))
+    }
+
+    fn with_signature(entry: &StartupEntry, signature: SignatureStatus) -> Self {
+        let mut points = 0;
+        let mut reasons = Vec::new();
+
+        match signature {
+            SignatureStatus::Invalid => {
+                points += INVALID_SIGNATURE_POINTS;
+                reasons.push("invalid signature".to_string());
+            }
+            SignatureStatus::Unsigned => {
+                points += UNSIGNED_POINTS;
+                reasons.push("unsigned".to_string());
+            }
+            SignatureStatus::Signed(_) => {}
+            SignatureStatus::Missing => {
+                return Self { level: RiskLevel::Low, reasons: vec!["file not found".to_string()] };
+            }
+        }
+
+        if let Some(path) = entry.target_path() {
+            if let Some(added) = added_at(&path) {
+                let age = Local::now() - added;
+                if age < Duration::days(RECENT_DAYS) {
+                    points += RECENT_POINTS;
+                    reasons.push(format!("added {} days ago", age.num_days().max(0)));
+                }
+            }
+            let lower = path.to_string_lossy().to_lowercase();
+            if let Some(dir) = USER_WRITABLE_DIRS.iter().find(|dir| lower.contains(*dir)) {
+                points += USER_WRITABLE_POINTS;
+                reasons.push(format!("runs from {}", dir.trim_matches('\\')));
+            }
+        }
+
+        let level = if points >= HIGH_POINTS {
+            RiskLevel::High
+        } else if points >= MEDIUM_POINTS {
+            RiskLevel::Medium
+        } else {
+            RiskLevel::Low
+        };
+        Self { level, reasons }
+    }
+}
+
+impl fmt::Display for Risk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.reasons.is_empty() {
+            write!(f, "{}", self.level)
+        } else {
+            write!(f, "{} ({})", self.level, self.reasons.join(", "))
+        }
+    }
+}
+
+/// When the file arrived: the later of its creation and modification
+/// times. Copying keeps the modification time but sets a new creation
+/// time, so the creation time usually tells when it landed on this disk.
+fn added_at(path: &Path) -> Option<DateTime<Local>> {
+    let metadata = fs::metadata(path).ok()?;
+    let times = [metadata.created().ok(), metadata.modified().ok()];
+    times.into_iter().flatten().max().map(DateTime::<Local>::from)
+}
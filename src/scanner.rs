@@ -0,0 +1,547 @@
+use crate::config::AppConfig;
+use crate::logger::ActionLogger;
+use crate::models::{StartupEntry, StartupSource};
+use crate::registry::RegistryScanner;
+use crate::services::ServicesScanner;
+use crate::startup_folder::StartupFolderScanner;
+use crate::task_scheduler::TaskSchedulerScanner;
+use crate::wmi::WmiScanner;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Instant;
+
+/// How often a scan in progress reports that it is still waiting.
+const SCAN_TICK: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Settings that widen what the scanners read, taken from the caller's
+/// config so command-line overrides apply and nothing rereads the file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanOptions {
+    pub scan_other_users: bool,
+    pub include_microsoft_tasks: bool,
+    pub wmi_cross_check: bool,
+}
+
+impl ScanOptions {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            scan_other_users: config.scan_other_users,
+            include_microsoft_tasks: config.include_microsoft_tasks,
+            wmi_cross_check: config.wmi_cross_check,
+        }
+    }
+}
+
+/// One of the independently scannable startup locations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanSource {
+    TaskScheduler,
+    Registry,
+    Services,
+    StartupFolder,
+    Wmi, // Cross-check of the others, not part of ALL; see `scanned`
+}
+
+impl ScanSource {
+    pub const ALL: &'static [ScanSource] = &[
+        ScanSource::TaskScheduler,
+        ScanSource::Registry,
+        ScanSource::Services,
+        ScanSource::StartupFolder,
+    ];
+
+    /// The sources a scan with `options` runs: every dedicated scanner, and
+    /// WMI when `wmi_cross_check` is on.
+    pub fn scanned(options: &ScanOptions) -> Vec<ScanSource> {
+        let mut sources = ScanSource::ALL.to_vec();
+        if options.wmi_cross_check {
+            sources.push(ScanSource::Wmi);
+        }
+        sources
+    }
+
+    /// Identifier used in the config file and on the command line.
+    pub fn key(&self) -> &'static str {
+        match self {
+            ScanSource::TaskScheduler => "task_scheduler",
+            ScanSource::Registry => "registry",
+            ScanSource::Services => "services",
+            ScanSource::StartupFolder => "startup_folder",
+            ScanSource::Wmi => "wmi",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        ScanSource::ALL.iter().copied().find(|s| s.key() == key)
+    }
+
+    /// Turns the configured lookup order into sources. Unknown keys are skipped
+    /// and sources missing from the list are appended so none is left out.
+    pub fn parse_order(keys: &[String]) -> Vec<ScanSource> {
+        let mut order: Vec<ScanSource> = Vec::new();
+        for key in keys {
+            match Self::from_key(key) {
+                Some(source) if !order.contains(&source) => order.push(source),
+                Some(_) => {}
+                None => log::warn!("Ignoring unknown scan source '{}' in lookup_order", key),
+            }
+        }
+        for source in ScanSource::ALL {
+            if !order.contains(source) {
+                order.push(*source);
+            }
+        }
+        order
+    }
+
+    /// Whether this source is the one that reported `entry`.
+    pub fn covers(&self, entry: &StartupEntry) -> bool {
+        if WmiScanner::reported_only_by_wmi(entry) {
+            return *self == ScanSource::Wmi;
+        }
+        match self {
+            ScanSource::TaskScheduler => entry.source == StartupSource::TaskScheduler,
+            ScanSource::Registry => matches!(
+                entry.source,
+                StartupSource::RegistryRun
+                    | StartupSource::RegistryRunOnce
+                    | StartupSource::RegistryRunServices
+                    | StartupSource::RegistryWow6432Node
+            ),
+            ScanSource::Services => entry.source == StartupSource::Service,
+            ScanSource::StartupFolder => entry.source == StartupSource::StartupFolder,
+            ScanSource::Wmi => false,
+        }
+    }
+
+    pub fn scan(&self, options: &ScanOptions) -> Result<Vec<StartupEntry>> {
+        match self {
+            ScanSource::TaskScheduler => TaskSchedulerScanner::scan(options.include_microsoft_tasks),
+            ScanSource::Registry => RegistryScanner::scan_all(options.scan_other_users),
+            ScanSource::Services => ServicesScanner::scan(),
+            ScanSource::StartupFolder => StartupFolderScanner::scan(),
+            ScanSource::Wmi => WmiScanner::scan(options.scan_other_users),
+        }
+    }
+}
+
+impl fmt::Display for ScanSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanSource::TaskScheduler => write!(f, "Task Scheduler"),
+            ScanSource::Registry => write!(f, "Registry"),
+            ScanSource::Services => write!(f, "Services"),
+            ScanSource::StartupFolder => write!(f, "Startup Folders"),
+            ScanSource::Wmi => write!(f, "WMI"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SourceProgress {
+    timestamp: String,
+    #[serde(default)]
+    options: Option<ScanOptions>, // None in files written before options were saved
+    entries: Vec<StartupEntry>,
+}
+
+impl SourceProgress {
+    /// Reads saved results back, unless they were scanned with other
+    /// options or are older than `max_age`.
+    fn load(path: &Path, options: &ScanOptions, max_age: Duration) -> Option<(DateTime<Local>, Vec<StartupEntry>)> {
+        let content = fs::read_to_string(path).ok()?;
+        let saved: SourceProgress = serde_json::from_str(&content).ok()?;
+        if saved.options.as_ref() != Some(options) {
+            return None;
+        }
+        let timestamp = DateTime::parse_from_rfc3339(&saved.timestamp)
+            .ok()?
+            .with_timezone(&Local);
+
+        if Local::now() - timestamp > max_age {
+            return None;
+        }
+        Some((timestamp, saved.entries))
+    }
+
+    fn to_json(entries: &[StartupEntry], options: &ScanOptions) -> serde_json::Result<String> {
+        serde_json::to_string(&SourceProgress {
+            timestamp: Local::now().to_rfc3339(),
+            options: Some(*options),
+            entries: entries.to_vec(),
+        })
+    }
+}
+
+/// Per-source scan results written as each source completes, so an
+/// interrupted scan can pick up where it stopped on the next launch.
+pub struct ScanProgress {
+    progress_dir: PathBuf,
+    max_age: Duration,
+}
+
+impl ScanProgress {
+    pub fn new(max_age_minutes: u64) -> Result<Self> {
+        let progress_dir = Self::dir().ok_or_else(|| anyhow::anyhow!("Failed to get data directory"))?;
+
+        if !progress_dir.exists() {
+            fs::create_dir_all(&progress_dir)
+                .context("Failed to create scan progress directory")?;
+        }
+
+        Ok(Self {
+            progress_dir,
+            max_age: Duration::minutes(max_age_minutes as i64),
+        })
+    }
+
+    fn dir() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join("deepboot").join("scan_progress"))
+    }
+
+    fn path(&self, source: ScanSource) -> PathBuf {
+        self.progress_dir.join(format!("{}.json", source.key()))
+    }
+
+    /// Returns the saved results for a source if they were scanned with the
+    /// same `options` and are recent enough to reuse.
+    pub fn load(&self, source: ScanSource, options: &ScanOptions) -> Option<(DateTime<Local>, Vec<StartupEntry>)> {
+        SourceProgress::load(&self.path(source), options, self.max_age)
+    }
+
+    pub fn save(&self, source: ScanSource, options: &ScanOptions, entries: &[StartupEntry]) -> Result<()> {
+        let content = SourceProgress::to_json(entries, options).context("Failed to serialize scan progress")?;
+        fs::write(self.path(source), content).context("Failed to write scan progress")
+    }
+
+    /// Drops all saved progress once a scan has run to completion.
+    pub fn clear(&self) {
+        for source in ScanSource::ALL {
+            let _ = fs::remove_file(self.path(*source));
+        }
+    }
+}
+
+/// Results of the last full scan, reused by later launches while they are
+/// younger than `scan_cache_seconds`.
+pub struct ScanCache;
+
+impl ScanCache {
+    fn path() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join("deepboot").join("scan_cache.json"))
+    }
+
+    /// Returns the cached entries and when they were scanned, unless they
+    /// were scanned with other `options`, are older than `max_age_seconds`
+    /// or are unreadable.
+    pub fn load(max_age_seconds: u64, options: &ScanOptions) -> Option<(DateTime<Local>, Vec<StartupEntry>)> {
+        let (timestamp, mut entries) =
+            SourceProgress::load(&Self::path()?, options, Duration::seconds(max_age_seconds as i64))?;
+        StartupEntry::assign_ids(&mut entries);
+        Some((timestamp, entries))
+    }
+
+    pub fn save(entries: &[StartupEntry], options: &ScanOptions) -> Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow::anyhow!("Failed to get data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create data directory")?;
+        }
+        let content = SourceProgress::to_json(entries, options).context("Failed to serialize scan cache")?;
+        fs::write(path, content).context("Failed to write scan cache")
+    }
+
+    /// Drops the cache and the progress of an interrupted scan after anything
+    /// changed startup entries, so the next launch scans again instead of
+    /// showing the old state.
+    pub fn invalidate() {
+        if let Some(path) = Self::path() {
+            let _ = fs::remove_file(path);
+        }
+        if let Some(dir) = ScanProgress::dir() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+/// Progress of a full scan, reported as each source starts and finishes.
+pub enum ScanUpdate {
+    Reused { source: ScanSource, timestamp: DateTime<Local>, entries: usize },
+    Started(ScanSource),
+    Found(ScanSource, usize),
+    Failed(ScanSource, String),
+    Waiting, // Nothing finished within the last tick
+    Finished(std::time::Duration),
+}
+
+impl ScanUpdate {
+    /// Prints the update the way a console scan reports it.
+    pub fn print(&self) {
+        match self {
+            ScanUpdate::Waiting => {}
+            ScanUpdate::Failed(..) => eprintln!("{}", self),
+            _ => println!("{}", self),
+        }
+    }
+}
+
+impl fmt::Display for ScanUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanUpdate::Reused { source, timestamp, entries } => write!(
+                f,
+                "Reusing {} results from an interrupted scan at {} ({} entries, not rescanned)",
+                source,
+                timestamp.format("%H:%M:%S"),
+                entries
+            ),
+            ScanUpdate::Started(source) => write!(f, "Scanning {}...", source),
+            ScanUpdate::Found(source, count) => write!(f, "  Found {} entries in {}", count, source),
+            ScanUpdate::Failed(source, e) => write!(f, "  Warning: Failed to scan {}: {}", source, e),
+            ScanUpdate::Waiting => Ok(()),
+            ScanUpdate::Finished(elapsed) => write!(f, "Scan finished in {:.1}s", elapsed.as_secs_f64()),
+        }
+    }
+}
+
+pub struct Scanner;
+
+impl Scanner {
+    /// Scans every startup location at once, printing progress as each
+    /// finishes. A source that fails to scan is reported and skipped. With
+    /// `progress`, recent results left by an interrupted run are reused
+    /// instead of rescanning.
+    pub fn scan_all(
+        action_logger: &ActionLogger,
+        progress: Option<&ScanProgress>,
+        options: &ScanOptions,
+    ) -> Vec<StartupEntry> {
+        Self::scan_with(action_logger, progress, options, |update| update.print())
+    }
+
+    /// Like `scan_all`, but hands each step to `report` instead of printing
+    /// it. `report` also receives `ScanUpdate::Waiting` regularly while
+    /// scans are running, so a caller can animate its progress display.
+    pub fn scan_with(
+        action_logger: &ActionLogger,
+        progress: Option<&ScanProgress>,
+        options: &ScanOptions,
+        mut report: impl FnMut(ScanUpdate),
+    ) -> Vec<StartupEntry> {
+        let started = Instant::now();
+        let mut results: Vec<Vec<StartupEntry>> = vec![Vec::new(); ScanSource::ALL.len()];
+        let mut pending = Vec::new();
+
+        for (i, source) in ScanSource::ALL.iter().enumerate() {
+            match progress.and_then(|p| p.load(*source, options)) {
+                Some((timestamp, entries)) => {
+                    report(ScanUpdate::Reused { source: *source, timestamp, entries: entries.len() });
+                    results[i] = entries;
+                }
+                None => {
+                    report(ScanUpdate::Started(*source));
+                    pending.push(*source);
+                }
+            }
+        }
+        // The cross-check is quick to redo, so it never resumes
+        if options.wmi_cross_check {
+            report(ScanUpdate::Started(ScanSource::Wmi));
+            pending.push(ScanSource::Wmi);
+        }
+
+        let mut reported_by_wmi = Vec::new();
+        Self::scan_concurrently(&pending, options, |done| match done {
+            Some((ScanSource::Wmi, Ok(entries))) => {
+                report(ScanUpdate::Found(ScanSource::Wmi, entries.len()));
+                reported_by_wmi = entries;
+            }
+            Some((source, Ok(entries))) => {
+                report(ScanUpdate::Found(source, entries.len()));
+                let _ = action_logger.log_scan(&source.to_string(), entries.len());
+                if let Some(progress) = progress {
+                    if let Err(e) = progress.save(source, options, &entries) {
+                        log::warn!("Failed to save scan progress for {}: {}", source, e);
+                    }
+                }
+                results[Self::position(source)] = entries;
+            }
+            Some((source, Err(e))) => report(ScanUpdate::Failed(source, e.to_string())),
+            None => report(ScanUpdate::Waiting),
+        });
+
+        if let Some(progress) = progress {
+            progress.clear();
+        }
+
+        report(ScanUpdate::Finished(started.elapsed()));
+
+        // Keep the usual source order however the scans finished
+        let mut entries: Vec<StartupEntry> = results.into_iter().flatten().collect();
+        WmiScanner::cross_check(&mut entries, reported_by_wmi);
+        StartupEntry::assign_ids(&mut entries);
+        entries
+    }
+
+    /// Scans every source without printing, for rescans while the TUI owns
+    /// the terminal. A source that fails to scan is logged and skipped, and
+    /// returned with the others that failed.
+    pub fn rescan(options: &ScanOptions) -> (Vec<StartupEntry>, Vec<ScanSource>) {
+        let mut results: Vec<Vec<StartupEntry>> = vec![Vec::new(); ScanSource::ALL.len()];
+        let mut reported_by_wmi = Vec::new();
+        let mut failed = Vec::new();
+        Self::scan_concurrently(&ScanSource::scanned(options), options, |done| match done {
+            Some((ScanSource::Wmi, Ok(entries))) => reported_by_wmi = entries,
+            Some((source, Ok(entries))) => results[Self::position(source)] = entries,
+            Some((source, Err(e))) => {
+                log::warn!("Failed to scan {}: {}", source, e);
+                failed.push(source);
+            }
+            None => {}
+        });
+        let mut entries: Vec<StartupEntry> = results.into_iter().flatten().collect();
+        WmiScanner::cross_check(&mut entries, reported_by_wmi);
+        StartupEntry::assign_ids(&mut entries);
+        (entries, failed)
+    }
+
+    /// Runs each source's scan on its own thread and hands every result to
+    /// `on_done` on the calling thread as soon as it arrives, or `None` when
+    /// a tick passes without one. The COM-based scanners initialize COM on
+    /// the thread they run on, so each keeps its own apartment.
+    fn scan_concurrently(
+        sources: &[ScanSource],
+        options: &ScanOptions,
+        mut on_done: impl FnMut(Option<(ScanSource, Result<Vec<StartupEntry>>)>),
+    ) {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::scope(|scope| {
+            for source in sources {
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    let _ = sender.send((*source, source.scan(options)));
+                });
+            }
+            drop(sender);
+
+            loop {
+                match receiver.recv_timeout(SCAN_TICK) {
+                    Ok(done) => on_done(Some(done)),
+                    Err(mpsc::RecvTimeoutError::Timeout) => on_done(None),
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+    }
+
+    fn position(source: ScanSource) -> usize {
+        ScanSource::ALL.iter().position(|s| *s == source).unwrap_or(0)
+    }
+
+    /// Finds a single entry by name (case-insensitive) without a full scan.
+    /// Sources are scanned in `order` and the search stops at the first source
+    /// with exactly one match; several matches in one source are ambiguous.
+    pub fn find_entry(name: &str, order: &[ScanSource], options: &ScanOptions) -> Result<StartupEntry> {
+        for source in order {
+            let entries = match source.scan(options) {
+                Ok(entries) => entries,
+                // With a single known source there is nothing else to fall back on
+                Err(e) if order.len() == 1 => {
+                    return Err(e).with_context(|| format!("Failed to scan {}", source))
+                }
+                Err(e) => {
+                    log::warn!("Failed to scan {}: {}", source, e);
+                    continue;
+                }
+            };
+            let mut matches: Vec<StartupEntry> = entries
+                .into_iter()
+                .filter(|e| e.name.eq_ignore_ascii_case(name))
+                .collect();
+
+            match matches.len() {
+                0 => continue,
+                1 => return Ok(matches.remove(0)),
+                n => anyhow::bail!(
+                    "'{}' is ambiguous: {} entries in {} share that name ({})",
+                    name,
+                    n,
+                    source,
+                    matches
+                        .iter()
+                        .map(|e| format!("{} -> {}", e.source, e.command))
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                ),
+            }
+        }
+
+        anyhow::bail!("No startup entry named '{}' found", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file of its own under the system temp dir, removed on drop.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, content: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("deepboot_{}_{}.json", name, std::process::id()));
+            fs::write(&path, content).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn saved(options: &ScanOptions) -> String {
+        let entry = StartupEntry::new(
+            "Updater".to_string(),
+            "C:\\Tools\\updater.exe".to_string(),
+            StartupSource::RegistryRun,
+            true,
+        );
+        SourceProgress::to_json(&[entry], options).unwrap()
+    }
+
+    #[test]
+    fn saved_results_are_reused_with_the_same_options() {
+        let options = ScanOptions { scan_other_users: true, ..ScanOptions::default() };
+        let file = TempFile::new("progress_same_options", &saved(&options));
+        let (_, entries) = SourceProgress::load(&file.0, &options, Duration::minutes(5)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(SourceProgress::load(&file.0, &options, Duration::minutes(-1)).is_none());
+    }
+
+    #[test]
+    fn saved_results_are_discarded_when_the_options_change() {
+        let file = TempFile::new("progress_other_options", &saved(&ScanOptions::default()));
+        for options in [
+            ScanOptions { scan_other_users: true, ..ScanOptions::default() },
+            ScanOptions { include_microsoft_tasks: true, ..ScanOptions::default() },
+            ScanOptions { wmi_cross_check: true, ..ScanOptions::default() },
+        ] {
+            assert!(SourceProgress::load(&file.0, &options, Duration::minutes(5)).is_none(), "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn results_saved_without_options_are_discarded() {
+        let file = TempFile::new(
+            "progress_without_options",
+            &format!(r#"{{"timestamp":"{}","entries":[]}}"#, Local::now().to_rfc3339()),
+        );
+        assert!(SourceProgress::load(&file.0, &ScanOptions::default(), Duration::minutes(5)).is_none());
+    }
+}
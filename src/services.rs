@@ -1,95 +1,218 @@
-use crate::models::{StartupEntry, StartupSource};
+use crate::models::{EntryFlag, StartupEntry, StartupSource};
+use crate::paths;
+use crate::system_services::SystemServiceList;
 use anyhow::{Context, Result};
-use serde_json;
-use std::process::Command;
-
-// Common Windows system services that should be filtered out
-const SYSTEM_SERVICES: &[&str] = &[
-    "AudioSrv", "BITS", "Browser", "CryptSvc", "DcomLaunch", "Dhcp", "Dnscache",
-    "EventLog", "EventSystem", "FontCache", "gpsvc", "hidserv", "IKEEXT", "iphlpsvc",
-    "KeyIso", "LanmanServer", "LanmanWorkstation", "lmhosts", "MMCSS", "MpsSvc",
-    "MSiSCSI", "Netlogon", "netprofm", "NlaSvc", "nsi", "p2pimsvc", "p2psvc",
-    "PlugPlay", "PolicyAgent", "ProfSvc", "RasMan", "RemoteAccess", "RpcEptMapper",
-    "RpcSs", "SamSs", "Schedule", "SENS", "SessionEnv", "Spooler", "SysMain",
-    "Themes", "TrkWks", "TrustedInstaller", "UmRdpService", "VaultSvc", "VSS",
-    "W32Time", "Wcmsvc", "WcsPlugInService", "WdiServiceHost", "Winmgmt", "WinRM",
-    "WlanSvc", "wmiApSrv", "WMPNetworkSvc", "WSearch", "wuauserv", "WudfSvc",
-    "wscsvc", "WbioSrvc", "WinHttpAutoProxySvc", "WerSvc", "WebClient", "WaaSMedicSvc",
-    "UsoSvc", "UevAgentService", "TabletInputService", "SysMain", "StiSvc", "SstpSvc",
-    "SSDPSRV", "Spooler", "SstpSvc", "ShellHWDetection", "SCardSvr", "SCPolicySvc",
-    "SCardSvr", "SCPolicySvc", "RpcLocator", "RemoteRegistry", "RemoteAccess",
-    "RasAuto", "QWAVE", "PNRPsvc", "PNRPsvc", "PcaSvc", "PcaSvc", "PcaSvc",
-    "PcaSvc", "PcaSvc", "PcaSvc", "PcaSvc", "PcaSvc", "PcaSvc", "PcaSvc",
-];
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::thread;
+use std::time::{Duration, Instant};
+use windows::{
+    core::{HSTRING, PCWSTR},
+    Win32::Foundation::ERROR_MORE_DATA,
+    Win32::System::Services::*,
+};
+use winreg::enums::HKEY_LOCAL_MACHINE;
+use winreg::RegKey;
+
+/// How long `remove_service` waits for a stopping service to reach STOPPED.
+const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Win32 error codes `sc` exits with
+const ERROR_ACCESS_DENIED: i32 = 5;
+const ERROR_SERVICE_NOT_ACTIVE: i32 = 1062;
+const ERROR_SERVICE_DOES_NOT_EXIST: i32 = 1060;
 
 pub struct ServicesScanner;
 
+/// A service as reported by the SCM or `sc`, before classification.
+struct RawService {
+    name: String,
+    display_name: String,
+    path: String,
+    enabled: bool,
+    delayed: bool,
+    system_service: bool,
+}
+
+/// The parts of a service's configuration the scanner needs.
+struct ServiceConfig {
+    start_type: SERVICE_START_TYPE,
+    delayed: bool, // Automatic (Delayed Start)
+    binary_path: String,
+}
+
+/// Closes the wrapped SCM or service handle when dropped.
+struct ScHandle(SC_HANDLE);
+
+impl ScHandle {
+    fn manager() -> Result<Self> {
+        unsafe { OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_ENUMERATE_SERVICE) }
+            .map(ScHandle)
+            .context("Failed to open the Service Control Manager")
+    }
+}
+
+impl Drop for ScHandle {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseServiceHandle(self.0);
+        }
+    }
+}
+
+/// Outcome of deciding whether a service belongs to Windows.
+struct Classification {
+    is_system: bool,
+    impersonating: bool,
+    reason: String,
+}
+
 impl ServicesScanner {
     pub fn scan() -> Result<Vec<StartupEntry>> {
-        // Use PowerShell to get services more reliably
-        let ps_command = r#"
-            Get-WmiObject Win32_Service | Where-Object {
-                $_.StartMode -eq 'Auto' -and 
-                $_.PathName -ne $null -and
-                $_.SystemService -eq $false
-            } | Select-Object Name, DisplayName, PathName | ConvertTo-Json
-        "#;
-
-        let output = Command::new("powershell")
-            .args(&["-Command", ps_command])
-            .output()
-            .context("Failed to execute PowerShell command. Make sure you're on Windows.")?;
+        let services = match Self::query_with_scm() {
+            Ok(services) => services,
+            Err(e) => {
+                // Last resort when the Service Control Manager can't be opened
+                log::warn!("Failed to enumerate services natively, falling back to sc: {:#}", e);
+                Self::query_with_sc()?
+            }
+        };
 
-        if !output.status.success() {
-            // Fallback to sc query if PowerShell fails
-            return Self::scan_with_sc();
-        }
+        Ok(Self::classify_all(services))
+    }
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        
-        // Parse JSON output
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&output_str) {
-            let mut entries = Vec::new();
-            
-            let services: Vec<&serde_json::Value> = if json.is_array() {
-                json.as_array().unwrap().iter().collect()
-            } else if json.is_object() {
-                // Single service
-                vec![&json]
-            } else {
-                return Ok(Vec::new());
+    /// Lists services through the Service Control Manager, keeping those
+    /// `listed_as_enabled` accepts.
+    fn query_with_scm() -> Result<Vec<RawService>> {
+        let manager = ScHandle::manager()?;
+        let mut raw = Vec::new();
+        let mut buffer: Vec<u64> = Vec::new();
+        let mut resume = 0u32;
+
+        loop {
+            let mut needed = 0u32;
+            let mut returned = 0u32;
+            // Buffer of u64 so the structures in it are properly aligned
+            let bytes = unsafe {
+                std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8)
+            };
+            let result = unsafe {
+                EnumServicesStatusExW(
+                    manager.0,
+                    SC_ENUM_PROCESS_INFO,
+                    SERVICE_WIN32,
+                    SERVICE_STATE_ALL,
+                    Some(bytes),
+                    &mut needed,
+                    &mut returned,
+                    Some(&mut resume),
+                    PCWSTR::null(),
+                )
+            };
+            let more_data = match result {
+                Ok(()) => false,
+                Err(e) if e.code() == ERROR_MORE_DATA.to_hresult() => true,
+                Err(e) => return Err(e).context("Failed to enumerate services"),
             };
 
+            let services = unsafe {
+                std::slice::from_raw_parts(
+                    buffer.as_ptr() as *const ENUM_SERVICE_STATUS_PROCESSW,
+                    returned as usize,
+                )
+            };
             for service in services {
-                if let (Some(name), Some(display_name), Some(path_name)) = (
-                    service.get("Name").and_then(|v| v.as_str()),
-                    service.get("DisplayName").and_then(|v| v.as_str()),
-                    service.get("PathName").and_then(|v| v.as_str()),
-                ) {
-                    if !Self::is_system_service(name) {
-                        entries.push(
-                            StartupEntry::new(
-                                display_name.to_string(),
-                                path_name.to_string(),
-                                StartupSource::Service,
-                                true,
-                            )
-                            .with_description(format!("Service: {}", name)),
-                        );
+                let (name, display_name) = unsafe {
+                    (
+                        service.lpServiceName.to_string().unwrap_or_default(),
+                        service.lpDisplayName.to_string().unwrap_or_default(),
+                    )
+                };
+                let config = match Self::query_config(&manager, &name) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        log::debug!("Skipping service {}: {:#}", name, e);
+                        continue;
                     }
+                };
+                let Some(enabled) = Self::listed_as_enabled(config.start_type) else {
+                    continue;
+                };
+                if config.binary_path.is_empty() {
+                    continue;
                 }
+
+                raw.push(RawService {
+                    name,
+                    display_name,
+                    path: config.binary_path,
+                    enabled,
+                    delayed: config.delayed,
+                    system_service: false,
+                });
             }
 
-            Ok(entries)
-        } else {
-            // Fallback to sc query
-            Self::scan_with_sc()
+            if !more_data {
+                break;
+            }
+            let wanted = (needed as usize).div_ceil(8);
+            if wanted > buffer.len() {
+                buffer.resize(wanted, 0);
+            }
         }
+
+        Ok(raw)
+    }
+
+    /// Start type, delayed-start flag and binary path of one service, as
+    /// stored by the SCM.
+    fn query_config(manager: &ScHandle, service_name: &str) -> Result<ServiceConfig> {
+        let name = HSTRING::from(service_name);
+        let service = ScHandle(
+            unsafe { OpenServiceW(manager.0, &name, SERVICE_QUERY_CONFIG) }
+                .with_context(|| format!("Failed to open service '{}'", service_name))?,
+        );
+
+        let mut needed = 0u32;
+        // The first call only reports the size the configuration needs
+        let _ = unsafe { QueryServiceConfigW(service.0, None, 0, &mut needed) };
+        let mut buffer = vec![0u64; (needed as usize).div_ceil(8).max(1)];
+        let config = buffer.as_mut_ptr() as *mut QUERY_SERVICE_CONFIGW;
+        unsafe { QueryServiceConfigW(service.0, Some(config), (buffer.len() * 8) as u32, &mut needed) }
+            .with_context(|| format!("Failed to query service '{}'", service_name))?;
+
+        let mut delayed = SERVICE_DELAYED_AUTO_START_INFO::default();
+        let delayed_bytes = unsafe {
+            std::slice::from_raw_parts_mut(
+                &mut delayed as *mut _ as *mut u8,
+                std::mem::size_of::<SERVICE_DELAYED_AUTO_START_INFO>(),
+            )
+        };
+        // Not fatal: services on older systems simply have no delayed flag
+        let _ = unsafe {
+            QueryServiceConfig2W(
+                service.0,
+                SERVICE_CONFIG_DELAYED_AUTO_START_INFO,
+                Some(delayed_bytes),
+                &mut needed,
+            )
+        };
+
+        let config = unsafe { &*config };
+        Ok(ServiceConfig {
+            start_type: config.dwStartType,
+            delayed: delayed.fDelayedAutostart.as_bool(),
+            binary_path: unsafe { config.lpBinaryPathName.to_string() }
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        })
     }
 
-    fn scan_with_sc() -> Result<Vec<StartupEntry>> {
+    fn query_with_sc() -> Result<Vec<RawService>> {
         let output = Command::new("sc")
-            .args(&["query"])
+            .args(["query", "state=", "all"])
             .output()
             .context("Failed to execute 'sc query' command")?;
 
@@ -98,7 +221,7 @@ impl ServicesScanner {
         }
 
         let output_str = String::from_utf8_lossy(&output.stdout);
-        let mut entries = Vec::new();
+        let mut raw = Vec::new();
         let mut current_service = None;
 
         for line in output_str.lines() {
@@ -116,33 +239,225 @@ impl ServicesScanner {
                         .trim()
                         .to_string();
 
-                    // Check if it's a third-party service (not a Windows system service)
-                    if !Self::is_system_service(service_name) {
-                        // Get service binary path
-                        let binary_path = Self::get_service_binary_path(service_name)
-                            .unwrap_or_else(|_| "Unknown".to_string());
-
-                        let enabled = Self::is_service_enabled(service_name);
-
-                        entries.push(
-                            StartupEntry::new(
-                                display_name,
-                                binary_path,
-                                StartupSource::Service,
-                                enabled,
-                            )
-                            .with_description(format!("Service: {}", service_name)),
-                        );
-                    }
+                    let Some((start_type, delayed)) = Self::start_mode(service_name) else {
+                        continue;
+                    };
+                    let Some(enabled) = Self::listed_as_enabled(start_type) else {
+                        continue;
+                    };
+
+                    // Get service binary path
+                    let binary_path = Self::get_service_binary_path(service_name)
+                        .unwrap_or_else(|_| "Unknown".to_string());
+
+                    raw.push(RawService {
+                        name: service_name.clone(),
+                        display_name,
+                        path: binary_path,
+                        enabled,
+                        delayed,
+                        system_service: false,
+                    });
                 }
             }
         }
 
-        Ok(entries)
+        Ok(raw)
     }
 
-    fn is_system_service(service_name: &str) -> bool {
-        SYSTEM_SERVICES.contains(&service_name)
+    /// Drops Windows' own services and turns the rest into entries, recording
+    /// why each one was kept.
+    fn classify_all(services: Vec<RawService>) -> Vec<StartupEntry> {
+        let system_root = Self::system_root();
+        let system32 = format!("{}\\system32\\", system_root);
+        let system_services = SystemServiceList::new().unwrap_or_else(|e| {
+            log::warn!("Using the built-in system service list: {}", e);
+            SystemServiceList::default()
+        });
+
+        // Signatures only matter for System32 binaries that aren't otherwise
+        // known, so check just those in a single PowerShell call
+        // Shared-host services are judged by the DLL they load, not svchost
+        let targets: Vec<(Option<String>, Option<PathBuf>)> = services
+            .iter()
+            .map(|s| {
+                let hosted_dll = Self::hosted_dll(s);
+                let target = match hosted_dll {
+                    Some(ref dll) => Some(PathBuf::from(dll)),
+                    None => paths::resolve_executable(&s.path),
+                };
+                (hosted_dll, target)
+            })
+            .collect();
+
+        let candidates: Vec<PathBuf> = services
+            .iter()
+            .zip(&targets)
+            .filter(|(s, _)| !system_services.contains(&s.name) && !s.system_service)
+            .filter_map(|(_, (_, target))| target.clone())
+            .filter(|p| p.to_string_lossy().to_lowercase().starts_with(&system32))
+            .collect();
+        let signed = Self::microsoft_signed(&candidates);
+
+        let mut entries = Vec::new();
+        for (service, (hosted_dll, target)) in services.into_iter().zip(targets) {
+            let executable = target.map(|p| p.to_string_lossy().to_lowercase());
+            let classification =
+                Self::classify(&service, executable.as_deref(), &system_root, &signed, &system_services);
+            if classification.is_system {
+                log::debug!("Skipping system service {}: {}", service.name, classification.reason);
+                continue;
+            }
+
+            let mut entry = StartupEntry::new(
+                service.display_name,
+                service.path,
+                StartupSource::Service,
+                service.enabled,
+            )
+            .with_description(format!("Service: {}", service.name));
+            entry.delayed_start = service.delayed;
+            entry.classification = Some(classification.reason);
+            entry.hosted_dll = hosted_dll;
+            if classification.impersonating {
+                entry.flags.push(EntryFlag::ImpersonatesSystemService);
+            }
+            entries.push(entry);
+        }
+
+        entries
+    }
+
+    fn classify(
+        service: &RawService,
+        executable: Option<&str>,
+        system_root: &str,
+        signed: &HashSet<String>,
+        system_services: &SystemServiceList,
+    ) -> Classification {
+        let in_system_root = executable
+            .map(|exe| exe.starts_with(&format!("{}\\", system_root)))
+            .unwrap_or(false);
+        let in_system32 = executable
+            .map(|exe| exe.starts_with(&format!("{}\\system32\\", system_root)))
+            .unwrap_or(false);
+
+        // Names the user added are trusted as they are; checking where they
+        // run would flag them as disguised instead of hiding them
+        if system_services.contains(&service.name) && !SystemServiceList::is_builtin(&service.name) {
+            return Classification {
+                is_system: true,
+                impersonating: false,
+                reason: "System: added to the system service list".to_string(),
+            };
+        }
+
+        // (why it counts as a system service, how to describe a disguised one)
+        let known = if system_services.contains(&service.name) {
+            Some(("listed as a built-in Windows service", "named like a built-in Windows service"))
+        } else if service.system_service {
+            Some(("marked as a system service by Windows", "marked as a system service"))
+        } else {
+            None
+        };
+
+        if let Some((known, disguise)) = known {
+            // A system service name running from elsewhere is a classic disguise
+            if executable.is_some() && !in_system_root {
+                return Classification {
+                    is_system: false,
+                    impersonating: true,
+                    reason: format!("Third-party: {} but runs from outside %SystemRoot%", disguise),
+                };
+            }
+            return Classification {
+                is_system: true,
+                impersonating: false,
+                reason: format!("System: {}", known),
+            };
+        }
+
+        match executable {
+            Some(exe) if in_system32 && signed.contains(exe) => Classification {
+                is_system: true,
+                impersonating: false,
+                reason: "System: Microsoft-signed binary in System32".to_string(),
+            },
+            Some(_) if in_system32 => Classification {
+                is_system: false,
+                impersonating: true,
+                reason: "Third-party: binary in System32 is not signed by Microsoft".to_string(),
+            },
+            _ => Classification {
+                is_system: false,
+                impersonating: false,
+                reason: "Third-party: not a known Windows service and runs from outside System32"
+                    .to_string(),
+            },
+        }
+    }
+
+    /// For services run by svchost.exe, the DLL named by `ServiceDll` under the
+    /// service's `Parameters` key (or, for older services, the key itself).
+    fn hosted_dll(service: &RawService) -> Option<String> {
+        let host = paths::resolve_executable(&service.path)?;
+        if !host
+            .file_name()
+            .map(|name| name.eq_ignore_ascii_case("svchost.exe"))
+            .unwrap_or(false)
+        {
+            return None;
+        }
+
+        let services_key = RegKey::predef(HKEY_LOCAL_MACHINE)
+            .open_subkey(format!("SYSTEM\\CurrentControlSet\\Services\\{}", service.name))
+            .ok()?;
+        let dll: String = services_key
+            .open_subkey("Parameters")
+            .and_then(|parameters| parameters.get_value("ServiceDll"))
+            .or_else(|_| services_key.get_value("ServiceDll"))
+            .ok()?;
+
+        Some(paths::expand_env_vars(dll.trim()))
+    }
+
+    fn system_root() -> String {
+        std::env::var("SystemRoot")
+            .unwrap_or_else(|_| "C:\\Windows".to_string())
+            .trim_end_matches('\\')
+            .to_lowercase()
+    }
+
+    /// Returns the lowercase paths among `executables` that carry a valid
+    /// Microsoft Authenticode or catalog signature.
+    fn microsoft_signed(executables: &[PathBuf]) -> HashSet<String> {
+        if executables.is_empty() {
+            return HashSet::new();
+        }
+
+        let list = executables
+            .iter()
+            .map(|p| format!("'{}'", p.to_string_lossy().replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(",");
+        let ps_command = format!(
+            "Get-AuthenticodeSignature -LiteralPath {} -ErrorAction SilentlyContinue | \
+             Where-Object {{ $_.Status -eq 'Valid' -and $_.SignerCertificate.Subject -like '*O=Microsoft Corporation*' }} | \
+             ForEach-Object {{ $_.Path }}",
+            list
+        );
+
+        match Command::new("powershell").args(["-Command", &ps_command]).output() {
+            Ok(output) => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|line| line.trim().to_lowercase())
+                .filter(|line| !line.is_empty())
+                .collect(),
+            Err(e) => {
+                log::warn!("Failed to check service signatures: {}", e);
+                HashSet::new()
+            }
+        }
     }
 
     fn get_service_binary_path(service_name: &str) -> Result<String> {
@@ -159,7 +474,8 @@ impl ServicesScanner {
         
         for line in output_str.lines() {
             if line.trim().starts_with("BINARY_PATH_NAME") {
-                if let Some(path) = line.split(':').nth(1) {
+                // Split on the first colon only; the path has its own after the drive letter
+                if let Some((_, path)) = line.split_once(':') {
                     return Ok(path.trim().to_string());
                 }
             }
@@ -168,42 +484,237 @@ impl ServicesScanner {
         Ok("Unknown".to_string())
     }
 
-    fn is_service_enabled(service_name: &str) -> bool {
-        let output = Command::new("sc")
-            .args(&["qc", service_name])
-            .output();
+    /// The configured start type and whether automatic start is delayed,
+    /// read natively or, failing that, from `sc qc`.
+    fn start_mode(service_name: &str) -> Option<(SERVICE_START_TYPE, bool)> {
+        if let Ok(config) =
+            ScHandle::manager().and_then(|manager| Self::query_config(&manager, service_name))
+        {
+            return Some((config.start_type, config.delayed));
+        }
 
-        if let Ok(output) = output {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            // Check if service is set to auto-start
-            output_str.contains("AUTO_START") || output_str.contains("DEMAND_START")
-        } else {
-            false
+        let output = Command::new("sc").args(["qc", service_name]).output().ok()?;
+        Self::parse_start_mode(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Reads the numeric code from `sc qc`'s `START_TYPE : 2   AUTO_START`
+    /// line. Delayed auto-start services report the same code as plain
+    /// automatic ones, with `(DELAYED)` appended.
+    fn parse_start_mode(qc_output: &str) -> Option<(SERVICE_START_TYPE, bool)> {
+        let line = qc_output
+            .lines()
+            .find(|line| line.trim_start().starts_with("START_TYPE"))?;
+        let (_, value) = line.split_once(':')?;
+        let start_type = value.split_whitespace().next()?.parse().ok()?;
+        Some((SERVICE_START_TYPE(start_type), value.contains("(DELAYED)")))
+    }
+
+    /// Whether a service with this start type is listed, and as enabled or
+    /// not. Only automatic services run at boot; manual, boot and system
+    /// services aren't startup entries, while disabled ones are kept so they
+    /// can be enabled again.
+    fn listed_as_enabled(start_type: SERVICE_START_TYPE) -> Option<bool> {
+        match start_type {
+            SERVICE_AUTO_START => Some(true),
+            SERVICE_DISABLED => Some(false),
+            _ => None,
         }
     }
 
-    pub fn disable_service(entry: &StartupEntry) -> Result<()> {
-        // Extract service name from description
-        let service_name = entry
+    /// The service's key name, which the scanner stores in the description.
+    pub fn service_name(entry: &StartupEntry) -> Result<&str> {
+        entry
             .description
             .as_ref()
             .and_then(|d| d.strip_prefix("Service: "))
-            .ok_or_else(|| anyhow::anyhow!("Invalid service entry"))?;
+            .ok_or_else(|| anyhow::anyhow!("Invalid service entry"))
+    }
 
-        Command::new("sc")
-            .args(&["config", service_name, "start=", "disabled"])
-            .output()
-            .context("Failed to disable service")?;
+    pub fn disable_service(entry: &StartupEntry) -> Result<()> {
+        let service_name = Self::service_name(entry)?;
+        let output = Self::run_sc(&["config", service_name, "start=", "disabled"])?;
+        if !output.status.success() {
+            return Err(Self::sc_error("disable", service_name, &output));
+        }
+        Ok(())
+    }
+
+    /// Sets the service back to automatic start, delayed if it was before.
+    pub fn enable_service(entry: &StartupEntry) -> Result<()> {
+        let service_name = Self::service_name(entry)?;
+        let output = Self::run_sc(&["config", service_name, "start=", Self::auto_start_mode(entry.delayed_start)])?;
+        if !output.status.success() {
+            return Err(Self::sc_error("enable", service_name, &output));
+        }
+        Ok(())
+    }
+
+    /// Switches an automatic service between plain and delayed start.
+    pub fn set_delayed_start(entry: &StartupEntry, delayed: bool) -> Result<()> {
+        let service_name = Self::service_name(entry)?;
+        let output = Self::run_sc(&["config", service_name, "start=", Self::auto_start_mode(delayed)])?;
+        if !output.status.success() {
+            return Err(Self::sc_error("reconfigure", service_name, &output));
+        }
+        Ok(())
+    }
 
+    /// Puts the service back to the start type `entry` was backed up with.
+    /// Fails unless `sc` reports success, so a restore only counts what
+    /// was actually changed.
+    pub fn restore_start_mode(entry: &StartupEntry) -> Result<()> {
+        let service_name = Self::service_name(entry)?;
+        let output = Self::run_sc(&["config", service_name, "start=", Self::recorded_start_mode(entry)])?;
+        if !output.status.success() {
+            return Err(Self::sc_error("restore", service_name, &output));
+        }
         Ok(())
     }
 
-    pub fn remove_service(_entry: &StartupEntry) -> Result<()> {
-        // Note: Removing services is dangerous and typically requires
-        // stopping the service first and then deleting it.
-        // This is a placeholder - actual implementation would need admin rights
-        // and proper service deletion logic.
-        anyhow::bail!("Service removal is not implemented for safety reasons")
+    /// The `sc config start=` value matching the entry's recorded state.
+    fn recorded_start_mode(entry: &StartupEntry) -> &'static str {
+        if entry.enabled {
+            Self::auto_start_mode(entry.delayed_start)
+        } else {
+            "disabled"
+        }
+    }
+
+    /// The `sc config start=` value for automatic start.
+    pub fn auto_start_mode(delayed: bool) -> &'static str {
+        if delayed {
+            "delayed-auto"
+        } else {
+            "auto"
+        }
+    }
+
+    /// Stops the service, waits for it to reach STOPPED and deletes it.
+    /// Deleting a service can't be undone, so callers must pass `confirmed`
+    /// once the user has explicitly agreed.
+    pub fn remove_service(entry: &StartupEntry, confirmed: bool) -> Result<()> {
+        let service_name = Self::service_name(entry)?;
+        if !confirmed {
+            anyhow::bail!("Deleting service '{}' requires explicit confirmation", service_name);
+        }
+
+        let output = Self::run_sc(&["stop", service_name])?;
+        match output.status.code() {
+            Some(0) | Some(ERROR_SERVICE_NOT_ACTIVE) => {}
+            _ => return Err(Self::sc_error("stop", service_name, &output)),
+        }
+        Self::wait_until_stopped(service_name)?;
+
+        let output = Self::run_sc(&["delete", service_name])?;
+        if !output.status.success() {
+            return Err(Self::sc_error("delete", service_name, &output));
+        }
+        Ok(())
+    }
+
+    fn wait_until_stopped(service_name: &str) -> Result<()> {
+        let started = Instant::now();
+        loop {
+            let output = Self::run_sc(&["query", service_name])?;
+            if !output.status.success() {
+                return Err(Self::sc_error("query", service_name, &output));
+            }
+            if String::from_utf8_lossy(&output.stdout).contains("STOPPED") {
+                return Ok(());
+            }
+            if started.elapsed() >= STOP_TIMEOUT {
+                anyhow::bail!(
+                    "Service '{}' did not stop within {} seconds",
+                    service_name,
+                    STOP_TIMEOUT.as_secs()
+                );
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+    }
+
+    fn run_sc(args: &[&str]) -> Result<Output> {
+        Command::new("sc")
+            .args(args)
+            .output()
+            .with_context(|| format!("Failed to run sc {}", args.join(" ")))
+    }
+
+    /// Turns a failed `sc` call into an error that tells missing rights and
+    /// missing services apart from everything else.
+    fn sc_error(operation: &str, service_name: &str, output: &Output) -> anyhow::Error {
+        match output.status.code() {
+            Some(ERROR_ACCESS_DENIED) => anyhow::anyhow!(
+                "Access denied trying to {} service '{}': run DeepBoot as administrator",
+                operation,
+                service_name
+            ),
+            Some(ERROR_SERVICE_DOES_NOT_EXIST) => {
+                anyhow::anyhow!("Service '{}' not found", service_name)
+            }
+            _ => anyhow::anyhow!(
+                "Failed to {} service '{}': {}",
+                operation,
+                service_name,
+                String::from_utf8_lossy(&output.stdout).trim()
+            ),
+        }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `sc qc` output for a service with the given START_TYPE line value.
+    fn qc_output(start_type: &str) -> String {
+        format!(
+            "[SC] QueryServiceConfig SUCCESS\r\n\r\n\
+             SERVICE_NAME: Updater\r\n\
+             \x20       TYPE               : 10  WIN32_OWN_PROCESS\r\n\
+             \x20       START_TYPE         : {}\r\n\
+             \x20       ERROR_CONTROL      : 1   NORMAL\r\n\
+             \x20       BINARY_PATH_NAME   : C:\\Tools\\updater.exe\r\n",
+            start_type
+        )
+    }
+
+    #[test]
+    fn parses_each_start_type() {
+        let cases = [
+            ("2   AUTO_START", SERVICE_AUTO_START, false),
+            ("2   AUTO_START  (DELAYED)", SERVICE_AUTO_START, true),
+            ("3   DEMAND_START", SERVICE_DEMAND_START, false),
+            ("4   DISABLED", SERVICE_DISABLED, false),
+        ];
+        for (line, start_type, delayed) in cases {
+            assert_eq!(ServicesScanner::parse_start_mode(&qc_output(line)), Some((start_type, delayed)), "{}", line);
+        }
+    }
+
+    #[test]
+    fn restores_the_recorded_start_type() {
+        let mut entry = StartupEntry::new(
+            "Updater".to_string(),
+            "C:\\Tools\\updater.exe".to_string(),
+            StartupSource::Service,
+            true,
+        );
+        assert_eq!(ServicesScanner::recorded_start_mode(&entry), "auto");
+        entry.delayed_start = true;
+        assert_eq!(ServicesScanner::recorded_start_mode(&entry), "delayed-auto");
+        entry.enabled = false;
+        assert_eq!(ServicesScanner::recorded_start_mode(&entry), "disabled");
+    }
+
+    #[test]
+    fn rejects_malformed_output() {
+        assert_eq!(ServicesScanner::parse_start_mode(""), None);
+        let not_installed = "[SC] OpenService FAILED 1060:\r\n\r\n\
+                             The specified service does not exist as an installed service.\r\n";
+        assert_eq!(ServicesScanner::parse_start_mode(not_installed), None);
+        assert_eq!(ServicesScanner::parse_start_mode(&qc_output("AUTO_START")), None);
+        assert_eq!(ServicesScanner::parse_start_mode("        START_TYPE         2   AUTO_START\r\n"), None);
+    }
+}
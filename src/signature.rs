@@ -0,0 +1,156 @@
+use crate::models::StartupEntry;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use windows::{
+    core::{GUID, HSTRING, PCWSTR},
+    Win32::Foundation::{BOOL, HANDLE, HWND},
+    Win32::Security::Cryptography::{CertGetNameStringW, CERT_NAME_SIMPLE_DISPLAY_TYPE},
+    Win32::Security::WinTrust::*,
+};
+
+// WinVerifyTrust results meaning the file carries no signature at all
+const TRUST_E_NOSIGNATURE: i32 = 0x800B0100_u32 as i32;
+const TRUST_E_SUBJECT_FORM_UNKNOWN: i32 = 0x800B0003_u32 as i32;
+const TRUST_E_PROVIDER_UNKNOWN: i32 = 0x800B0001_u32 as i32;
+
+lazy_static::lazy_static! {
+    static ref CACHE: Mutex<HashMap<PathBuf, SignatureStatus>> = Mutex::new(HashMap::new());
+}
+
+/// Authenticode state of an entry's executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Signed(String), // Signer certificate subject
+    Unsigned,
+    Invalid,
+    Missing,
+}
+
+impl SignatureStatus {
+    /// Ordering used when sorting by signature, most suspicious first.
+    pub fn rank(&self) -> u8 {
+        match self {
+            SignatureStatus::Invalid => 0,
+            SignatureStatus::Unsigned => 1,
+            SignatureStatus::Missing => 2,
+            SignatureStatus::Signed(_) => 3,
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, SignatureStatus::Signed(_))
+    }
+}
+
+impl fmt::Display for SignatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureStatus::Signed(signer) => write!(f, "Signed by {}", signer),
+            SignatureStatus::Unsigned => write!(f, "Unsigned"),
+            SignatureStatus::Invalid => write!(f, "Invalid signature"),
+            SignatureStatus::Missing => write!(f, "File not found"),
+        }
+    }
+}
+
+/// Signature of the entry's executable, verified once per path and then
+/// served from the cache.
+pub fn entry_status(entry: &StartupEntry) -> SignatureStatus {
+    match entry.target_path() {
+        Some(path) => cached_status(&path),
+        None => SignatureStatus::Missing,
+    }
+}
+
+/// The entry's signature if it has been verified already. Never verifies,
+/// so it returns at once; `None` means the check hasn't happened yet.
+pub fn peek_entry_status(entry: &StartupEntry) -> Option<SignatureStatus> {
+    match entry.target_path() {
+        Some(path) => CACHE.lock().unwrap().get(&path).cloned(),
+        None => Some(SignatureStatus::Missing),
+    }
+}
+
+pub fn cached_status(path: &Path) -> SignatureStatus {
+    if let Some(status) = CACHE.lock().unwrap().get(path) {
+        return status.clone();
+    }
+
+    let status = verify(path);
+    CACHE
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), status.clone());
+    status
+}
+
+/// Checks the file's embedded Authenticode signature with WinVerifyTrust.
+/// Files signed only through a security catalog, as many Windows
+/// components are, report as unsigned.
+pub fn verify(path: &Path) -> SignatureStatus {
+    if !path.is_file() {
+        return SignatureStatus::Missing;
+    }
+
+    let file_path = HSTRING::from(path.as_os_str());
+    let mut file_info = WINTRUST_FILE_INFO {
+        cbStruct: std::mem::size_of::<WINTRUST_FILE_INFO>() as u32,
+        pcwszFilePath: PCWSTR(file_path.as_ptr()),
+        ..Default::default()
+    };
+    let mut data = WINTRUST_DATA {
+        cbStruct: std::mem::size_of::<WINTRUST_DATA>() as u32,
+        dwUIChoice: WTD_UI_NONE,
+        fdwRevocationChecks: WTD_REVOKE_NONE,
+        dwUnionChoice: WTD_CHOICE_FILE,
+        dwStateAction: WTD_STATEACTION_VERIFY,
+        dwProvFlags: WTD_CACHE_ONLY_URL_RETRIEVAL,
+        ..Default::default()
+    };
+    data.Anonymous.pFile = &mut file_info;
+    let mut action: GUID = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+
+    unsafe {
+        let result = WinVerifyTrust(HWND::default(), &mut action, &mut data as *mut _ as *mut _);
+        let status = match result {
+            0 => SignatureStatus::Signed(
+                signer_name(data.hWVTStateData).unwrap_or_else(|| "unknown signer".to_string()),
+            ),
+            TRUST_E_NOSIGNATURE | TRUST_E_SUBJECT_FORM_UNKNOWN | TRUST_E_PROVIDER_UNKNOWN => {
+                SignatureStatus::Unsigned
+            }
+            _ => SignatureStatus::Invalid,
+        };
+
+        // Release the state data kept alive for reading the signer
+        data.dwStateAction = WTD_STATEACTION_CLOSE;
+        WinVerifyTrust(HWND::default(), &mut action, &mut data as *mut _ as *mut _);
+        status
+    }
+}
+
+/// Subject name of the leaf certificate of the first signer.
+unsafe fn signer_name(state: HANDLE) -> Option<String> {
+    let provider = WTHelperProvDataFromStateData(state);
+    if provider.is_null() {
+        return None;
+    }
+    let signer = WTHelperGetProvSignerFromChain(provider, 0, BOOL(0), 0);
+    if signer.is_null() || (*signer).csCertChain == 0 || (*signer).pasCertChain.is_null() {
+        return None;
+    }
+    let cert = (*(*signer).pasCertChain).pCert;
+    if cert.is_null() {
+        return None;
+    }
+
+    let mut name = [0u16; 256];
+    let len = CertGetNameStringW(cert, CERT_NAME_SIMPLE_DISPLAY_TYPE, 0, None, Some(&mut name));
+    // The length includes the terminating NUL
+    if len <= 1 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&name[..len as usize - 1]))
+}
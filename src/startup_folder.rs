@@ -0,0 +1,247 @@
+use crate::com::ComGuard;
+use crate::models::{EntryScope, StartupEntry, StartupSource};
+use crate::paths;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use windows::{
+    core::*,
+    Win32::System::Com::*,
+    Win32::UI::Shell::*,
+};
+
+/// Location of the Startup folder below `%APPDATA%` and `%ProgramData%`.
+const STARTUP_SUBPATH: &str = "Microsoft\\Windows\\Start Menu\\Programs\\Startup";
+
+/// Subfolder disabled items are moved to. Windows only launches the files
+/// directly inside the Startup folder, so anything in here stays put.
+const DISABLED_FOLDER: &str = "Disabled";
+
+pub const STARTUP_EXTENSIONS: &[&str] = &["lnk", "bat"];
+
+pub struct StartupFolderScanner;
+
+impl StartupFolderScanner {
+    /// Lists shortcuts and batch files in the per-user (`shell:startup`) and
+    /// all-users (`shell:common startup`) Startup folders, including ones
+    /// previously disabled into their `Disabled` subfolders.
+    pub fn scan() -> Result<Vec<StartupEntry>> {
+        // Shortcut targets need the shell's COM objects; without them the
+        // shortcut itself is reported as the command
+        let com = ComGuard::init().ok();
+        let com_ready = com.is_some();
+
+        let mut entries = Vec::new();
+        for (folder, scope) in Self::folders() {
+            Self::scan_folder(&folder, scope, true, com_ready, &mut entries);
+            Self::scan_folder(&folder.join(DISABLED_FOLDER), scope, false, com_ready, &mut entries);
+        }
+
+        drop(com);
+        Ok(entries)
+    }
+
+    fn folders() -> Vec<(PathBuf, EntryScope)> {
+        let mut folders = Vec::new();
+        if let Some(app_data) = dirs::data_dir() {
+            folders.push((app_data.join(STARTUP_SUBPATH), EntryScope::User));
+        }
+        if let Some(program_data) = std::env::var_os("ProgramData") {
+            folders.push((PathBuf::from(program_data).join(STARTUP_SUBPATH), EntryScope::Machine));
+        }
+        folders
+    }
+
+    fn folder_for(scope: EntryScope) -> Result<PathBuf> {
+        Self::folders()
+            .into_iter()
+            .find(|(_, s)| *s == scope)
+            .map(|(folder, _)| folder)
+            .ok_or_else(|| anyhow::anyhow!("Failed to locate the {} Startup folder", scope))
+    }
+
+    fn scan_folder(
+        folder: &Path,
+        scope: EntryScope,
+        enabled: bool,
+        com_ready: bool,
+        entries: &mut Vec<StartupEntry>,
+    ) {
+        let Ok(dir) = fs::read_dir(folder) else {
+            return;
+        };
+
+        for path in dir.flatten().map(|e| e.path()) {
+            let Some(extension) = path.extension().map(|e| e.to_string_lossy().to_lowercase()) else {
+                continue;
+            };
+            if !path.is_file() || !STARTUP_EXTENSIONS.contains(&extension.as_str()) {
+                continue;
+            }
+
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let command = if extension == "lnk" && com_ready {
+                match unsafe { Self::resolve_shortcut(&path) } {
+                    Ok(command) => command,
+                    Err(e) => {
+                        log::warn!("Failed to resolve shortcut {:?}: {}", path, e);
+                        path.display().to_string()
+                    }
+                }
+            } else {
+                path.display().to_string()
+            };
+
+            entries.push(
+                StartupEntry::new(name, command, StartupSource::StartupFolder, enabled)
+                    .with_scope(scope)
+                    .with_description(path.display().to_string()),
+            );
+        }
+    }
+
+    /// Reads a shortcut's target and arguments as a command line.
+    unsafe fn resolve_shortcut(path: &Path) -> Result<String> {
+        let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)
+            .context("Failed to create ShellLink COM object")?;
+        let file: IPersistFile = link.cast().context("Failed to query IPersistFile")?;
+        file.Load(&HSTRING::from(path.as_os_str()), STGM_READ)
+            .context("Failed to load shortcut")?;
+
+        let mut target = [0u16; 1024];
+        link.GetPath(&mut target, std::ptr::null_mut(), SLGP_RAWPATH.0 as u32)
+            .context("Failed to read shortcut target")?;
+        let mut arguments = [0u16; 1024];
+        link.GetArguments(&mut arguments)
+            .context("Failed to read shortcut arguments")?;
+
+        let target = Self::from_wide(&target);
+        let arguments = Self::from_wide(&arguments);
+        if target.is_empty() {
+            anyhow::bail!("Shortcut has no file system target");
+        }
+
+        let target = if target.contains(' ') {
+            format!("\"{}\"", target)
+        } else {
+            target
+        };
+        Ok(if arguments.is_empty() {
+            target
+        } else {
+            format!("{} {}", target, arguments)
+        })
+    }
+
+    fn from_wide(buffer: &[u16]) -> String {
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        String::from_utf16_lossy(&buffer[..len])
+    }
+
+    /// Active and disabled locations of the entry's file.
+    fn locations(entry: &StartupEntry) -> Result<(PathBuf, PathBuf)> {
+        let folder = Self::folder_for(entry.scope)?;
+        Ok((
+            folder.join(&entry.name),
+            folder.join(DISABLED_FOLDER).join(&entry.name),
+        ))
+    }
+
+    /// The file currently backing the entry, wherever it is.
+    pub fn file_path(entry: &StartupEntry) -> Result<PathBuf> {
+        let (active, disabled) = Self::locations(entry)?;
+        [active, disabled]
+            .into_iter()
+            .find(|path| path.exists())
+            .ok_or_else(|| anyhow::anyhow!("'{}' is no longer in the Startup folder", entry.name))
+    }
+
+    /// Moves the file into the `Disabled` subfolder.
+    pub fn disable_entry(entry: &StartupEntry) -> Result<()> {
+        let (active, disabled) = Self::locations(entry)?;
+        if !active.exists() {
+            anyhow::bail!("'{}' is not in the Startup folder", entry.name);
+        }
+        if let Some(parent) = disabled.parent() {
+            fs::create_dir_all(parent).context("Failed to create Disabled folder")?;
+        }
+        fs::rename(&active, &disabled).context("Failed to move file to the Disabled folder")
+    }
+
+    /// Moves the file back from the `Disabled` subfolder.
+    pub fn enable_entry(entry: &StartupEntry) -> Result<()> {
+        let (active, disabled) = Self::locations(entry)?;
+        if !disabled.exists() {
+            anyhow::bail!("'{}' is not in the Disabled folder", entry.name);
+        }
+        if active.exists() {
+            anyhow::bail!("A file named '{}' is already in the Startup folder", entry.name);
+        }
+        fs::rename(&disabled, &active).context("Failed to move file back to the Startup folder")
+    }
+
+    /// Puts the file back where the entry's enabled state says it belongs.
+    /// Deleted files can't be recreated, as backups don't hold their contents.
+    pub fn restore_entry(entry: &StartupEntry) -> Result<()> {
+        let (active, disabled) = Self::locations(entry)?;
+        match (entry.enabled, active.exists(), disabled.exists()) {
+            (true, true, _) | (false, _, true) => Ok(()),
+            (true, false, true) => Self::enable_entry(entry),
+            (false, true, false) => Self::disable_entry(entry),
+            _ => anyhow::bail!("'{}' was deleted and can't be recreated from a backup", entry.name),
+        }
+    }
+
+    /// Creates a shortcut named `name` running `command` in the user's or the
+    /// all-users Startup folder. A file of that name, active or disabled,
+    /// is only replaced with `force`.
+    pub fn add_entry(name: &str, command: &str, scope: EntryScope, force: bool) -> Result<()> {
+        let (target, arguments) = paths::split_command(command)
+            .ok_or_else(|| anyhow::anyhow!("The command is empty"))?;
+        let file_name = if name.to_lowercase().ends_with(".lnk") {
+            name.to_string()
+        } else {
+            format!("{}.lnk", name)
+        };
+        let folder = Self::folder_for(scope)?;
+        let (active, disabled) = (folder.join(&file_name), folder.join(DISABLED_FOLDER).join(&file_name));
+
+        if let Some(existing) = [&active, &disabled].into_iter().find(|path| path.exists()) {
+            if !force {
+                anyhow::bail!("{:?} already exists (use --force to overwrite it)", existing);
+            }
+        }
+        // Saving overwrites an active shortcut; a disabled one would shadow it
+        if force && disabled.exists() {
+            fs::remove_file(&disabled).with_context(|| format!("Failed to replace {:?}", disabled))?;
+        }
+        fs::create_dir_all(&folder).context("Failed to create the Startup folder")?;
+
+        let _com = ComGuard::init()?;
+        unsafe { Self::create_shortcut(&active, target.trim(), arguments.trim()) }
+    }
+
+    unsafe fn create_shortcut(path: &Path, target: &str, arguments: &str) -> Result<()> {
+        let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)
+            .context("Failed to create ShellLink COM object")?;
+        link.SetPath(&HSTRING::from(target))
+            .context("Failed to set shortcut target")?;
+        link.SetArguments(&HSTRING::from(arguments))
+            .context("Failed to set shortcut arguments")?;
+        if let Some(directory) = paths::resolve_executable(target).and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+            let _ = link.SetWorkingDirectory(&HSTRING::from(directory.as_os_str()));
+        }
+
+        let file: IPersistFile = link.cast().context("Failed to query IPersistFile")?;
+        file.Save(&HSTRING::from(path.as_os_str()), true)
+            .with_context(|| format!("Failed to save shortcut {:?}", path))
+    }
+
+    pub fn remove_entry(entry: &StartupEntry) -> Result<()> {
+        let path = Self::file_path(entry)?;
+        fs::remove_file(&path).with_context(|| format!("Failed to delete {:?}", path))
+    }
+}
@@ -1,49 +1,162 @@
-use crate::models::StartupEntry;
-use std::collections::HashMap;
+use crate::config::FootprintWeights;
+use crate::models::{EntryScope, StartupEntry, StartupSource};
+use crate::risk::RiskLevel;
+use crate::signature;
+use crate::whitelist::WhitelistManager;
+use serde::{Serialize, Serializer};
+use std::fmt;
 
-#[derive(Debug, Clone)]
+/// Headline rating derived from the footprint score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FootprintRating {
+    Good,
+    Fair,
+    Heavy,
+}
+
+impl fmt::Display for FootprintRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FootprintRating::Good => write!(f, "Good"),
+            FootprintRating::Fair => write!(f, "Fair"),
+            FootprintRating::Heavy => write!(f, "Heavy"),
+        }
+    }
+}
+
+/// Weighted summary of how much runs at startup.
+#[derive(Debug, Clone, Serialize)]
+pub struct Footprint {
+    pub score: f64,
+    pub rating: FootprintRating,
+    pub estimated_impact: f64,
+    pub flagged_count: usize,
+}
+
+impl Footprint {
+    pub fn from_entries(entries: &[StartupEntry], weights: &FootprintWeights) -> Self {
+        let enabled: Vec<&StartupEntry> = entries.iter().filter(|e| e.enabled).collect();
+        let estimated_impact: f64 = enabled.iter().map(|e| Self::estimated_impact(e)).sum();
+        let flagged_count = entries.iter().filter(|e| !e.flags.is_empty()).count();
+
+        let score = weights.entry * enabled.len() as f64
+            + weights.impact * estimated_impact
+            + weights.flagged * flagged_count as f64;
+
+        let rating = if score < weights.fair_threshold {
+            FootprintRating::Good
+        } else if score < weights.heavy_threshold {
+            FootprintRating::Fair
+        } else {
+            FootprintRating::Heavy
+        };
+
+        Self {
+            score,
+            rating,
+            estimated_impact,
+            flagged_count,
+        }
+    }
+
+    /// Rough boot cost of an enabled entry, judged by how it is started.
+    /// Services start before logon and stay resident; RunOnce entries only
+    /// run on the next boot.
+    fn estimated_impact(entry: &StartupEntry) -> f64 {
+        match entry.source {
+            StartupSource::Service => 1.5,
+            StartupSource::RegistryRunOnce => 0.5,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ScanStatistics {
     pub total_entries: usize,
     pub enabled_count: usize,
     pub disabled_count: usize,
-    pub by_source: HashMap<String, usize>,
-    pub by_status: HashMap<String, usize>,
+    #[serde(serialize_with = "counts_as_map")]
+    pub by_source: Vec<(StartupSource, usize)>, // In StartupSource::ALL order; absent sources aren't listed
+    #[serde(serialize_with = "counts_as_map")]
+    pub by_status: Vec<(&'static str, usize)>,
+    #[serde(serialize_with = "counts_as_map")]
+    pub by_scope: Vec<(EntryScope, usize)>, // In EntryScope::ALL order
+    #[serde(serialize_with = "counts_as_map")]
+    pub by_risk: Vec<(RiskLevel, usize)>, // Highest first; unrated entries aren't counted
+    pub footprint: Footprint,
+    pub missing_target: usize, // Target file no longer exists
+    pub unsigned: usize,       // Executable unsigned or with an invalid signature
+    pub unverified: usize,     // Signature not checked yet, so not counted in `unsigned`
+    pub whitelisted: usize,    // Matches the whitelist, counting hidden entries
+    pub hidden_whitelisted: usize,
 }
 
 impl ScanStatistics {
-    pub fn from_entries(entries: &[StartupEntry]) -> Self {
-        let mut by_source = HashMap::new();
-        let mut by_status = HashMap::new();
-        let mut enabled_count = 0;
-        let mut disabled_count = 0;
-
-        for entry in entries {
-            // Count by source
-            let source_str = entry.source.to_string();
-            *by_source.entry(source_str).or_insert(0) += 1;
-
-            // Count by status
-            if entry.enabled {
-                enabled_count += 1;
-            } else {
-                disabled_count += 1;
-            }
-        }
-
-        by_status.insert("Enabled".to_string(), enabled_count);
-        by_status.insert("Disabled".to_string(), disabled_count);
+    /// Counts `entries`. Only signatures verified so far are counted; the
+    /// rest show up in `unverified`.
+    pub fn from_entries(
+        entries: &[StartupEntry],
+        weights: &FootprintWeights,
+        whitelist: &WhitelistManager,
+    ) -> Self {
+        let enabled_count = entries.iter().filter(|e| e.enabled).count();
+        let disabled_count = entries.len() - enabled_count;
 
         Self {
             total_entries: entries.len(),
             enabled_count,
             disabled_count,
-            by_source,
-            by_status,
+            by_source: StartupSource::ALL
+                .iter()
+                .map(|source| (source.clone(), entries.iter().filter(|e| e.source == *source).count()))
+                .filter(|(_, count)| *count > 0)
+                .collect(),
+            by_status: vec![("Enabled", enabled_count), ("Disabled", disabled_count)],
+            by_scope: EntryScope::ALL
+                .iter()
+                .map(|scope| (*scope, entries.iter().filter(|e| e.scope == *scope).count()))
+                .collect(),
+            by_risk: [RiskLevel::High, RiskLevel::Medium, RiskLevel::Low]
+                .iter()
+                .map(|level| {
+                    (*level, entries.iter().filter(|e| e.risk.as_ref().is_some_and(|r| r.level == *level)).count())
+                })
+                .collect(),
+            footprint: Footprint::from_entries(entries, weights),
+            missing_target: entries.iter().filter(|e| !e.target_exists()).count(),
+            unsigned: entries
+                .iter()
+                .filter(|e| {
+                    matches!(
+                        signature::peek_entry_status(e),
+                        Some(signature::SignatureStatus::Unsigned | signature::SignatureStatus::Invalid)
+                    )
+                })
+                .count(),
+            unverified: entries.iter().filter(|e| signature::peek_entry_status(e).is_none()).count(),
+            whitelisted: entries.iter().filter(|e| whitelist.is_whitelisted(e)).count(),
+            hidden_whitelisted: 0,
         }
     }
 
+    /// Adds whitelisted entries that were left out of `entries` because
+    /// whitelisted entries are hidden.
+    pub fn with_hidden_whitelisted(mut self, hidden: usize) -> Self {
+        self.whitelisted += hidden;
+        self.hidden_whitelisted = hidden;
+        self
+    }
+
     pub fn get_summary(&self) -> String {
-        let mut summary = format!("Total Entries: {}\n", self.total_entries);
+        let mut summary = format!(
+            "Startup Footprint: {:.1} ({})\n  Estimated boot impact: {:.1}\n  Flagged entries: {}\n\n",
+            self.footprint.score,
+            self.footprint.rating,
+            self.footprint.estimated_impact,
+            self.footprint.flagged_count
+        );
+        summary.push_str(&format!("Total Entries: {}\n", self.total_entries));
         summary.push_str(&format!("  Enabled: {} ({:.1}%)\n", 
             self.enabled_count,
             if self.total_entries > 0 {
@@ -60,19 +173,106 @@ impl ScanStatistics {
                 0.0
             }
         ));
+        summary.push_str("\nHealth:\n");
+        summary.push_str(&format!("  Missing target: {}\n", self.missing_target));
+        if self.unverified > 0 {
+            summary.push_str(&format!(
+                "  Unsigned or invalid signature: {} ({} not checked yet)\n",
+                self.unsigned, self.unverified
+            ));
+        } else {
+            summary.push_str(&format!("  Unsigned or invalid signature: {}\n", self.unsigned));
+        }
+        if self.hidden_whitelisted > 0 {
+            summary.push_str(&format!(
+                "  Whitelisted: {} ({} hidden)\n",
+                self.whitelisted, self.hidden_whitelisted
+            ));
+        } else {
+            summary.push_str(&format!("  Whitelisted: {}\n", self.whitelisted));
+        }
+        // Fixed source order so reports from different runs line up
         summary.push_str("\nBy Source:\n");
         for (source, count) in &self.by_source {
-            summary.push_str(&format!("  {}: {} ({:.1}%)\n",
-                source,
-                count,
-                if self.total_entries > 0 {
-                    (*count as f64 / self.total_entries as f64) * 100.0
-                } else {
-                    0.0
-                }
-            ));
+            summary.push_str(&format!("  {}: {} ({:.1}%)\n", source, count, self.percent(*count)));
+        }
+        summary.push_str("\nBy Scope:\n");
+        for (scope, count) in self.by_scope.iter().filter(|(_, count)| *count > 0) {
+            summary.push_str(&format!("  {}: {} ({:.1}%)\n", scope, count, self.percent(*count)));
+        }
+        if self.by_risk.iter().any(|(_, count)| *count > 0) {
+            summary.push_str("\nBy Risk:\n");
+            for (level, count) in &self.by_risk {
+                summary.push_str(&format!("  {}: {} ({:.1}%)\n", level, count, self.percent(*count)));
+            }
         }
         summary
     }
+
+    fn percent(&self, count: usize) -> f64 {
+        if self.total_entries > 0 {
+            (count as f64 / self.total_entries as f64) * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Writes (label, count) pairs as a JSON object keyed by the label, in order.
+fn counts_as_map<S: Serializer, K: fmt::Display>(counts: &[(K, usize)], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(counts.iter().map(|(key, count)| (key.to_string(), count)))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::whitelist::WhitelistConfig;
+
+    fn entries() -> Vec<StartupEntry> {
+        let entry = |name: &str, source: StartupSource, enabled: bool, scope: EntryScope| {
+            StartupEntry::new(name.to_string(), format!("C:\\Tools\\{}.exe", name), source, enabled).with_scope(scope)
+        };
+        vec![
+            entry("Updater", StartupSource::RegistryRun, true, EntryScope::User),
+            entry("Sync", StartupSource::StartupFolder, true, EntryScope::User),
+            entry("Agent", StartupSource::Service, false, EntryScope::Machine),
+            entry("Helper", StartupSource::RegistryRun, false, EntryScope::Machine),
+            entry("Cleanup", StartupSource::TaskScheduler, true, EntryScope::Machine),
+            entry("Tray", StartupSource::RegistryRunOnce, true, EntryScope::OtherUser),
+        ]
+    }
+
+    /// The text summary and the JSON export, as `stats` prints them.
+    fn render(entries: &[StartupEntry]) -> (String, String) {
+        let whitelist = WhitelistManager::in_memory(WhitelistConfig::default());
+        let stats = ScanStatistics::from_entries(entries, &FootprintWeights::default(), &whitelist);
+        (stats.get_summary(), serde_json::to_string(&stats).unwrap())
+    }
+
+    #[test]
+    fn output_does_not_depend_on_entry_order() {
+        let entries = entries();
+        let expected = render(&entries);
+        assert_eq!(render(&entries), expected);
+
+        let mut reversed = entries.clone();
+        reversed.reverse();
+        assert_eq!(render(&reversed), expected);
+
+        let mut rotated = entries;
+        rotated.rotate_left(2);
+        assert_eq!(render(&rotated), expected);
+    }
+
+    #[test]
+    fn sources_are_listed_in_a_fixed_order() {
+        let (summary, json) = render(&entries());
+        let sources = ["Task Scheduler", "Registry (Run)", "Registry (RunOnce)", "Service", "Startup Folder"];
+        let positions: Vec<usize> = sources
+            .iter()
+            .map(|source| summary.find(&format!("  {}: ", source)).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]), "{}", summary);
+        assert!(json.contains(r#""by_status":{"Enabled":4,"Disabled":2}"#), "{}", json);
+    }
+}
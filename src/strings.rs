@@ -0,0 +1,378 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+/// Longest locale name Windows returns, terminator included.
+const LOCALE_NAME_MAX_LENGTH: usize = 85;
+
+/// Languages the TUI has a catalog for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: &'static [Language] = &[Language::English, Language::German];
+
+    /// Identifier used in the config file, an ISO 639-1 code.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    /// Accepts a language code or a locale name like `de-DE`.
+    pub fn from_key(key: &str) -> Option<Self> {
+        let code = key.split(['-', '_']).next().unwrap_or(key);
+        Language::ALL.iter().copied().find(|l| l.key().eq_ignore_ascii_case(code))
+    }
+
+    /// The language of the user's Windows locale, English when there is no
+    /// catalog for it.
+    pub fn detect() -> Self {
+        let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+        let len = unsafe { GetUserDefaultLocaleName(&mut name) };
+        if len <= 1 {
+            return Language::English;
+        }
+        // The length counts the terminating null
+        Self::from_key(&String::from_utf16_lossy(&name[..len as usize - 1])).unwrap_or(Language::English)
+    }
+}
+
+// Index into Language::ALL of the language in use
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Switches the catalog from the `language` setting: a language code, or
+/// "auto" (or anything unknown) for the OS locale.
+pub fn set_language(setting: &str) {
+    let language = Language::from_key(setting).unwrap_or_else(Language::detect);
+    let index = Language::ALL.iter().position(|l| *l == language).unwrap_or(0);
+    CURRENT.store(index, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    Language::ALL.get(CURRENT.load(Ordering::Relaxed)).copied().unwrap_or(Language::English)
+}
+
+/// The message `id` in the current language. Messages a catalog lacks fall
+/// back to English, and unknown ids to the id itself.
+pub fn text(id: &'static str) -> &'static str {
+    let translated = match language() {
+        Language::English => None,
+        Language::German => german(id),
+    };
+    translated.or_else(|| english(id)).unwrap_or(id)
+}
+
+/// `text` with the placeholders `{0}`, `{1}`, ... replaced by `args`.
+/// Placeholders are numbered so translations can reorder them.
+pub fn fill(id: &'static str, args: &[&dyn fmt::Display]) -> String {
+    args.iter()
+        .enumerate()
+        .fold(text(id).to_string(), |message, (i, arg)| {
+            message.replace(&format!("{{{}}}", i), &arg.to_string())
+        })
+}
+
+fn english(id: &str) -> Option<&'static str> {
+    Some(match id {
+        "action.disable" => "disable",
+        "action.remove" => "remove",
+        "action.enable" => "enable",
+
+        "prompt.action_one" => "Press 'y' to {0} '{1}' or 'n' to cancel",
+        "prompt.action_selected" => "Press 'y' to {0} {1} selected entries or 'n' to cancel",
+        "prompt.listed_source" => "Press 'y' to {0} only the listed source or 'n' to cancel",
+        "prompt.duplicates" => " - also applies to the same program in {0}; press 'o' to {1} only the listed source",
+        "prompt.critical" => {
+            "Warning: {0} may be security or backup software. Press 'y' again to {1} anyway or 'n' to cancel"
+        }
+        "prompt.backup_saved" => "A backup is saved to {0}. {1}",
+        "prompt.backup_failed" => "Warning: backup failed ({0}). {1}",
+        "prompt.type_count" => {
+            "You are about to remove {0} entries. This cannot be trivially undone.{1} \
+             Type {0} and press Enter to confirm, or Esc to cancel"
+        }
+        "prompt.type_count_backup" => " A backup was saved to {0}.",
+        "prompt.count_mismatch" => "That doesn't match. Type {0} and press Enter to remove them, or Esc to cancel",
+        "prompt.whitelist_all" => {
+            "Press 'y' to whitelist all {0} entries in the current view or 'n' to cancel (they are hidden on future scans)"
+        }
+        "prompt.reset_config" => "Press 'y' to back up and reset the config to defaults or 'n' to cancel",
+        "prompt.reset_whitelist" => "Press 'y' to back up and reset the whitelist to defaults or 'n' to cancel",
+        "prompt.profile" => "Profile '{0}': {1}. Press 'y' to apply or 'n' to cancel",
+        "prompt.restore" => "Press 'y' to restore {0} entries from {1} or 'n' to cancel",
+
+        "list.position" => " row {0}/{1} ",
+        "status.title" => "Status",
+        "status.entries" => "Entries: {0}/{1} | ",
+        "status.selected" => "Selected: {0} | ",
+        "status.sort" => "Sort: {0} | ",
+        "status.group" => "Group: {0} | ",
+        "status.flag" => "Flag: {0} | ",
+        "status.sources" => "Sources: {0} | ",
+        "status.sources_none" => "none",
+        "status.enabled_only" => "Enabled only ('+' clears) | ",
+        "status.disabled_only" => "Disabled only ('-' clears) | ",
+        "status.unsigned_only" => "Unsigned only | ",
+        "status.severity" => "Severity ≥ {0} | ",
+        "status.risk" => "Risk ≥ {0} | ",
+        "status.search" => "Search{0}: {1}{2} | ",
+        "status.search_regex" => " (regex)",
+        "status.search_fuzzy" => " (fuzzy)",
+        "status.whitelisted_shown" => "Whitelisted: shown | ",
+        "status.whitelisted_hidden" => "Whitelisted: hidden | ",
+        "status.checking" => "Checking {0} files… | ",
+        "status.warning" => "1 warning (!) | ",
+        "status.warnings" => "{0} warnings (!) | ",
+        "status.help" => "Press 'h' for help",
+
+        "help.title" => "Help - DeepBoot Pro",
+        "help.navigation" => "Navigation:",
+        "help.navigation.keys" => {
+            "  ↑/k - Move up
+  ↓/j - Move down
+  PgUp/PgDn - Move up or down by a screen
+  Home/End - Jump to the first or last entry
+  Space - Toggle selection
+  S   - Select every entry in the current view
+  i   - Invert the selection within the current view
+  X   - Clear the selection (or Esc)"
+        }
+        "help.actions" => "Actions:",
+        "help.actions.keys" => {
+            "  d   - Disable selected entry(ies)
+  r   - Remove selected entry(ies)
+  E   - Re-enable selected disabled entry(ies)
+  O   - Toggle the highlighted entry on/off (quick_toggle skips 'y')
+  D   - Toggle delayed start of the selected service
+  w   - Add to / remove from whitelist (W marks whitelisted rows)
+  A   - Whitelist every entry in the current view
+  e   - Export (JSON, CSV, Markdown or HTML)
+  I   - Export an incident report of flagged entries
+  P   - Profiles: save the current state or apply a saved one
+  C   - Reset config to defaults (backs up first)
+  W   - Reset whitelist to defaults (backs up first)
+  L   - Review the whitelist and remove keys
+  M   - Add the selected service to the system service list"
+        }
+        "help.views" => "Views:",
+        "help.views.keys" => {
+            "  Enter - Open the selected entry full screen
+  s   - Show statistics
+  R/F5 - Rescan all sources
+  l   - Show action log (all days, filterable)
+  b   - Show backups and restore one
+  c   - Change settings (saved to config.json)
+  !   - Show scan failures and other warnings
+  h   - Toggle help"
+        }
+        "help.search" => "Search & Filter:",
+        "help.search.keys" => {
+            "  /   - Search as you type (Enter keeps, Esc clears; re: for a regex)
+  Esc - Cancel search
+  f   - Cycle flag filter
+  x   - Show only broken entries (missing target) / all
+  F   - Choose which sources to show
+  H   - Show / hide whitelisted entries (saved)
+  z   - Toggle fuzzy / substring search
+  t   - Cycle all / enabled only / disabled only
+  +   - Show only enabled entries (again to show all)
+  -   - Show only disabled entries (again to show all)
+  a   - Acknowledge flagged entry (hide from flag filter)
+  v   - Cycle minimum severity (none / low / medium / high)
+  T   - Cycle minimum risk (low / medium / high)
+        Severity rates the flags, risk the signature and location;
+        entries must meet both floors to be shown"
+        }
+        "help.sorting" => "Sorting:",
+        "help.sorting.keys" => {
+            "  1   - Sort by name
+  2   - Sort by source
+  3   - Sort by status
+  4   - Sort by command
+  5   - Sort by signature (unsigned first)
+  6   - Sort by startup impact (highest first)
+  7   - Sort by risk (highest first)
+  u   - Show only unsigned or invalid executables
+  g   - Group by source / directory / none"
+        }
+        "help.other" => "Other:",
+        "help.other.keys" => {
+            "  o   - Open the entry's file location in Explorer
+  K   - Open a registry entry's key in the Registry Editor
+  p   - Copy the entry's command to the clipboard
+  Y   - Copy the entry as JSON to the clipboard
+  m   - Manage files (open config, whitelist, backup or log location)
+  q   - Quit"
+        }
+        "help.legend" => "Legend:",
+        "help.legend.enabled" => "Enabled",
+        "help.legend.disabled" => "Disabled",
+        "help.legend.selected" => "Selected",
+        "help.legend.keys" => {
+            "  ⏏ - Launches from a removable drive
+  ? - Launches from an unknown or missing drive
+  ! - Service posing as a Windows system service
+  » - Double extension (e.g. invoice.pdf.exe)
+  ≠ - Executable behind a non-executable extension
+  ⚠ - Target file no longer exists
+  ✎ - Can be changed with the current rights
+  ⚷ - Needs DeepBoot to run as administrator"
+        }
+        _ => return None,
+    })
+}
+
+fn german(id: &str) -> Option<&'static str> {
+    Some(match id {
+        "action.disable" => "deaktivieren",
+        "action.remove" => "entfernen",
+        "action.enable" => "aktivieren",
+
+        "prompt.action_one" => "'y' drücken, um '{1}' zu {0}, oder 'n' zum Abbrechen",
+        "prompt.action_selected" => "'y' drücken, um {1} ausgewählte Einträge zu {0}, oder 'n' zum Abbrechen",
+        "prompt.listed_source" => "'y' drücken, um nur die angezeigte Quelle zu {0}, oder 'n' zum Abbrechen",
+        "prompt.duplicates" => " – betrifft auch dasselbe Programm in {0}; 'o' drücken, um nur die angezeigte Quelle zu {1}",
+        "prompt.critical" => {
+            "Warnung: {0} ist möglicherweise Sicherheits- oder Backup-Software. Erneut 'y' drücken, um trotzdem zu {1}, oder 'n' zum Abbrechen"
+        }
+        "prompt.backup_saved" => "Eine Sicherung wurde unter {0} gespeichert. {1}",
+        "prompt.backup_failed" => "Warnung: Sicherung fehlgeschlagen ({0}). {1}",
+        "prompt.type_count" => {
+            "Sie sind dabei, {0} Einträge zu entfernen. Das lässt sich nicht ohne Weiteres rückgängig machen.{1} \
+             Zum Bestätigen {0} eingeben und Enter drücken, oder Esc zum Abbrechen"
+        }
+        "prompt.type_count_backup" => " Eine Sicherung wurde unter {0} gespeichert.",
+        "prompt.count_mismatch" => "Das stimmt nicht überein. {0} eingeben und Enter drücken, um sie zu entfernen, oder Esc zum Abbrechen",
+        "prompt.whitelist_all" => {
+            "'y' drücken, um alle {0} Einträge der aktuellen Ansicht auf die Whitelist zu setzen (sie werden bei künftigen Scans ausgeblendet), oder 'n' zum Abbrechen"
+        }
+        "prompt.reset_config" => "'y' drücken, um die Konfiguration zu sichern und zurückzusetzen, oder 'n' zum Abbrechen",
+        "prompt.reset_whitelist" => "'y' drücken, um die Whitelist zu sichern und zurückzusetzen, oder 'n' zum Abbrechen",
+        "prompt.profile" => "Profil '{0}': {1}. 'y' zum Anwenden, 'n' zum Abbrechen",
+        "prompt.restore" => "'y' drücken, um {0} Einträge der Sicherung vom {1} wiederherzustellen, oder 'n' zum Abbrechen",
+
+        "list.position" => " Zeile {0}/{1} ",
+        "status.title" => "Status",
+        "status.entries" => "Einträge: {0}/{1} | ",
+        "status.selected" => "Ausgewählt: {0} | ",
+        "status.sort" => "Sortierung: {0} | ",
+        "status.group" => "Gruppierung: {0} | ",
+        "status.flag" => "Markierung: {0} | ",
+        "status.sources" => "Quellen: {0} | ",
+        "status.sources_none" => "keine",
+        "status.enabled_only" => "Nur aktivierte ('+' hebt auf) | ",
+        "status.disabled_only" => "Nur deaktivierte ('-' hebt auf) | ",
+        "status.unsigned_only" => "Nur unsignierte | ",
+        "status.severity" => "Schweregrad ≥ {0} | ",
+        "status.risk" => "Risiko ≥ {0} | ",
+        "status.search" => "Suche{0}: {1}{2} | ",
+        "status.search_regex" => " (Regex)",
+        "status.search_fuzzy" => " (unscharf)",
+        "status.whitelisted_shown" => "Whitelist-Einträge: sichtbar | ",
+        "status.whitelisted_hidden" => "Whitelist-Einträge: ausgeblendet | ",
+        "status.checking" => "Prüfe {0} Dateien… | ",
+        "status.warning" => "1 Warnung (!) | ",
+        "status.warnings" => "{0} Warnungen (!) | ",
+        "status.help" => "'h' drücken für Hilfe",
+
+        "help.title" => "Hilfe – DeepBoot Pro",
+        "help.navigation" => "Navigation:",
+        "help.navigation.keys" => {
+            "  ↑/k - Nach oben
+  ↓/j - Nach unten
+  Bild↑/Bild↓ - Eine Seite nach oben oder unten
+  Pos1/Ende - Zum ersten oder letzten Eintrag
+  Leertaste - Auswahl umschalten
+  S   - Alle Einträge der aktuellen Ansicht auswählen
+  i   - Auswahl innerhalb der aktuellen Ansicht umkehren
+  X   - Auswahl aufheben (oder Esc)"
+        }
+        "help.actions" => "Aktionen:",
+        "help.actions.keys" => {
+            "  d   - Ausgewählte Einträge deaktivieren
+  r   - Ausgewählte Einträge entfernen
+  E   - Ausgewählte deaktivierte Einträge wieder aktivieren
+  O   - Markierten Eintrag ein-/ausschalten (quick_toggle überspringt 'y')
+  D   - Verzögerten Start des ausgewählten Dienstes umschalten
+  w   - Zur Whitelist hinzufügen / daraus entfernen (W markiert Whitelist-Zeilen)
+  A   - Alle Einträge der aktuellen Ansicht auf die Whitelist setzen
+  e   - Exportieren (JSON, CSV, Markdown oder HTML)
+  I   - Vorfallbericht der markierten Einträge exportieren
+  P   - Profile: aktuellen Zustand speichern oder ein gespeichertes anwenden
+  C   - Konfiguration zurücksetzen (vorher gesichert)
+  W   - Whitelist zurücksetzen (vorher gesichert)
+  L   - Whitelist prüfen und Schlüssel entfernen
+  M   - Ausgewählten Dienst zur Liste der Systemdienste hinzufügen"
+        }
+        "help.views" => "Ansichten:",
+        "help.views.keys" => {
+            "  Enter - Ausgewählten Eintrag im Vollbild öffnen
+  s   - Statistik anzeigen
+  R/F5 - Alle Quellen neu scannen
+  l   - Aktionsprotokoll anzeigen (alle Tage, filterbar)
+  b   - Sicherungen anzeigen und eine wiederherstellen
+  c   - Einstellungen ändern (in config.json gespeichert)
+  !   - Scanfehler und andere Warnungen anzeigen
+  h   - Hilfe ein-/ausblenden"
+        }
+        "help.search" => "Suchen & Filtern:",
+        "help.search.keys" => {
+            "  /   - Suche beim Tippen (Enter behält, Esc löscht; re: für einen regulären Ausdruck)
+  Esc - Suche abbrechen
+  f   - Markierungsfilter durchschalten
+  x   - Nur defekte Einträge (Ziel fehlt) / alle anzeigen
+  F   - Anzuzeigende Quellen wählen
+  H   - Whitelist-Einträge ein-/ausblenden (gespeichert)
+  z   - Zwischen unscharfer Suche und Teilstring-Suche wechseln
+  t   - Alle / nur aktivierte / nur deaktivierte durchschalten
+  +   - Nur aktivierte Einträge anzeigen (erneut für alle)
+  -   - Nur deaktivierte Einträge anzeigen (erneut für alle)
+  a   - Markierten Eintrag bestätigen (aus dem Markierungsfilter ausblenden)
+  v   - Mindest-Schweregrad durchschalten (none / low / medium / high)
+  T   - Mindestrisiko durchschalten (low / medium / high)
+        Der Schweregrad bewertet die Markierungen, das Risiko Signatur
+        und Speicherort; angezeigt wird nur, was beide Grenzen erreicht"
+        }
+        "help.sorting" => "Sortierung:",
+        "help.sorting.keys" => {
+            "  1   - Nach Name sortieren
+  2   - Nach Quelle sortieren
+  3   - Nach Status sortieren
+  4   - Nach Befehl sortieren
+  5   - Nach Signatur sortieren (unsignierte zuerst)
+  6   - Nach Startauswirkung sortieren (höchste zuerst)
+  7   - Nach Risiko sortieren (höchstes zuerst)
+  u   - Nur unsignierte oder ungültig signierte Programme anzeigen
+  g   - Nach Quelle / Verzeichnis / nicht gruppieren"
+        }
+        "help.other" => "Sonstiges:",
+        "help.other.keys" => {
+            "  o   - Speicherort der Datei im Explorer öffnen
+  K   - Schlüssel eines Registry-Eintrags im Registrierungs-Editor öffnen
+  p   - Befehl des Eintrags in die Zwischenablage kopieren
+  Y   - Eintrag als JSON in die Zwischenablage kopieren
+  m   - Dateien verwalten (Konfiguration, Whitelist, Sicherungen oder Protokolle öffnen)
+  q   - Beenden"
+        }
+        "help.legend" => "Legende:",
+        "help.legend.enabled" => "Aktiviert",
+        "help.legend.disabled" => "Deaktiviert",
+        "help.legend.selected" => "Ausgewählt",
+        "help.legend.keys" => {
+            "  ⏏ - Startet von einem Wechseldatenträger
+  ? - Startet von einem unbekannten oder fehlenden Laufwerk
+  ! - Dienst, der sich als Windows-Systemdienst ausgibt
+  » - Doppelte Dateiendung (z. B. rechnung.pdf.exe)
+  ≠ - Programm hinter einer nicht ausführbaren Endung
+  ⚠ - Zieldatei existiert nicht mehr
+  ✎ - Mit den aktuellen Rechten änderbar
+  ⚷ - DeepBoot muss als Administrator laufen"
+        }
+        _ => return None,
+    })
+}
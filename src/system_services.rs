@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Common Windows system services, written to the list file the first time.
+const DEFAULT_SYSTEM_SERVICES: &[&str] = &[
+    "AudioSrv", "BITS", "Browser", "CryptSvc", "DcomLaunch", "Dhcp", "Dnscache",
+    "EventLog", "EventSystem", "FontCache", "gpsvc", "hidserv", "IKEEXT", "iphlpsvc",
+    "KeyIso", "LanmanServer", "LanmanWorkstation", "lmhosts", "MMCSS", "MpsSvc",
+    "MSiSCSI", "Netlogon", "netprofm", "NlaSvc", "nsi", "p2pimsvc", "p2psvc", "PcaSvc",
+    "PlugPlay", "PNRPsvc", "PolicyAgent", "ProfSvc", "QWAVE", "RasAuto", "RasMan",
+    "RemoteAccess", "RemoteRegistry", "RpcEptMapper", "RpcLocator", "RpcSs", "SamSs",
+    "SCardSvr", "Schedule", "SCPolicySvc", "SENS", "SessionEnv", "ShellHWDetection",
+    "Spooler", "SSDPSRV", "SstpSvc", "StiSvc", "SysMain", "TabletInputService",
+    "Themes", "TrkWks", "TrustedInstaller", "UevAgentService", "UmRdpService", "UsoSvc",
+    "VaultSvc", "VSS", "W32Time", "WaaSMedicSvc", "WbioSrvc", "Wcmsvc",
+    "WcsPlugInService", "WdiServiceHost", "WebClient", "WerSvc", "WinHttpAutoProxySvc",
+    "Winmgmt", "WinRM", "WlanSvc", "wmiApSrv", "WMPNetworkSvc", "wscsvc", "WSearch",
+    "wuauserv", "WudfSvc",
+];
+
+lazy_static::lazy_static! {
+    // Lowercase, so lookups don't scan the list
+    static ref BUILTIN: HashSet<String> =
+        DEFAULT_SYSTEM_SERVICES.iter().map(|name| name.to_lowercase()).collect();
+}
+
+/// Service names treated as part of Windows and left out of the services
+/// scan. Kept in `%APPDATA%\deepboot\system_services.json`, which starts
+/// out with the built-in names and can be edited; names are compared
+/// ignoring case, so duplicates in the file collapse into one.
+pub struct SystemServiceList {
+    names: HashSet<String>, // Lowercase
+    path: PathBuf,
+}
+
+impl Default for SystemServiceList {
+    /// The built-in names, used when the list file can't be read.
+    fn default() -> Self {
+        Self {
+            names: BUILTIN.clone(),
+            path: PathBuf::new(),
+        }
+    }
+}
+
+impl SystemServiceList {
+    pub fn new() -> Result<Self> {
+        let data_dir = dirs::data_dir()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get data directory"))?
+            .join("deepboot");
+
+        if !data_dir.exists() {
+            fs::create_dir_all(&data_dir)
+                .context("Failed to create data directory")?;
+        }
+
+        let path = data_dir.join("system_services.json");
+        if !path.exists() {
+            let list = Self { path, ..Self::default() };
+            list.save()?;
+            return Ok(list);
+        }
+
+        let content = fs::read_to_string(&path)
+            .context("Failed to read system service list")?;
+        let names: Vec<String> = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse system service list {:?}", path))?;
+        Ok(Self {
+            names: names.iter().map(|name| name.trim().to_lowercase()).collect(),
+            path,
+        })
+    }
+
+    pub fn contains(&self, service_name: &str) -> bool {
+        self.names.contains(&service_name.to_lowercase())
+    }
+
+    /// Whether the name is one of the built-in Windows services, as opposed
+    /// to one the user added to the list.
+    pub fn is_builtin(service_name: &str) -> bool {
+        BUILTIN.contains(&service_name.to_lowercase())
+    }
+
+    /// Adds a service so later scans skip it. Returns false if it was
+    /// already listed.
+    pub fn add(&mut self, service_name: &str) -> Result<bool> {
+        if !self.names.insert(service_name.to_lowercase()) {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn save(&self) -> Result<()> {
+        let mut names: Vec<&String> = self.names.iter().collect();
+        names.sort();
+        let content = serde_json::to_string_pretty(&names)
+            .context("Failed to serialize system service list")?;
+        fs::write(&self.path, content)
+            .context("Failed to save system service list")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_names_have_no_case_insensitive_duplicates() {
+        assert_eq!(DEFAULT_SYSTEM_SERVICES.len(), BUILTIN.len());
+    }
+}
@@ -1,8 +1,10 @@
-use crate::models::{StartupEntry, StartupSource};
+use crate::com::ComGuard;
+use crate::models::{EntryScope, StartupEntry, StartupSource, TaskInfo};
+use crate::paths;
 use anyhow::{Context, Result};
 use windows::{
     core::*,
-    Win32::Foundation::VARIANT_BOOL,
+    Win32::Foundation::{VARIANT_FALSE, VARIANT_TRUE},
     Win32::System::Com::*,
     Win32::System::TaskScheduler::*,
 };
@@ -11,91 +13,180 @@ use windows::core::GUID;
 // TaskScheduler CLSID: {0F87369F-A4E5-4CFC-BD3E-73E6154572DD}
 const CLSID_TASK_SCHEDULER: GUID = GUID::from_u128(0x0F87369F_A4E5_4CFC_BD3E_73E6154572DD);
 
+/// Folder holding the tasks Windows itself ships, skipped unless
+/// `include_microsoft_tasks` is set.
+const MICROSOFT_FOLDER: &str = "\\Microsoft";
+
+/// Well-known SID of the built-in Users group, which tasks added for all
+/// users run as.
+const USERS_GROUP_SID: &str = "S-1-5-32-545";
+
 pub struct TaskSchedulerScanner;
 
 impl TaskSchedulerScanner {
-    pub fn scan() -> Result<Vec<StartupEntry>> {
-        unsafe {
-            CoInitializeEx(None, COINIT_MULTITHREADED)
-                .ok()
-                .context("Failed to initialize COM")?;
-
-            let entries = {
-                let task_service: ITaskService = CoCreateInstance(
-                    &CLSID_TASK_SCHEDULER,
-                    None,
-                    CLSCTX_INPROC_SERVER,
-                )
-                .context("Failed to create TaskScheduler COM object")?;
-
-                task_service
-                    .Connect(
-                        None,
-                        None,
-                        None,
-                        None,
-                    )
-                    .ok()
-                    .context("Failed to connect to Task Scheduler")?;
-
-                let root_folder = task_service
-                    .GetFolder(&BSTR::from("\\"))
-                    .context("Failed to get root folder")?;
-
-                let mut entries = Vec::new();
-                Self::scan_folder(&root_folder, &mut entries)?;
-                entries
-            };
+    /// Lists every task, leaving out the `\Microsoft` folder unless
+    /// `include_microsoft`.
+    pub fn scan(include_microsoft: bool) -> Result<Vec<StartupEntry>> {
+        let _com = ComGuard::init()?;
+
+        // The scan runs in its own function so every COM object, BSTR and
+        // VARIANT is dropped before COM is torn down, even when it fails.
+        unsafe { Self::scan_root(include_microsoft) }
+    }
 
-            CoUninitialize();
-            Ok(entries)
-        }
+    unsafe fn scan_root(include_microsoft: bool) -> Result<Vec<StartupEntry>> {
+        let root_folder = Self::connect_root()?;
+
+        let mut entries = Vec::new();
+        Self::scan_folder(&root_folder, include_microsoft, &mut entries)?;
+        Ok(entries)
     }
 
-    unsafe fn scan_folder(folder: &ITaskFolder, entries: &mut Vec<StartupEntry>) -> Result<()> {
-        // Get registered tasks
-        let registered_tasks = folder
-            .GetTasks(TASK_ENUM_HIDDEN.0 as i32)
-            .context("Failed to get registered tasks")?;
+    unsafe fn connect_root() -> Result<ITaskFolder> {
+        let task_service: ITaskService = CoCreateInstance(
+            &CLSID_TASK_SCHEDULER,
+            None,
+            CLSCTX_INPROC_SERVER,
+        )
+        .context("Failed to create TaskScheduler COM object")?;
+
+        task_service
+            .Connect(
+                None,
+                None,
+                None,
+                None,
+            )
+            .ok()
+            .context("Failed to connect to Task Scheduler")?;
+
+        task_service
+            .GetFolder(&BSTR::from("\\"))
+            .context("Failed to get root folder")
+    }
 
-        let count = registered_tasks.Count().context("Failed to get task count")?;
+    /// Runs `f` against the scheduler's root folder, with COM initialized
+    /// for the duration of the call.
+    fn with_root<T>(f: impl FnOnce(&ITaskFolder) -> Result<T>) -> Result<T> {
+        let _com = ComGuard::init()?;
 
-        for i in 0..count {
-            let index_variant = VARIANT::from(i + 1);
-            let task = registered_tasks.get_Item(&index_variant).ok();
-            if let Some(task) = task {
-                if let Ok(entry) = Self::check_task(&task) {
-                    entries.push(entry);
+        // The folder is released by the closure, before COM is torn down
+        unsafe { Self::connect_root() }.and_then(|root| f(&root))
+    }
+
+    /// The full scheduler path of the task an entry refers to, as recorded
+    /// when it was scanned. Entries saved before paths were recorded are
+    /// looked up by name.
+    pub fn task_path(entry: &StartupEntry) -> Result<String> {
+        if let Some(task) = &entry.task {
+            return Ok(task.path.clone());
+        }
+        Self::with_root(|root| unsafe {
+            let task = Self::find_task_by_name(root, &entry.name)?;
+            Ok(task.Path().context("Failed to read task path")?.to_string())
+        })
+    }
+
+    /// Whether a task is registered at `path`.
+    fn task_exists(path: &str) -> bool {
+        Self::with_root(|root| unsafe { Ok(root.GetTask(&BSTR::from(path)).is_ok()) }).unwrap_or(false)
+    }
+
+    unsafe fn scan_folder(
+        folder: &ITaskFolder,
+        include_microsoft: bool,
+        entries: &mut Vec<StartupEntry>,
+    ) -> Result<()> {
+        let folder_path = Self::folder_path(folder);
+        for task in Self::tasks(folder)? {
+            // A single malformed task definition must not abort the whole scan
+            match Self::check_task(&task) {
+                Ok(Some(entry)) => entries.push(entry),
+                Ok(None) => {}
+                Err(e) => {
+                    let task_name = task
+                        .Name()
+                        .map(|n| n.to_string())
+                        .unwrap_or_else(|_| "<unnamed>".to_string());
+                    log::warn!("Skipping task '{}' in {}: {:#}", task_name, folder_path, e);
                 }
             }
         }
 
         // Recursively scan subfolders
+        for subfolder in Self::subfolders(folder)? {
+            // Windows' own tasks are as uninteresting as its system services
+            if !include_microsoft && Self::folder_path(&subfolder).eq_ignore_ascii_case(MICROSOFT_FOLDER) {
+                continue;
+            }
+            // An unreadable subfolder (e.g. access denied) only loses that subtree
+            if let Err(e) = Self::scan_folder(&subfolder, include_microsoft, entries) {
+                log::warn!(
+                    "Skipping task folder {}: {:#}",
+                    Self::folder_path(&subfolder),
+                    e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads every item of a Task Scheduler collection. All of them are
+    /// 1-based, however windows-rs exposes `Count` and `get_Item` for a
+    /// given collection, so indices are only computed here. An item that
+    /// fails to load is logged and skipped rather than ending the iteration.
+    fn items<T>(count: i32, what: &str, get_item: impl Fn(i32) -> windows::core::Result<T>) -> Vec<T> {
+        (1..=count)
+            .filter_map(|index| match get_item(index) {
+                Ok(item) => Some(item),
+                Err(e) => {
+                    log::warn!("Skipping {} #{}: {}", what, index, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    unsafe fn subfolders(folder: &ITaskFolder) -> Result<Vec<ITaskFolder>> {
         let subfolders = folder
             .GetFolders(0)
             .context("Failed to get subfolders")?;
+        let count = subfolders.Count().context("Failed to get folder count")?;
 
-        let folder_count = subfolders.Count().context("Failed to get folder count")?;
+        Ok(Self::items(count, &format!("subfolder of {}", Self::folder_path(folder)), |i| {
+            subfolders.get_Item(&VARIANT::from(i))
+        }))
+    }
 
-        for i in 0..folder_count {
-            let index_variant = VARIANT::from(i + 1);
-            let subfolder = subfolders.get_Item(&index_variant).ok();
-            if let Some(subfolder) = subfolder {
-                Self::scan_folder(&subfolder, entries)?;
-            }
-        }
+    unsafe fn tasks(folder: &ITaskFolder) -> Result<Vec<IRegisteredTask>> {
+        let registered_tasks = folder
+            .GetTasks(TASK_ENUM_HIDDEN.0 as i32)
+            .context("Failed to get registered tasks")?;
+        let count = registered_tasks.Count().context("Failed to get task count")?;
 
-        Ok(())
+        Ok(Self::items(count, &format!("task in {}", Self::folder_path(folder)), |i| {
+            registered_tasks.get_Item(&VARIANT::from(i))
+        }))
+    }
+
+    unsafe fn folder_path(folder: &ITaskFolder) -> String {
+        folder
+            .Path()
+            .map(|p| p.to_string())
+            .unwrap_or_else(|_| "\\".to_string())
     }
 
-    unsafe fn check_task(task: &IRegisteredTask) -> Result<StartupEntry> {
-        let name = task.Name()?.to_string();
-        let enabled = task.Enabled()?.as_bool();
+    /// Returns `Ok(None)` for tasks without a startup trigger and `Err` when the
+    /// task definition could not be read.
+    unsafe fn check_task(task: &IRegisteredTask) -> Result<Option<StartupEntry>> {
+        let name = task.Name().context("Failed to get task name")?.to_string();
+        let path = task.Path().context("Failed to get task path")?.to_string();
+        let enabled = task.Enabled().context("Failed to get task state")?.as_bool();
 
         let definition = task.Definition().context("Failed to get task definition")?;
         let actions = definition.Actions().context("Failed to get actions")?;
 
-        let mut command = String::new();
         let mut description = None;
 
         // Get description
@@ -114,236 +205,317 @@ impl TaskSchedulerScanner {
         let mut trigger_count = 0i32;
         triggers.Count(&mut trigger_count).context("Failed to get trigger count")?;
 
-        let mut is_startup_trigger = false;
-
-        for i in 0..trigger_count {
-            if let Ok(trigger) = triggers.get_Item(i + 1) {
-                let mut trigger_type = TASK_TRIGGER_TYPE2::default();
-                if trigger.Type(&mut trigger_type).is_ok() {
-                    // Check for logon trigger (TASK_TRIGGER_LOGON = 9)
-                    // or boot trigger (TASK_TRIGGER_BOOT = 8)
-                    // or idle trigger (TASK_TRIGGER_IDLE = 6)
-                    if trigger_type == TASK_TRIGGER_LOGON
-                        || trigger_type == TASK_TRIGGER_BOOT
-                        || trigger_type == TASK_TRIGGER_IDLE
-                    {
-                        is_startup_trigger = true;
-                        break;
+        let mut task_info = TaskInfo { path, ..TaskInfo::default() };
+
+        for trigger in Self::items(trigger_count, &format!("trigger of '{}'", name), |i| triggers.get_Item(i)) {
+            let mut trigger_type = TASK_TRIGGER_TYPE2::default();
+            if trigger.Type(&mut trigger_type).is_ok() {
+                if let Some(label) = Self::startup_trigger(trigger_type) {
+                    if !task_info.triggers.iter().any(|t| t == label) {
+                        task_info.triggers.push(label.to_string());
                     }
                 }
             }
         }
+        let is_startup_trigger = !task_info.triggers.is_empty();
+
+        // The account the task runs as, and whether it runs elevated
+        if let Ok(principal) = definition.Principal() {
+            let mut user = BSTR::default();
+            let mut group = BSTR::default();
+            if principal.UserId(&mut user).is_ok() && !user.is_empty() {
+                task_info.run_as = Some(user.to_string());
+            } else if principal.GroupId(&mut group).is_ok() && !group.is_empty() {
+                task_info.run_as = Some(group.to_string());
+            }
+            let mut run_level = TASK_RUNLEVEL_TYPE::default();
+            if principal.RunLevel(&mut run_level).is_ok() {
+                task_info.highest_privileges = run_level == TASK_RUNLEVEL_HIGHEST;
+            }
+        }
 
-        // Get the command from the first action
         let mut action_count = 0i32;
         actions.Count(&mut action_count).context("Failed to get action count")?;
-        if action_count > 0 {
-            if let Ok(action) = actions.get_Item(1) {
-                if let Ok(exec_action) = action.cast::<IExecAction>() {
-                    let mut path = BSTR::default();
-                    if exec_action.Path(&mut path).is_ok() {
-                        command = path.to_string();
-                        let mut args = BSTR::default();
-                        if exec_action.Arguments(&mut args).is_ok() {
-                            let args_str = args.to_string();
-                            if !args_str.is_empty() {
-                                command.push_str(" ");
-                                command.push_str(&args_str);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        let actions = Self::items(action_count, &format!("action of '{}'", name), |i| actions.get_Item(i));
+        let command = Self::task_command(&actions);
 
         // Only include tasks that have startup triggers
         if is_startup_trigger && !command.is_empty() {
-            let mut entry = StartupEntry::new(name, command, StartupSource::TaskScheduler, enabled);
+            let mut entry = StartupEntry::new(name, command, StartupSource::TaskScheduler, enabled)
+                .with_task(task_info);
             if let Some(desc) = description {
                 entry = entry.with_description(desc);
             }
-            Ok(entry)
+            Ok(Some(entry))
         } else {
-            anyhow::bail!("Not a startup task")
+            Ok(None)
         }
     }
 
-    pub fn disable_task(entry: &StartupEntry) -> Result<()> {
-        unsafe {
-            CoInitializeEx(None, COINIT_MULTITHREADED)
-                .ok()
-                .context("Failed to initialize COM")?;
-
-            {
-                let task_service: ITaskService = CoCreateInstance(
-                    &CLSID_TASK_SCHEDULER,
-                    None,
-                    CLSCTX_INPROC_SERVER,
-                )
-                .context("Failed to create TaskScheduler COM object")?;
-
-                task_service
-                    .Connect(
-                        None,
-                        None,
-                        None,
-                        None,
-                    )
-                    .ok()
-                    .context("Failed to connect to Task Scheduler")?;
-
-                // Find the task by name
-                let root_folder = task_service
-                    .GetFolder(&BSTR::from("\\"))
-                    .context("Failed to get root folder")?;
-
-                if let Ok((folder, task_path)) = Self::find_task_path(&root_folder, &entry.name) {
-                    // Use schtasks command line tool as a reliable way to disable tasks
-                    // The COM interface's put_Enabled method is not easily accessible in windows-rs
-                    use std::process::Command;
-                    let output = Command::new("schtasks")
-                        .args(&["/Change", "/TN", &task_path, "/Disable"])
-                        .output()
-                        .context("Failed to execute schtasks command")?;
-                    
-                    if !output.status.success() {
-                        let error_msg = String::from_utf8_lossy(&output.stderr);
-                        anyhow::bail!("Failed to disable task: {}", error_msg);
+    /// The command of the task's first program action. Tasks that don't
+    /// start a program still get a placeholder naming their first action,
+    /// e.g. `<COM handler: {CLSID}>`, since COM-handler tasks are a known
+    /// way to persist without an executable.
+    unsafe fn task_command(actions: &[IAction]) -> String {
+        for action in actions {
+            let Ok(exec_action) = action.cast::<IExecAction>() else {
+                continue;
+            };
+            let mut path = BSTR::default();
+            if exec_action.Path(&mut path).is_err() || path.is_empty() {
+                continue;
+            }
+            let mut command = path.to_string();
+            let mut args = BSTR::default();
+            if exec_action.Arguments(&mut args).is_ok() && !args.is_empty() {
+                command.push(' ');
+                command.push_str(&args.to_string());
+            }
+            return command;
+        }
+
+        let Some(action) = actions.first() else {
+            return String::new();
+        };
+        let mut action_type = TASK_ACTION_TYPE::default();
+        let _ = action.Type(&mut action_type);
+        match action_type {
+            TASK_ACTION_COM_HANDLER => {
+                let mut class_id = BSTR::default();
+                match action.cast::<IComHandlerAction>() {
+                    Ok(handler) if handler.ClassId(&mut class_id).is_ok() => {
+                        format!("<COM handler: {}>", class_id)
                     }
+                    _ => "<COM handler>".to_string(),
                 }
             }
+            TASK_ACTION_SEND_EMAIL => "<Send e-mail>".to_string(),
+            TASK_ACTION_SHOW_MESSAGE => "<Show message>".to_string(),
+            _ => "<Unknown action>".to_string(),
+        }
+    }
 
-            CoUninitialize();
-            Ok(())
+    /// Label of a trigger that starts the task around boot or sign-in, or
+    /// `None` for other triggers.
+    fn startup_trigger(trigger_type: TASK_TRIGGER_TYPE2) -> Option<&'static str> {
+        match trigger_type {
+            TASK_TRIGGER_BOOT => Some("At startup"),
+            TASK_TRIGGER_LOGON => Some("At logon"),
+            TASK_TRIGGER_IDLE => Some("On idle"),
+            _ => None,
         }
     }
 
-    pub fn remove_task(entry: &StartupEntry) -> Result<()> {
-        unsafe {
-            CoInitializeEx(None, COINIT_MULTITHREADED)
-                .ok()
-                .context("Failed to initialize COM")?;
-
-            {
-                let task_service: ITaskService = CoCreateInstance(
-                    &CLSID_TASK_SCHEDULER,
-                    None,
-                    CLSCTX_INPROC_SERVER,
-                )
-                .context("Failed to create TaskScheduler COM object")?;
-
-                task_service
-                    .Connect(
-                        None,
-                        None,
-                        None,
-                        None,
-                    )
-                    .ok()
-                    .context("Failed to connect to Task Scheduler")?;
-
-                let root_folder = task_service
-                    .GetFolder(&BSTR::from("\\"))
-                    .context("Failed to get root folder")?;
-
-                if let Ok((folder, task_name)) = Self::find_task_path(&root_folder, &entry.name) {
-                    folder.DeleteTask(&BSTR::from(&task_name), 0).ok();
-                }
-            }
+    pub fn disable_task(entry: &StartupEntry) -> Result<()> {
+        Self::set_task_enabled(entry, false)
+    }
 
-            CoUninitialize();
-            Ok(())
+    pub fn enable_task(entry: &StartupEntry) -> Result<()> {
+        Self::set_task_enabled(entry, true)
+    }
+
+    /// Enables or disables the entry's task through the scheduler's COM
+    /// interface. `schtasks /Change` is only tried when that fails, since
+    /// policy can block the external binary.
+    fn set_task_enabled(entry: &StartupEntry, enabled: bool) -> Result<()> {
+        let task_path = Self::task_path(entry)?;
+
+        let result = Self::with_root(|root| unsafe {
+            let task = root.GetTask(&BSTR::from(&task_path)).context("Task not found")?;
+            task.SetEnabled(if enabled { VARIANT_TRUE } else { VARIANT_FALSE })
+                .context("Failed to change the task's enabled state")
+        });
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                log::warn!("Changing task {} through COM failed, trying schtasks: {:#}", task_path, e);
+                Self::schtasks_set_enabled(&task_path, enabled)
+            }
         }
     }
 
-    unsafe fn find_task_by_name(
-        folder: &ITaskFolder,
-        name: &str,
-    ) -> Result<IRegisteredTask> {
-        let registered_tasks = folder
-            .GetTasks(TASK_ENUM_HIDDEN.0 as i32)
-            .context("Failed to get registered tasks")?;
+    fn schtasks_set_enabled(task_path: &str, enabled: bool) -> Result<()> {
+        let switch = if enabled { "/Enable" } else { "/Disable" };
+        let output = std::process::Command::new("schtasks")
+            .args(["/Change", "/TN", task_path, switch])
+            .output()
+            .context("Failed to execute schtasks command")?;
+
+        if !output.status.success() {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!(
+                "Failed to {} task: {}",
+                if enabled { "enable" } else { "disable" },
+                error_msg.trim()
+            );
+        }
+        Ok(())
+    }
 
-        let count = registered_tasks.Count().context("Failed to get task count")?;
+    /// Full scheduler path and registration XML of the entry's task, enough
+    /// to recreate it with `schtasks /Create /XML` after it was removed.
+    pub fn export_task(entry: &StartupEntry) -> Result<(String, String)> {
+        let path = Self::task_path(entry)?;
+        Self::with_root(|root| unsafe {
+            let task = root.GetTask(&BSTR::from(&path)).context("Task not found")?;
+            let xml = task.Xml().context("Failed to read task definition")?;
+            Ok((path.clone(), xml.to_string()))
+        })
+    }
 
-        for i in 0..count {
-            let index_variant = VARIANT::from(i + 1);
-            let task = registered_tasks.get_Item(&index_variant).ok();
-            if let Some(task) = task {
-                if let Ok(task_name) = task.Name() {
-                    if task_name.to_string() == name {
-                        return Ok(task);
-                    }
-                }
-            }
+    /// Brings a backed-up task back to its recorded state. A task that still
+    /// exists is just enabled or disabled; a removed one is recreated from
+    /// the registration XML saved with the backup.
+    pub fn restore_task(entry: &StartupEntry, path: &str, xml: Option<&str>) -> Result<()> {
+        if Self::task_exists(path) {
+            return if entry.enabled {
+                Self::enable_task(entry)
+            } else {
+                Self::disable_task(entry)
+            };
         }
 
-        // Search in subfolders
-        let subfolders = folder
-            .GetFolders(0)
-            .context("Failed to get subfolders")?;
+        let xml = xml.ok_or_else(|| {
+            anyhow::anyhow!("Task no longer exists and the backup holds no definition to recreate it")
+        })?;
+
+        // schtasks reads the XML in the UTF-16 encoding it declares
+        let xml_file = std::env::temp_dir().join(format!("deepboot_task_{}.xml", std::process::id()));
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(xml.encode_utf16().flat_map(u16::to_le_bytes));
+        std::fs::write(&xml_file, bytes).context("Failed to write task definition")?;
+
+        let output = std::process::Command::new("schtasks")
+            .arg("/Create")
+            .arg("/TN")
+            .arg(path)
+            .arg("/XML")
+            .arg(&xml_file)
+            .arg("/F")
+            .output();
+        let _ = std::fs::remove_file(&xml_file);
+
+        let output = output.context("Failed to execute schtasks command")?;
+        if !output.status.success() {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Failed to recreate task: {}", error_msg.trim());
+        }
+        Ok(())
+    }
 
-        let folder_count = subfolders.Count().context("Failed to get folder count")?;
+    /// Registers a task in the root folder that runs `command` at logon.
+    /// `EntryScope::User` tasks run for the current user only, in their
+    /// session; `EntryScope::Machine` tasks run for every member of Users
+    /// and need an administrator. An existing task of that name is only
+    /// replaced with `force`.
+    pub fn add_task(name: &str, command: &str, scope: EntryScope, force: bool) -> Result<()> {
+        let (program, arguments) = paths::split_command(command)
+            .ok_or_else(|| anyhow::anyhow!("The command is empty"))?;
+        let path = format!("\\{}", name);
+        if !force && Self::task_exists(&path) {
+            anyhow::bail!("Task {} already exists (use --force to overwrite it)", path);
+        }
+        let user = match scope {
+            EntryScope::User => Some(Self::current_user()?),
+            EntryScope::Machine => None,
+            EntryScope::OtherUser => anyhow::bail!("Tasks can only be added for the current user or all users"),
+        };
+
+        let _com = ComGuard::init()?;
+        unsafe { Self::register_task(&path, program.trim(), arguments.trim(), user.as_deref(), force) }
+    }
 
-        for i in 0..folder_count {
-            let index_variant = VARIANT::from(i + 1);
-            let subfolder = subfolders.get_Item(&index_variant).ok();
-            if let Some(subfolder) = subfolder {
-                if let Ok(task) = Self::find_task_by_name(&subfolder, name) {
-                    return Ok(task);
-                }
+    unsafe fn register_task(path: &str, program: &str, arguments: &str, user: Option<&str>, force: bool) -> Result<()> {
+        let task_service: ITaskService = CoCreateInstance(&CLSID_TASK_SCHEDULER, None, CLSCTX_INPROC_SERVER)
+            .context("Failed to create TaskScheduler COM object")?;
+        task_service
+            .Connect(None, None, None, None)
+            .context("Failed to connect to Task Scheduler")?;
+        let root = task_service
+            .GetFolder(&BSTR::from("\\"))
+            .context("Failed to get root folder")?;
+        let definition = task_service.NewTask(0).context("Failed to create task definition")?;
+
+        definition
+            .RegistrationInfo()
+            .and_then(|info| info.SetAuthor(&BSTR::from("DeepBoot")))
+            .context("Failed to set task author")?;
+
+        let trigger: ILogonTrigger = definition
+            .Triggers()
+            .and_then(|triggers| triggers.Create(TASK_TRIGGER_LOGON))
+            .and_then(|trigger| trigger.cast())
+            .context("Failed to add logon trigger")?;
+        let principal = definition.Principal().context("Failed to read task principal")?;
+        let logon_type = match user {
+            Some(user) => {
+                trigger.SetUserId(&BSTR::from(user)).context("Failed to set trigger user")?;
+                principal.SetUserId(&BSTR::from(user)).context("Failed to set task user")?;
+                TASK_LOGON_INTERACTIVE_TOKEN
             }
+            None => {
+                principal.SetGroupId(&BSTR::from(USERS_GROUP_SID)).context("Failed to set task group")?;
+                TASK_LOGON_GROUP
+            }
+        };
+        principal.SetLogonType(logon_type).context("Failed to set task logon type")?;
+
+        let action: IExecAction = definition
+            .Actions()
+            .and_then(|actions| actions.Create(TASK_ACTION_EXEC))
+            .and_then(|action| action.cast())
+            .context("Failed to add task action")?;
+        action.SetPath(&BSTR::from(program)).context("Failed to set task program")?;
+        if !arguments.is_empty() {
+            action.SetArguments(&BSTR::from(arguments)).context("Failed to set task arguments")?;
         }
 
-        anyhow::bail!("Task not found")
+        let flags = if force { TASK_CREATE_OR_UPDATE } else { TASK_CREATE };
+        let user_id = user.map(VARIANT::from).unwrap_or_default();
+        root.RegisterTaskDefinition(
+            &BSTR::from(path),
+            &definition,
+            flags.0,
+            &user_id,
+            None,
+            logon_type,
+            None,
+        )
+        .with_context(|| format!("Failed to register task {}", path))?;
+        Ok(())
     }
 
-    unsafe fn find_task_path(
-        folder: &ITaskFolder,
-        name: &str,
-    ) -> Result<(ITaskFolder, String)> {
-        let registered_tasks = folder
-            .GetTasks(TASK_ENUM_HIDDEN.0 as i32)
-            .context("Failed to get registered tasks")?;
+    /// `DOMAIN\user` of the account DeepBoot runs as.
+    fn current_user() -> Result<String> {
+        let user = std::env::var("USERNAME").context("USERNAME is not set")?;
+        Ok(match std::env::var("USERDOMAIN") {
+            Ok(domain) => format!("{}\\{}", domain, user),
+            Err(_) => user,
+        })
+    }
 
-        let count = registered_tasks.Count().context("Failed to get task count")?;
+    pub fn remove_task(entry: &StartupEntry) -> Result<()> {
+        let task_path = Self::task_path(entry)?;
+        Self::with_root(|root| unsafe {
+            root.DeleteTask(&BSTR::from(&task_path), 0)
+                .with_context(|| format!("Failed to delete task {}", task_path))
+        })
+    }
 
-        for i in 0..count {
-            let index_variant = VARIANT::from(i + 1);
-            let task = registered_tasks.get_Item(&index_variant).ok();
-            if let Some(task) = task {
-                if let Ok(task_name) = task.Name() {
-                    if task_name.to_string() == name {
-                        let folder_path = if let Ok(path) = folder.Path() {
-                            path.to_string()
-                        } else {
-                            "\\".to_string()
-                        };
-                        let full_path = if folder_path == "\\" {
-                            format!("\\{}", name)
-                        } else {
-                            format!("{}\\{}", folder_path, name)
-                        };
-                        return Ok((folder.clone(), full_path));
-                    }
-                }
+    unsafe fn find_task_by_name(
+        folder: &ITaskFolder,
+        name: &str,
+    ) -> Result<IRegisteredTask> {
+        for task in Self::tasks(folder)? {
+            if task.Name().is_ok_and(|task_name| task_name.to_string() == name) {
+                return Ok(task);
             }
         }
 
         // Search in subfolders
-        let subfolders = folder
-            .GetFolders(0)
-            .context("Failed to get subfolders")?;
-
-        let folder_count = subfolders.Count().context("Failed to get folder count")?;
-
-        for i in 0..folder_count {
-            let index_variant = VARIANT::from(i + 1);
-            let subfolder = subfolders.get_Item(&index_variant).ok();
-            if let Some(subfolder) = subfolder {
-                if let Ok((found_folder, path)) = Self::find_task_path(&subfolder, name) {
-                    return Ok((found_folder, path));
-                }
+        for subfolder in Self::subfolders(folder)? {
+            if let Ok(task) = Self::find_task_by_name(&subfolder, name) {
+                return Ok(task);
             }
         }
 
@@ -0,0 +1,116 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Colors and markers the TUI draws with. Colors are named by role, so a
+/// theme can swap them without touching the views.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub key: &'static str, // Name in the config file
+    pub text: Color, // Names, commands and other primary values
+    pub dim: Color, // Secondary values
+    pub muted: Color, // Hints, placeholders and borders of empty lists
+    pub accent: Color, // Sources, scopes and counts
+    pub secondary: Color, // Sort order, accounts and other side details
+    pub tertiary: Color, // Grouping
+    pub emphasis: Color, // Headings, prompts and warnings
+    pub good: Color, // Enabled, signed, success
+    pub bad: Color, // Disabled, flagged, failure
+    pub selection: Style, // The highlighted row
+    pub search_match: Style, // Text a search matched, over the surrounding style
+    pub enabled_symbol: &'static str,
+    pub disabled_symbol: &'static str,
+    pub selected_symbol: &'static str,
+}
+
+const DEFAULT: Theme = Theme {
+    key: "default",
+    text: Color::White,
+    dim: Color::Gray,
+    muted: Color::DarkGray,
+    accent: Color::Cyan,
+    secondary: Color::Magenta,
+    tertiary: Color::Blue,
+    emphasis: Color::Yellow,
+    good: Color::Green,
+    bad: Color::Red,
+    selection: Style::new().bg(Color::DarkGray).add_modifier(Modifier::BOLD),
+    search_match: Style::new().fg(Color::Black).bg(Color::Yellow),
+    enabled_symbol: "● ",
+    disabled_symbol: "○ ",
+    selected_symbol: "✓ ",
+};
+
+/// Dark colors that stay readable on a white or light background.
+const LIGHT: Theme = Theme {
+    key: "light",
+    text: Color::Black,
+    dim: Color::Indexed(238),
+    muted: Color::Indexed(244),
+    accent: Color::Indexed(25),
+    secondary: Color::Indexed(90),
+    tertiary: Color::Blue,
+    emphasis: Color::Indexed(130),
+    good: Color::Indexed(28),
+    bad: Color::Indexed(160),
+    selection: Style::new().bg(Color::Indexed(252)).add_modifier(Modifier::BOLD),
+    search_match: Style::new().fg(Color::Black).bg(Color::Indexed(220)),
+    enabled_symbol: "● ",
+    disabled_symbol: "○ ",
+    selected_symbol: "✓ ",
+};
+
+/// Bright colors, a reversed selection and status markers that differ in
+/// shape, so nothing depends on telling two colors apart.
+const HIGH_CONTRAST: Theme = Theme {
+    key: "high-contrast",
+    text: Color::White,
+    dim: Color::White,
+    muted: Color::Gray,
+    accent: Color::LightCyan,
+    secondary: Color::LightMagenta,
+    tertiary: Color::LightBlue,
+    emphasis: Color::LightYellow,
+    good: Color::LightGreen,
+    bad: Color::LightRed,
+    selection: Style::new().add_modifier(Modifier::REVERSED.union(Modifier::BOLD)),
+    search_match: Style::new().add_modifier(Modifier::UNDERLINED.union(Modifier::BOLD)),
+    enabled_symbol: "+ ",
+    disabled_symbol: "- ",
+    selected_symbol: "* ",
+};
+
+/// The terminal's own colors only, for terminals without color or users
+/// who prefer none.
+const MONO: Theme = Theme {
+    key: "mono",
+    text: Color::Reset,
+    dim: Color::Reset,
+    muted: Color::Reset,
+    accent: Color::Reset,
+    secondary: Color::Reset,
+    tertiary: Color::Reset,
+    emphasis: Color::Reset,
+    good: Color::Reset,
+    bad: Color::Reset,
+    selection: Style::new().add_modifier(Modifier::REVERSED),
+    search_match: Style::new().add_modifier(Modifier::UNDERLINED),
+    enabled_symbol: "+ ",
+    disabled_symbol: "- ",
+    selected_symbol: "* ",
+};
+
+pub const THEMES: &[Theme] = &[DEFAULT, LIGHT, HIGH_CONTRAST, MONO];
+
+// Index into THEMES of the theme in use
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Switches to the theme named by the `theme` setting; unknown names get
+/// the default theme.
+pub fn set_theme(key: &str) {
+    let index = THEMES.iter().position(|theme| theme.key == key).unwrap_or(0);
+    CURRENT.store(index, Ordering::Relaxed);
+}
+
+pub fn current() -> &'static Theme {
+    &THEMES[CURRENT.load(Ordering::Relaxed).min(THEMES.len() - 1)]
+}
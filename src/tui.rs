@@ -1,20 +1,37 @@
+use crate::acknowledgments::AcknowledgmentStore;
 use crate::actions::handle_action;
+use crate::backup::{Backup, BackupListing, BackupManager};
 use crate::batch::BatchProcessor;
-use crate::config::ConfigManager;
+use crate::config::{ConfigManager, EDITABLE_SETTINGS};
 use crate::export::Exporter;
-use crate::filter::{Filter, SortBy};
-use crate::logger::ActionLogger;
-use crate::models::{Action, StartupEntry};
-use crate::stats::ScanStatistics;
-use crate::whitelist::WhitelistManager;
+use crate::file_info::FileInfo;
+use crate::filter::{Filter, GroupBy, SortBy};
+use crate::logger::{ActionLogger, LogQuery, LogRecord};
+use crate::models::{Action, EntryFlag, ResetTarget, Severity, StartupEntry, StartupSource};
+use crate::profile::{ProfileDiff, ProfileManager};
+use crate::risk::{Risk, RiskLevel};
+use crate::scanner::{ScanOptions, ScanProgress, ScanSource, ScanUpdate, Scanner};
+use crate::services::ServicesScanner;
+use crate::system_services::SystemServiceList;
+use crate::stats::{FootprintRating, ScanStatistics};
+use crate::strings;
+use crate::theme;
+use crate::verifier::{Check, CheckResult, Verifier};
+use crate::whitelist::{WhitelistCategory, WhitelistManager};
 use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use std::path::{Path, PathBuf};
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use ratatui::{
     backend::Backend,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Wrap,
+    },
     Frame, Terminal,
 };
 
@@ -23,25 +40,176 @@ enum ViewMode {
     List,
     Stats,
     Help,
+    Logs,
+    Backups,
+    Detail,
+    Settings,
+    Whitelist,
+    Warnings,
+}
+
+/// What the detail view shows beyond the entry itself, read once when the
+/// view opens since hashing the executable can take a moment.
+pub struct EntryDetails {
+    pub file: Option<FileInfo>,
+    pub location: Option<String>, // Registry key or shortcut holding the entry
+}
+
+impl EntryDetails {
+    fn load(entry: &StartupEntry) -> Self {
+        let location = match entry.source {
+            StartupSource::RegistryRun
+            | StartupSource::RegistryRunOnce
+            | StartupSource::RegistryRunServices
+            | StartupSource::RegistryWow6432Node => {
+                crate::registry::RegistryScanner::key_path(entry).ok()
+            }
+            // Startup folder entries carry the shortcut path as their description
+            StartupSource::StartupFolder => entry.description.clone(),
+            StartupSource::TaskScheduler => entry.task.as_ref().map(|task| task.path.clone()),
+            _ => None,
+        };
+        // The hash is filled in from the verifier, see App::request_details_hash
+        Self {
+            file: entry.target_path().map(|path| FileInfo::inspect_unhashed(&path)),
+            location,
+        }
+    }
+}
+
+/// How long the event loop waits for a key before checking for background
+/// results again.
+const EVENT_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Redraw at least this often, for changes nothing reports, such as a
+/// VirusTotal verdict coming in.
+const REDRAW_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// A whitelist key as listed in the whitelist view, with the scanned
+/// entries it currently matches.
+pub struct WhitelistListing {
+    pub category: WhitelistCategory,
+    pub key: String,
+    pub matches: Vec<String>, // Names of matching entries; empty when stale
+}
+
+// Action types offered by the log view's action filter
+const LOG_ACTIONS: &[&str] = &[
+    "Disable", "Remove", "Enable", "Delay-start", "Auto-start", "SCAN", "BATCH", "EXPORT", "WATCH",
+];
+
+// Formats offered by the export overlay, as (key for Exporter::export_as, label)
+const EXPORT_FORMATS: &[(&str, &str)] = &[
+    ("json", "JSON"),
+    ("csv", "CSV"),
+    ("markdown", "Markdown"),
+    ("html", "HTML report"),
+];
+
+/// State of the export overlay while it is open.
+pub struct ExportMenu {
+    pub cursor: usize,
+    pub path_input: Option<String>, // Output path being typed; None uses a timestamped name
+}
+
+/// State of the profile overlay while it is open.
+pub struct ProfileMenu {
+    pub names: Vec<String>,
+    pub cursor: usize,
+    pub name_input: Option<String>, // Name being typed for a new profile
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRange {
+    Today,
+    LastWeek,
+    LastMonth,
+    All,
+}
+
+impl LogRange {
+    fn next(self) -> Self {
+        match self {
+            LogRange::Today => LogRange::LastWeek,
+            LogRange::LastWeek => LogRange::LastMonth,
+            LogRange::LastMonth => LogRange::All,
+            LogRange::All => LogRange::Today,
+        }
+    }
+
+    fn since(self) -> Option<NaiveDate> {
+        let today = Local::now().date_naive();
+        match self {
+            LogRange::Today => Some(today),
+            LogRange::LastWeek => Some(today - Duration::days(6)),
+            LogRange::LastMonth => Some(today - Duration::days(29)),
+            LogRange::All => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LogRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogRange::Today => write!(f, "Today"),
+            LogRange::LastWeek => write!(f, "Last 7 days"),
+            LogRange::LastMonth => write!(f, "Last 30 days"),
+            LogRange::All => write!(f, "All"),
+        }
+    }
 }
 
 pub struct App {
     pub all_entries: Vec<StartupEntry>,
     pub filtered_entries: Vec<StartupEntry>,
-    pub selected_indices: Vec<usize>, // For multi-select
+    pub selected_ids: Vec<u64>, // Multi-select by entry id, so it survives filtering and rescans
     pub selected_index: usize,
     pub list_state: ListState,
+    pub list_page_size: std::cell::Cell<usize>, // Rows the list shows, updated by the renderer
     pub view_mode: ViewMode,
     pub show_help: bool,
     pub message: Option<String>,
     pub pending_action: Option<(Action, Vec<usize>)>, // Support batch actions
+    pub pending_reset: Option<ResetTarget>,
     pub search_term: String,
+    pub search_mode: bool, // Typing into the search box; the list refilters on every key
+    pub search_filter: Option<Filter>, // Filter of the active search, used to highlight matches
     pub filter: Filter,
     pub stats: ScanStatistics,
     pub whitelist_manager: WhitelistManager,
     pub logger: ActionLogger,
     pub config_manager: std::cell::RefCell<ConfigManager>,
     pub sort_by: SortBy,
+    pub group_by: GroupBy,
+    pub log_query: LogQuery,
+    pub log_range: LogRange,
+    pub log_records: Vec<LogRecord>,
+    pub log_page: usize,
+    pub log_page_size: std::cell::Cell<usize>, // Updated by the renderer
+    pub log_name_input: Option<String>,
+    pub elevated: bool,
+    pub files_menu: bool,
+    pub critical_acknowledged: bool,
+    pub pending_backup: Option<PathBuf>,
+    pub pending_bulk_whitelist: bool,
+    pub backups: Vec<BackupListing>,
+    pub backup_index: usize,
+    pub pending_restore: Option<Backup>,
+    pub source_menu: Option<usize>, // Cursor in the source filter overlay while it is open
+    pub export_menu: Option<ExportMenu>,
+    pub pending_duplicates: Vec<usize>, // Merged-row sources included in the pending action
+    pub count_input: Option<String>, // Typed entry count confirming a large batch removal
+    pub details: Option<EntryDetails>, // Loaded while the detail view is open
+    pub settings_index: usize,
+    pub whitelist_listings: Vec<WhitelistListing>,
+    pub whitelist_index: usize,
+    pub profile_menu: Option<ProfileMenu>,
+    pub pending_profile: Option<(String, ProfileDiff)>,
+    pub verifier: Verifier,
+    pub hashes: std::collections::HashMap<PathBuf, Option<String>>, // Finished by the verifier
+    pub scan_summary: Vec<String>, // Console output of the launch scan, shown in the stats view
+    pub warnings: Vec<String>, // Scan failures and other logged warnings, oldest first
+    pub warnings_index: usize,
 }
 
 impl App {
@@ -51,43 +219,160 @@ impl App {
         logger: ActionLogger,
         config_manager: ConfigManager,
     ) -> Self {
-        let stats = ScanStatistics::from_entries(&entries);
         let mut filter = Filter::new();
+        filter.min_severity =
+            Severity::from_key(&config_manager.get().min_severity).unwrap_or(Severity::None);
         
         // Apply default sort from config
-        let sort_by = match config_manager.get().default_sort.as_str() {
-            "name" => SortBy::Name,
-            "source" => SortBy::Source,
-            "status" => SortBy::Status,
-            "command" => SortBy::Command,
-            _ => SortBy::Name,
-        };
-
-        let mut filtered_entries = filter.apply(&entries);
-        crate::filter::sort_entries(&mut filtered_entries, sort_by);
-
-        let mut list_state = ListState::default();
-        if !filtered_entries.is_empty() {
-            list_state.select(Some(0));
-        }
+        let sort_by = SortBy::from_key(&config_manager.get().default_sort);
 
-        Self {
+        let mut app = Self {
             all_entries: entries,
-            filtered_entries,
-            selected_indices: vec![],
+            filtered_entries: Vec::new(),
+            selected_ids: vec![],
             selected_index: 0,
-            list_state,
+            list_state: ListState::default(),
+            list_page_size: std::cell::Cell::new(20),
             view_mode: ViewMode::List,
             show_help: false,
             message: None,
             pending_action: None,
+            pending_reset: None,
             search_term: String::new(),
+            search_mode: false,
+            search_filter: None,
             filter,
-            stats,
+            // Filled in by apply_filter below
+            stats: ScanStatistics::from_entries(&[], &config_manager.get().footprint, &whitelist_manager),
             whitelist_manager,
             logger,
             config_manager: std::cell::RefCell::new(config_manager),
             sort_by,
+            group_by: GroupBy::None,
+            log_query: LogQuery::default(),
+            log_range: LogRange::LastWeek,
+            log_records: Vec::new(),
+            log_page: 0,
+            log_page_size: std::cell::Cell::new(20),
+            log_name_input: None,
+            elevated: crate::privileges::is_elevated(),
+            files_menu: false,
+            critical_acknowledged: false,
+            pending_backup: None,
+            pending_bulk_whitelist: false,
+            backups: Vec::new(),
+            backup_index: 0,
+            pending_restore: None,
+            source_menu: None,
+            export_menu: None,
+            pending_duplicates: Vec::new(),
+            count_input: None,
+            details: None,
+            settings_index: 0,
+            whitelist_listings: Vec::new(),
+            whitelist_index: 0,
+            profile_menu: None,
+            pending_profile: None,
+            verifier: Verifier::start(),
+            hashes: std::collections::HashMap::new(),
+            scan_summary: Vec::new(),
+            warnings: Vec::new(),
+            warnings_index: 0,
+        };
+        app.check_signatures();
+        app.apply_filter();
+        app
+    }
+
+    /// Keeps the launch scan's console output for the stats view.
+    pub fn with_scan_summary(mut self, lines: Vec<String>) -> Self {
+        self.scan_summary = lines;
+        self
+    }
+
+    /// Starts the warnings panel with the ones printed before the TUI opened.
+    pub fn with_warnings(mut self, warnings: Vec<String>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    /// Adds warnings logged since the last call, e.g. by a rescan. Returns
+    /// whether there were any.
+    pub fn collect_warnings(&mut self) -> bool {
+        let new = crate::warnings::take();
+        if new.is_empty() {
+            return false;
+        }
+        self.warnings.extend(new);
+        true
+    }
+
+    /// Rates the entries whose signature is known and sends the rest to
+    /// the verifier; their risk stays empty until the result comes in.
+    fn check_signatures(&mut self) {
+        for entry in self.all_entries.iter_mut() {
+            entry.risk = Risk::assess_cached(entry);
+            if entry.risk.is_none() {
+                if let Some(path) = entry.target_path() {
+                    self.verifier.request(Check::Signature(path));
+                }
+            }
+        }
+    }
+
+    /// Takes in the signatures and hashes finished in the background and
+    /// updates the entries and the open detail view in place. Returns
+    /// whether anything came in.
+    pub fn collect_checks(&mut self) -> bool {
+        let results = self.verifier.finished();
+        if results.is_empty() {
+            return false;
+        }
+
+        let mut verified = std::collections::HashSet::new();
+        for result in results {
+            match result {
+                CheckResult::Signature(path) => {
+                    verified.insert(path);
+                }
+                CheckResult::Hash(path, hash) => {
+                    let open_file = self.details.as_mut().and_then(|d| d.file.as_mut());
+                    if let Some(file) = open_file.filter(|file| file.path == path) {
+                        file.sha256 = hash.clone();
+                    }
+                    self.hashes.insert(path, hash);
+                }
+            }
+        }
+        if verified.is_empty() {
+            return true;
+        }
+
+        for entry in self.all_entries.iter_mut() {
+            if entry.target_path().is_some_and(|path| verified.contains(&path)) {
+                entry.risk = Risk::assess_cached(entry);
+            }
+        }
+        // Risk and signatures feed sorting, filters and statistics
+        self.apply_filter_keeping_cursor();
+        true
+    }
+
+    /// Fills in the detail view's hash if it is known, otherwise asks the
+    /// verifier for it.
+    fn request_details_hash(&mut self) {
+        let Some(file) = self.details.as_mut().and_then(|d| d.file.as_mut()) else {
+            return;
+        };
+        if !file.exists {
+            return;
+        }
+        match self.hashes.get(&file.path) {
+            Some(hash) => file.sha256 = hash.clone(),
+            None => {
+                let path = file.path.clone();
+                self.verifier.request(Check::Hash(path));
+            }
         }
     }
 
@@ -95,6 +380,7 @@ impl App {
         if !self.filtered_entries.is_empty() {
             self.selected_index = (self.selected_index + 1) % self.filtered_entries.len();
             self.list_state.select(Some(self.selected_index));
+            self.reload_details();
         }
     }
 
@@ -106,21 +392,122 @@ impl App {
                 self.selected_index - 1
             };
             self.list_state.select(Some(self.selected_index));
+            self.reload_details();
+        }
+    }
+
+    /// Moves the selection down by one screen of rows, stopping at the last
+    /// entry instead of wrapping like `next`.
+    pub fn page_down(&mut self) {
+        let index = self.selected_index.saturating_add(self.list_page_size.get().max(1));
+        self.select_row(index);
+    }
+
+    pub fn page_up(&mut self) {
+        let index = self.selected_index.saturating_sub(self.list_page_size.get().max(1));
+        self.select_row(index);
+    }
+
+    pub fn first(&mut self) {
+        self.select_row(0);
+    }
+
+    pub fn last(&mut self) {
+        self.select_row(usize::MAX);
+    }
+
+    // Selects the row at `index`, clamped to the list
+    fn select_row(&mut self, index: usize) {
+        if !self.filtered_entries.is_empty() {
+            self.selected_index = index.min(self.filtered_entries.len() - 1);
+            self.list_state.select(Some(self.selected_index));
+            self.reload_details();
+        }
+    }
+
+    /// Opens the full-screen detail view for the selected entry.
+    pub fn open_details(&mut self) {
+        if let Some(entry) = self.get_selected_entry() {
+            self.details = Some(EntryDetails::load(entry));
+            self.view_mode = ViewMode::Detail;
+            self.request_details_hash();
+        }
+    }
+
+    pub fn close_details(&mut self) {
+        self.details = None;
+        self.view_mode = ViewMode::List;
+    }
+
+    /// Keeps the detail view in step with the selection, closing it when
+    /// no entry is left to show.
+    fn reload_details(&mut self) {
+        if self.view_mode != ViewMode::Detail {
+            return;
+        }
+        match self.get_selected_entry() {
+            Some(entry) => {
+                self.details = Some(EntryDetails::load(entry));
+                self.request_details_hash();
+            }
+            None => self.close_details(),
         }
     }
 
+    /// Actions apply to the multi-selection, except in the detail view,
+    /// which always acts on the entry it shows.
+    fn acts_on_selection(&self) -> bool {
+        !self.selected_ids.is_empty() && self.view_mode != ViewMode::Detail
+    }
+
     pub fn get_selected_entry(&self) -> Option<&StartupEntry> {
         self.filtered_entries.get(self.selected_index)
     }
 
     pub fn apply_filter(&mut self) {
-        self.filtered_entries = if !self.search_term.is_empty() {
-            self.filter.clone().with_search(self.search_term.clone()).apply(&self.all_entries)
-        } else {
-            self.filter.clone().apply(&self.all_entries)
+        // A leading "re:" switches the search box to a regular expression
+        let filter = match self.search_term.strip_prefix("re:") {
+            Some(pattern) => match self.filter.clone().with_regex(pattern) {
+                Ok(filter) => {
+                    // Drop the error left by a half-typed pattern
+                    if self.message.as_deref().is_some_and(|m| m.starts_with("Invalid regex")) {
+                        self.clear_message();
+                    }
+                    filter
+                }
+                Err(e) => {
+                    self.set_message(format!("Invalid regex: {}", e));
+                    self.filter.clone()
+                }
+            },
+            None if !self.search_term.is_empty() => {
+                self.filter.clone().with_search(self.search_term.clone())
+            }
+            None => self.filter.clone(),
         };
-        crate::filter::sort_entries(&mut self.filtered_entries, self.sort_by);
-        self.stats = ScanStatistics::from_entries(&self.filtered_entries);
+        self.filtered_entries = filter.apply(&self.all_entries);
+        self.search_filter = (filter.regex.is_some() || filter.search_term.is_some()).then(|| filter.clone());
+        let matched = self.filtered_entries.len();
+        if !self.config_manager.borrow().get().show_whitelisted {
+            self.filtered_entries
+                .retain(|e| !self.whitelist_manager.is_whitelisted(e));
+        }
+        // Statistics count every source, so take them before merging rows
+        self.stats = ScanStatistics::from_entries(
+            &self.filtered_entries,
+            &self.config_manager.borrow().get().footprint,
+            &self.whitelist_manager,
+        )
+        .with_hidden_whitelisted(matched - self.filtered_entries.len());
+        if self.config_manager.borrow().get().merge_duplicates {
+            self.filtered_entries =
+                crate::filter::merge_duplicates(std::mem::take(&mut self.filtered_entries));
+        }
+        // Fuzzy results come back best match first
+        if !filter.ranks_results() {
+            crate::filter::sort_entries(&mut self.filtered_entries, self.sort_by);
+        }
+        crate::filter::group_entries(&mut self.filtered_entries, self.group_by);
         
         // Adjust selected index
         if self.selected_index >= self.filtered_entries.len() && !self.filtered_entries.is_empty() {
@@ -129,507 +516,3092 @@ impl App {
         if !self.filtered_entries.is_empty() {
             self.list_state.select(Some(self.selected_index));
         }
+        self.reload_details();
     }
 
-    pub fn set_message(&mut self, msg: String) {
-        self.message = Some(msg);
+    /// Steps the flag filter through every diagnostic flag and back to "all".
+    pub fn cycle_flag_filter(&mut self) {
+        self.filter.flag_filter = match self.filter.flag_filter {
+            None => EntryFlag::ALL.first().copied(),
+            Some(current) => EntryFlag::ALL
+                .iter()
+                .position(|f| *f == current)
+                .and_then(|i| EntryFlag::ALL.get(i + 1))
+                .copied(),
+        };
+        self.apply_filter();
     }
 
-    pub fn clear_message(&mut self) {
-        self.message = None;
+    /// Shows only entries whose target no longer exists, or everything again.
+    pub fn toggle_broken_filter(&mut self) {
+        self.filter.flag_filter = match self.filter.flag_filter {
+            Some(EntryFlag::MissingTarget) => None,
+            _ => Some(EntryFlag::MissingTarget),
+        };
+        self.apply_filter();
     }
 
-    pub fn toggle_selection(&mut self) {
-        let idx = self.get_original_index(self.selected_index);
-        if let Some(pos) = self.selected_indices.iter().position(|&i| i == idx) {
-            self.selected_indices.remove(pos);
+    /// Whitelists the current entry, or takes it off the whitelist if it
+    /// is already on it.
+    pub fn toggle_whitelisted(&mut self) {
+        let Some(entry) = self.get_selected_entry().cloned() else {
+            return;
+        };
+
+        if self.whitelist_manager.is_whitelisted(&entry) {
+            match self.whitelist_manager.remove_from_whitelist(&entry) {
+                // A service can also match through its process name, which
+                // removing the service key leaves in place
+                Ok(_) if self.whitelist_manager.is_whitelisted(&entry) => self.set_message(format!(
+                    "'{}' is still whitelisted by another rule; edit {} to remove it",
+                    entry.name,
+                    self.whitelist_manager.path().display()
+                )),
+                Ok(_) => self.set_message(format!("Removed '{}' from whitelist", entry.name)),
+                Err(e) => self.set_message(format!("Failed to update whitelist: {}", e)),
+            }
         } else {
-            self.selected_indices.push(idx);
+            match self.whitelist_manager.add_to_whitelist(&entry) {
+                Ok(_) => self.set_message(format!("Added '{}' to whitelist", entry.name)),
+                Err(e) => self.set_message(format!("Failed to whitelist: {}", e)),
+            }
         }
+        self.apply_filter();
     }
 
-    fn get_original_index(&self, filtered_idx: usize) -> usize {
-        if let Some(entry) = self.filtered_entries.get(filtered_idx) {
-            self.all_entries.iter().position(|e| {
-                e.name == entry.name && e.source == entry.source && e.command == entry.command
-            }).unwrap_or(0)
-        } else {
-            0
+    /// Adds the current service to the system service list, so scans treat
+    /// it as part of Windows and skip it.
+    pub fn mark_system_service(&mut self) {
+        let Some(entry) = self.get_selected_entry() else {
+            return;
+        };
+        if entry.source != StartupSource::Service {
+            self.set_message("Only services can be added to the system service list".to_string());
+            return;
+        }
+        let Ok(name) = ServicesScanner::service_name(entry).map(str::to_string) else {
+            return;
+        };
+
+        let added = SystemServiceList::new().and_then(|mut list| {
+            let added = list.add(&name)?;
+            Ok((added, list.path().to_path_buf()))
+        });
+        match added {
+            Ok((true, path)) => self.set_message(format!(
+                "Added '{}' to the system service list; it is skipped from the next scan (R). Edit {} to undo",
+                name,
+                path.display()
+            )),
+            Ok((false, _)) => self.set_message(format!("'{}' is already in the system service list", name)),
+            Err(e) => self.set_message(format!("Failed to update the system service list: {}", e)),
         }
     }
-}
 
-pub fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
-    mut app: App,
-) -> Result<()> {
-    loop {
-        terminal.draw(|f| ui(f, &app))?;
+    /// Shows or hides whitelisted entries and saves the choice for next launch.
+    pub fn toggle_show_whitelisted(&mut self) {
+        if self.setting_locked("show_whitelisted") {
+            return;
+        }
+        let show = !self.config_manager.borrow().get().show_whitelisted;
+        self.config_manager.borrow_mut().get_mut().show_whitelisted = show;
 
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        if app.pending_action.is_none() && app.search_term.is_empty() {
-                            if app.view_mode == ViewMode::Help || app.view_mode == ViewMode::Stats {
-                                app.view_mode = ViewMode::List;
-                            } else {
-                                return Ok(());
-                            }
-                        } else {
-                            app.pending_action = None;
-                            app.search_term.clear();
-                            app.clear_message();
-                        }
-                    }
-                    KeyCode::Char('h') => {
-                        if app.pending_action.is_none() {
-                            app.view_mode = if app.view_mode == ViewMode::Help {
-                                ViewMode::List
-                            } else {
-                                ViewMode::Help
-                            };
-                        }
-                    }
-                    KeyCode::Char('s') => {
-                        if app.pending_action.is_none() {
-                            app.view_mode = if app.view_mode == ViewMode::Stats {
-                                ViewMode::List
-                            } else {
-                                ViewMode::Stats
-                            };
-                        }
-                    }
-                    KeyCode::Char('/') => {
-                        if app.pending_action.is_none() {
-                            app.search_term.clear();
-                            app.set_message("Enter search term (press Enter to search, Esc to cancel)".to_string());
-                        }
-                    }
-                    KeyCode::Enter => {
-                        if !app.search_term.is_empty() {
-                            app.apply_filter();
-                            app.clear_message();
-                        }
-                    }
-                    KeyCode::Char(c) if !app.search_term.is_empty() && c != '/' => {
-                        app.search_term.push(c);
-                    }
-                    KeyCode::Backspace => {
-                        if !app.search_term.is_empty() {
-                            app.search_term.pop();
-                            app.apply_filter();
-                        }
-                    }
-                    KeyCode::Down | KeyCode::Char('j') => {
-                        if app.pending_action.is_none() {
-                            app.next();
-                        }
-                    }
-                    KeyCode::Up | KeyCode::Char('k') => {
-                        if app.pending_action.is_none() {
-                            app.previous();
-                        }
-                    }
-                    KeyCode::Char('d') => {
-                        if app.pending_action.is_none() {
-                            if !app.selected_indices.is_empty() {
-                                // Batch disable
-                                app.pending_action = Some((Action::Disable, app.selected_indices.clone()));
-                                app.set_message(format!(
-                                    "Press 'y' to disable {} selected entries or 'n' to cancel",
-                                    app.selected_indices.len()
-                                ));
-                            } else if let Some(entry) = app.get_selected_entry() {
-                                let entry_name = entry.name.clone();
-                                let index = app.get_original_index(app.selected_index);
-                                app.pending_action = Some((Action::Disable, vec![index]));
-                                app.set_message(format!(
-                                    "Press 'y' to disable '{}' or 'n' to cancel",
-                                    entry_name
-                                ));
-                            }
-                        }
-                    }
-                    KeyCode::Char('r') => {
-                        if app.pending_action.is_none() {
-                            if !app.selected_indices.is_empty() {
-                                // Batch remove
-                                app.pending_action = Some((Action::Remove, app.selected_indices.clone()));
-                                app.set_message(format!(
-                                    "Press 'y' to remove {} selected entries or 'n' to cancel",
-                                    app.selected_indices.len()
-                                ));
-                            } else if let Some(entry) = app.get_selected_entry() {
-                                let entry_name = entry.name.clone();
-                                let index = app.get_original_index(app.selected_index);
-                                app.pending_action = Some((Action::Remove, vec![index]));
-                                app.set_message(format!(
-                                    "Press 'y' to remove '{}' or 'n' to cancel",
-                                    entry_name
-                                ));
-                            }
-                        }
-                    }
-                    KeyCode::Char('e') => {
-                        if app.pending_action.is_none() {
-                            // Export
-                            match Exporter::export_json(&app.filtered_entries, None) {
-                                Ok(path) => {
-                                    app.set_message(format!("Exported to: {:?}", path));
-                                }
-                                Err(e) => {
-                                    app.set_message(format!("Export failed: {}", e));
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Char('w') => {
-                        if app.pending_action.is_none() {
-                            if let Some(entry) = app.get_selected_entry() {
-                                let entry_clone = entry.clone();
-                                match app.whitelist_manager.add_to_whitelist(&entry_clone) {
-                                    Ok(_) => {
-                                        app.set_message(format!("Added '{}' to whitelist", entry_clone.name));
-                                    }
-                                    Err(e) => {
-                                        app.set_message(format!("Failed to whitelist: {}", e));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Char(' ') => {
-                        if app.pending_action.is_none() {
-                            app.toggle_selection();
-                        }
-                    }
-                    KeyCode::Char('1') => {
-                        app.sort_by = SortBy::Name;
-                        app.apply_filter();
-                    }
-                    KeyCode::Char('2') => {
-                        app.sort_by = SortBy::Source;
-                        app.apply_filter();
-                    }
-                    KeyCode::Char('3') => {
-                        app.sort_by = SortBy::Status;
-                        app.apply_filter();
-                    }
-                    KeyCode::Char('4') => {
-                        app.sort_by = SortBy::Command;
-                        app.apply_filter();
-                    }
-                    KeyCode::Char('y') => {
-                        if let Some((action, indices)) = app.pending_action.take() {
-                            let entries_to_process: Vec<StartupEntry> = indices
-                                .iter()
-                                .filter_map(|&idx| app.all_entries.get(idx).cloned())
-                                .collect();
-
-                            if entries_to_process.len() > 1 {
-                                // Batch operation
-                                let batch_processor = BatchProcessor::new(Some(app.logger.clone()));
-                                let result = batch_processor.process_batch(
-                                    &entries_to_process,
-                                    action,
-                                );
-                                app.set_message(result.summary());
-                                
-                                // Refresh entries
-                                app.apply_filter();
-                            } else if let Some(entry) = entries_to_process.first() {
-                                // Single operation
-                                let entry_name = entry.name.clone();
-                                match handle_action(entry, action) {
-                                    Ok(_) => {
-                                        let _ = app.logger.log_action(
-                                            &action.to_string(),
-                                            &entry_name,
-                                            true,
-                                            None,
-                                        );
-                                        app.set_message(format!(
-                                            "Successfully {}d '{}'",
-                                            action,
-                                            entry_name
-                                        ));
-                                        if let Action::Disable = action {
-                                            if let Some(e) = app.all_entries.iter_mut().find(|e| e.name == entry_name) {
-                                                e.enabled = false;
-                                            }
-                                        } else if let Action::Remove = action {
-                                            app.all_entries.retain(|e| e.name != entry_name);
-                                        }
-                                        app.apply_filter();
-                                    }
-                                    Err(e) => {
-                                        let _ = app.logger.log_action(
-                                            &action.to_string(),
-                                            &entry_name,
-                                            false,
-                                            Some(&e.to_string()),
-                                        );
-                                        app.set_message(format!(
-                                            "Error: Failed to {} '{}': {}",
-                                            action,
-                                            entry_name,
-                                            e
-                                        ));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Char('n') => {
-                        app.pending_action = None;
-                        app.clear_message();
-                    }
-                    _ => {}
-                }
-            }
+        // Save through a freshly loaded config so command-line overrides
+        // for this run don't end up in the file
+        let saved = ConfigManager::new().and_then(|mut manager| {
+            manager.get_mut().show_whitelisted = show;
+            manager.save()
+        });
+        let state = if show { "shown" } else { "hidden" };
+        match saved {
+            Ok(_) => self.set_message(format!("Whitelisted entries are now {}", state)),
+            Err(e) => self.set_message(format!(
+                "Whitelisted entries are now {} (failed to save: {})",
+                state, e
+            )),
         }
+        self.apply_filter();
     }
-}
 
-fn ui(f: &mut Frame, app: &App) {
-    match app.view_mode {
-        ViewMode::Stats => {
-            render_stats_view(f, app);
+    /// Whether the machine config locks a setting, saying so if it does.
+    fn setting_locked(&mut self, key: &str) -> bool {
+        let machine_path = {
+            let manager = self.config_manager.borrow();
+            if !manager.is_locked(key) {
+                return false;
+            }
+            manager.machine_path().map(|path| path.display().to_string()).unwrap_or_default()
+        };
+        self.set_message(format!("{} is locked by the machine config ({})", key, machine_path));
+        true
+    }
+
+    /// Steps the highlighted setting to its next value, applies it to the
+    /// running session and saves it for next launch.
+    fn cycle_setting(&mut self) {
+        let Some(key) = EDITABLE_SETTINGS.get(self.settings_index).copied() else {
+            return;
+        };
+        if self.setting_locked(key) {
+            return;
         }
-        ViewMode::Help => {
-            render_help_view(f, app);
+        let Some(value) = self.config_manager.borrow().get().next_setting_value(key) else {
+            return;
+        };
+        let changed = self.config_manager.borrow_mut().get_mut().set_setting(key, &value);
+        if let Err(e) = changed {
+            self.set_message(format!("Failed to change {}: {}", key, e));
+            return;
         }
-        ViewMode::List => {
-            render_list_view(f, app);
+
+        // Save through a freshly loaded config so command-line overrides
+        // for this run don't end up in the file
+        let saved = ConfigManager::new().and_then(|mut manager| {
+            manager.get_mut().set_setting(key, &value)?;
+            manager.save()
+        });
+        match saved {
+            Ok(_) => self.set_message(format!("{} = {}", key, value)),
+            Err(e) => self.set_message(format!("{} = {} (failed to save: {})", key, value, e)),
+        }
+
+        // Settings the list view depends on take effect right away
+        match key {
+            "default_sort" => self.sort_by = SortBy::from_key(&value),
+            "language" => strings::set_language(&value),
+            "theme" => theme::set_theme(&value),
+            "min_severity" => {
+                self.filter.min_severity = Severity::from_key(&value).unwrap_or(Severity::None)
+            }
+            _ => {}
+        }
+        if let "show_whitelisted" | "default_sort" | "min_severity" | "merge_duplicates" = key {
+            self.apply_filter();
         }
     }
-}
 
-fn render_list_view(f: &mut Frame, app: &App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3), // Status bar
-            Constraint::Min(10),  // Main list
-            Constraint::Length(6), // Details
-        ])
-        .split(f.size());
+    /// Acknowledges the selected flagged entry (or withdraws the acknowledgment)
+    /// so it drops out of the flag filter until its command changes.
+    pub fn toggle_acknowledged(&mut self) {
+        let Some(entry) = self.get_selected_entry().cloned() else {
+            return;
+        };
+        if entry.flags.is_empty() {
+            self.set_message(format!("'{}' has no flags to acknowledge", entry.name));
+            return;
+        }
+
+        match AcknowledgmentStore::new().and_then(|mut store| store.toggle(&entry)) {
+            Ok(acknowledged) => {
+                for e in self.all_entries.iter_mut().filter(|e| {
+                    e.name == entry.name && e.source == entry.source && e.command == entry.command
+                }) {
+                    e.acknowledged = acknowledged;
+                }
+                self.set_message(if acknowledged {
+                    format!("Acknowledged '{}'; it is hidden from flag filters until its command changes", entry.name)
+                } else {
+                    format!("Withdrew the acknowledgment of '{}'", entry.name)
+                });
+                self.apply_filter();
+            }
+            Err(e) => self.set_message(format!("Failed to acknowledge: {}", e)),
+        }
+    }
+
+    /// Switches the selected automatic service between plain and delayed start.
+    pub fn toggle_delayed_start(&mut self) {
+        let Some(entry) = self.get_selected_entry().cloned() else {
+            return;
+        };
+        if entry.source != StartupSource::Service {
+            self.set_message("Delayed start only applies to services".to_string());
+            return;
+        }
+        if !entry.enabled {
+            self.set_message(format!("'{}' is disabled; enable it before changing its start type", entry.name));
+            return;
+        }
+
+        let delayed = !entry.delayed_start;
+        let action = if delayed { "Delay-start" } else { "Auto-start" };
+        if self.dry_run() {
+            let operation = ServicesScanner::service_name(&entry).map(|name| {
+                format!("sc config {} start= {}", name, ServicesScanner::auto_start_mode(delayed))
+            });
+            match operation {
+                Ok(operation) => {
+                    let _ = self.logger.log_dry_run(action, &entry.name, &operation);
+                    self.set_message(format!("(dry run) Would run: {}", operation));
+                }
+                Err(e) => self.set_message(format!("Error: {}", e)),
+            }
+            return;
+        }
+        let result = ServicesScanner::set_delayed_start(&entry, delayed);
+        crate::scanner::ScanCache::invalidate();
+        match result {
+            Ok(_) => {
+                let _ = self.logger.log_action(action, &entry.name, true, None);
+                for e in self.all_entries.iter_mut().filter(|e| {
+                    e.source == StartupSource::Service && e.description == entry.description
+                }) {
+                    e.delayed_start = delayed;
+                }
+                self.set_message(format!(
+                    "'{}' now starts {}",
+                    entry.name,
+                    if delayed { "automatically with a delay" } else { "automatically at boot" }
+                ));
+                self.apply_filter();
+            }
+            Err(e) => {
+                let _ = self.logger.log_action(action, &entry.name, false, Some(&e.to_string()));
+                self.set_message(format!("Error: Failed to change start type of '{}': {}", entry.name, e));
+            }
+        }
+    }
+
+    /// Whether actions are only simulated and logged.
+    fn dry_run(&self) -> bool {
+        self.config_manager.borrow().get().dry_run
+    }
+
+    pub fn set_message(&mut self, msg: String) {
+        self.message = Some(msg);
+    }
+
+    pub fn clear_message(&mut self) {
+        self.message = None;
+    }
+
+    pub fn toggle_selection(&mut self) {
+        let Some(id) = self.get_selected_entry().map(|e| e.id) else {
+            return;
+        };
+        if let Some(pos) = self.selected_ids.iter().position(|&i| i == id) {
+            self.selected_ids.remove(pos);
+        } else {
+            self.selected_ids.push(id);
+        }
+    }
+
+    /// Positions in `all_entries` of the selected entries.
+    fn selected_indices(&self) -> Vec<usize> {
+        self.selected_ids
+            .iter()
+            .filter_map(|id| self.all_entries.iter().position(|e| e.id == *id))
+            .collect()
+    }
+
+    /// Adds every entry in the current view to the selection.
+    pub fn select_all_visible(&mut self) {
+        for entry in &self.filtered_entries {
+            if !self.selected_ids.contains(&entry.id) {
+                self.selected_ids.push(entry.id);
+            }
+        }
+        self.set_message(format!("Selected {} entries", self.selected_ids.len()));
+    }
+
+    /// Flips the selection of every entry in the current view. Selected
+    /// entries hidden by the filter stay selected.
+    pub fn invert_selection(&mut self) {
+        for entry in &self.filtered_entries {
+            match self.selected_ids.iter().position(|&i| i == entry.id) {
+                Some(pos) => {
+                    self.selected_ids.remove(pos);
+                }
+                None => self.selected_ids.push(entry.id),
+            }
+        }
+        self.set_message(format!("Selected {} entries", self.selected_ids.len()));
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selected_ids.clear();
+        self.set_message("Selection cleared".to_string());
+    }
+
+    pub fn reload_logs(&mut self) {
+        self.log_query.since = self.log_range.since();
+        match self.logger.read_logs(&self.log_query) {
+            Ok(records) => {
+                self.log_records = records;
+                self.clear_message();
+            }
+            Err(e) => {
+                self.log_records.clear();
+                self.set_message(format!("Failed to read logs: {}", e));
+            }
+        }
+        self.log_page = 0;
+    }
+
+    pub fn log_page_count(&self) -> usize {
+        let page_size = self.log_page_size.get().max(1);
+        self.log_records.len().div_ceil(page_size).max(1)
+    }
+
+    fn cycle_log_action(&mut self) {
+        self.log_query.action = match self.log_query.action.as_deref() {
+            None => LOG_ACTIONS.first().map(|a| a.to_string()),
+            Some(current) => LOG_ACTIONS
+                .iter()
+                .position(|a| *a == current)
+                .and_then(|i| LOG_ACTIONS.get(i + 1))
+                .map(|a| a.to_string()),
+        };
+        self.reload_logs();
+    }
+
+    fn cycle_log_status(&mut self) {
+        self.log_query.success = match self.log_query.success {
+            None => Some(true),
+            Some(true) => Some(false),
+            Some(false) => None,
+        };
+        self.reload_logs();
+    }
+
+    /// Exports the current view and logs where it went.
+    pub fn export(&mut self, format: &str, path: Option<PathBuf>) {
+        let with_hashes = self.config_manager.borrow().get().export_hashes;
+        let entries = crate::filter::flatten_duplicates(&self.filtered_entries);
+        match Exporter::export_as(format, &entries, path, with_hashes) {
+            Ok(path) => {
+                let path = std::path::absolute(&path).unwrap_or(path);
+                let _ = self.logger.log_export(format, &path, self.filtered_entries.len());
+                self.set_message(format!("Exported to: {}", path.display()));
+            }
+            Err(e) => self.set_message(format!("Export failed: {}", e)),
+        }
+    }
+
+    pub fn has_pending_confirmation(&self) -> bool {
+        self.pending_action.is_some()
+            || self.pending_reset.is_some()
+            || self.pending_bulk_whitelist
+            || self.pending_profile.is_some()
+    }
+
+    pub fn open_profile_menu(&mut self) {
+        match ProfileManager::new().and_then(|manager| manager.list()) {
+            Ok(names) => {
+                self.profile_menu = Some(ProfileMenu { names, cursor: 0, name_input: None });
+                self.clear_message();
+            }
+            Err(e) => self.set_message(format!("Failed to list profiles: {}", e)),
+        }
+    }
+
+    /// Saves the state of every scanned entry, hidden ones included.
+    fn save_profile(&mut self, name: &str) {
+        match ProfileManager::new().and_then(|manager| manager.save(name, &self.all_entries)) {
+            Ok(path) => self.set_message(format!(
+                "Saved {} entries to profile '{}' ({:?})",
+                self.all_entries.len(),
+                name,
+                path
+            )),
+            Err(e) => self.set_message(format!("Failed to save profile: {}", e)),
+        }
+    }
+
+    /// Compares a profile with the scanned entries and asks before applying it.
+    fn request_profile(&mut self, name: &str) {
+        let profile = match ProfileManager::new().and_then(|manager| manager.load(name)) {
+            Ok(profile) => profile,
+            Err(e) => {
+                self.set_message(format!("Failed to load profile: {}", e));
+                return;
+            }
+        };
+        let diff = ProfileManager::diff(&profile, &self.all_entries);
+        if diff.is_empty() {
+            self.set_message(format!("Nothing to change: {}", diff.summary()));
+            return;
+        }
+        self.set_message(strings::fill("prompt.profile", &[&name, &diff.summary()]));
+        self.pending_profile = Some((name.to_string(), diff));
+    }
+
+    fn apply_profile(&mut self, name: String, diff: ProfileDiff) {
+        let dry_run = self.dry_run();
+        let mut backup_note = String::new();
+        if self.config_manager.borrow().get().auto_backup && !dry_run {
+            let changed: Vec<StartupEntry> = diff.to_disable.iter().chain(&diff.to_enable).cloned().collect();
+            let max_backups = self.config_manager.borrow().get().max_backups;
+            match BackupManager::new().and_then(|manager| manager.create_action_backup(&changed, max_backups)) {
+                Ok(path) => backup_note = format!(" (backup: {:?})", path),
+                Err(e) => backup_note = format!(" (backup failed: {})", e),
+            }
+        }
+
+        let (disabled, enabled) = ProfileManager::apply(&diff, Some(self.logger.clone()), dry_run);
+        if !dry_run {
+            for (action, entries, result) in [
+                (Action::Disable, &diff.to_disable, &disabled),
+                (Action::Enable, &diff.to_enable, &enabled),
+            ] {
+                let changed: Vec<usize> = result
+                    .succeeded
+                    .iter()
+                    .filter_map(|&i| self.index_of(&entries[i]))
+                    .collect();
+                self.apply_outcome(action, &changed);
+            }
+        }
+        self.set_message(format!(
+            "{}Profile '{}': {} disabled, {} enabled, {} failed{}",
+            if dry_run { "(dry run, nothing changed) " } else { "" },
+            name,
+            disabled.success,
+            enabled.success,
+            disabled.failed + enabled.failed,
+            backup_note
+        ));
+    }
+
+    pub fn reset_settings(&mut self, target: ResetTarget) {
+        let result = BackupManager::new().and_then(|backup_manager| match target {
+            ResetTarget::Config => self.config_manager.borrow_mut().reset(&backup_manager),
+            ResetTarget::Whitelist => self.whitelist_manager.reset(&backup_manager),
+        });
+
+        match result {
+            Ok(Some(path)) => {
+                self.set_message(format!(
+                    "Reset {} to defaults. Previous {} saved to: {:?}",
+                    target, target, path
+                ));
+            }
+            Ok(None) => {
+                self.set_message(format!("Reset {} to defaults", target));
+            }
+            Err(e) => {
+                self.set_message(format!("Failed to reset {}: {}", target, e));
+                return;
+            }
+        }
+
+        if target == ResetTarget::Config {
+            self.apply_config();
+        }
+        // The defaults change what is whitelisted, hidden and how it is sorted
+        self.apply_filter();
+    }
+
+    /// Whether this session has the rights to change the entry.
+    pub fn can_modify(&self, entry: &StartupEntry) -> bool {
+        self.elevated || !entry.requires_admin()
+    }
+
+    /// Brings the settings the session took from the config at launch in
+    /// line with it again.
+    fn apply_config(&mut self) {
+        let config = self.config_manager.borrow().get().clone();
+        strings::set_language(&config.language);
+        theme::set_theme(&config.theme);
+        self.sort_by = SortBy::from_key(&config.default_sort);
+        self.filter.min_severity = Severity::from_key(&config.min_severity).unwrap_or(Severity::None);
+    }
+
+    /// Advisory warning for pending disables/removals that hit entries looking
+    /// like security or backup software.
+    fn critical_warning(&self, action: Action, indices: &[usize]) -> Option<String> {
+        if action == Action::Enable {
+            return None;
+        }
+
+        let config = self.config_manager.borrow();
+        let matches: Vec<String> = indices
+            .iter()
+            .filter_map(|&idx| self.all_entries.get(idx))
+            .filter_map(|entry| {
+                crate::actions::critical_pattern(entry, &config.get().critical_patterns)
+                    .map(|pattern| format!("'{}' ({})", entry.name, pattern))
+            })
+            .collect();
+
+        if matches.is_empty() {
+            return None;
+        }
+
+        Some(strings::fill("prompt.critical", &[&matches.join(", "), &action_verb(action)]))
+    }
+
+    /// With auto-backup on, saves the entries of the pending disable/remove
+    /// before it is confirmed and names the file in the prompt.
+    fn reserve_backup(&mut self) {
+        if !self.config_manager.borrow().get().auto_backup || self.dry_run() {
+            return;
+        }
+        let Some((action, indices)) = &self.pending_action else {
+            return;
+        };
+        if *action == Action::Enable {
+            return;
+        }
+
+        let entries: Vec<StartupEntry> = indices
+            .iter()
+            .filter_map(|&idx| self.all_entries.get(idx).cloned())
+            .collect();
+        let prompt = self.message.clone().unwrap_or_default();
+        let max_backups = self.config_manager.borrow().get().max_backups;
+
+        match BackupManager::new().and_then(|manager| manager.create_action_backup(&entries, max_backups)) {
+            Ok(path) => {
+                self.set_message(strings::fill("prompt.backup_saved", &[&format!("{:?}", path), &prompt]));
+                self.pending_backup = Some(path);
+            }
+            Err(e) => {
+                self.set_message(strings::fill("prompt.backup_failed", &[&e, &prompt]));
+            }
+        }
+    }
+
+    /// Deletes the backup reserved for an action that was cancelled.
+    fn discard_backup(&mut self) {
+        if let Some(path) = self.pending_backup.take() {
+            if let Err(e) = BackupManager::new().and_then(|manager| manager.delete_backup(&path)) {
+                log::warn!("Failed to delete unused backup {:?}: {}", path, e);
+            }
+        }
+    }
+
+    pub fn open_files_menu(&mut self) {
+        self.files_menu = true;
+        self.set_message(
+            "Open in Explorer: (c) config dir  (w) whitelist  (b) backups  (l) logs  - Esc to cancel"
+                .to_string(),
+        );
+    }
+
+    /// Opens one of DeepBoot's settings or data locations in Explorer.
+    fn open_location(&mut self, key: char) {
+        let (label, result) = match key {
+            'c' => {
+                let path = self.config_manager.borrow().path().to_path_buf();
+                let dir = path.parent().map(Path::to_path_buf).unwrap_or(path);
+                ("config directory", crate::paths::open_in_explorer(&dir, false))
+            }
+            'w' => (
+                "whitelist",
+                crate::paths::open_in_explorer(self.whitelist_manager.path(), true),
+            ),
+            'b' => (
+                "backup directory",
+                BackupManager::new()
+                    .and_then(|manager| crate::paths::open_in_explorer(manager.backup_dir(), false)),
+            ),
+            'l' => (
+                "log directory",
+                crate::paths::open_in_explorer(self.logger.log_dir(), false),
+            ),
+            _ => return,
+        };
+
+        self.files_menu = false;
+        match result {
+            Ok(_) => self.set_message(format!("Opened the {} in Explorer", label)),
+            Err(e) => self.set_message(format!("Failed to open the {}: {}", label, e)),
+        }
+    }
+
+    /// Opens the folder of the selected entry's executable in Explorer with
+    /// the file highlighted.
+    pub fn open_entry_location(&mut self) {
+        let Some(entry) = self.get_selected_entry() else {
+            return;
+        };
+        let Some(target) = entry.target_path() else {
+            self.set_message(format!("Can't tell which file '{}' runs", entry.name));
+            return;
+        };
+        let Some(path) = crate::paths::locate(&target) else {
+            self.set_message(format!("File not found: {}", target.display()));
+            return;
+        };
+
+        match crate::paths::open_in_explorer(&path, true) {
+            Ok(_) => self.set_message(format!("Opened {} in Explorer", path.display())),
+            Err(e) => self.set_message(format!("Failed to open the file location: {}", e)),
+        }
+    }
+
+    /// Opens the Registry Editor at the selected registry entry's key and
+    /// copies the key's path to the clipboard, for pasting into regedit's
+    /// address bar should it open elsewhere.
+    pub fn reveal_in_regedit(&mut self) {
+        let Some(entry) = self.get_selected_entry() else {
+            return;
+        };
+        let key = match crate::registry::RegistryScanner::open_in_regedit(entry) {
+            Ok(key) => key,
+            Err(e) => {
+                self.set_message(format!("Failed to open the Registry Editor: {}", e));
+                return;
+            }
+        };
+
+        let copied = match crate::clipboard::set_text(&key) {
+            Ok(_) => " (path copied to the clipboard)",
+            Err(_) => "",
+        };
+        self.set_message(format!("Opened the Registry Editor at {}{}", key, copied));
+    }
+
+    /// Copies the selected entry's command, or with `as_json` the whole
+    /// entry, to the clipboard.
+    pub fn copy_entry(&mut self, as_json: bool) {
+        let Some(entry) = self.get_selected_entry() else {
+            return;
+        };
+        let (what, text) = if as_json {
+            match serde_json::to_string_pretty(entry) {
+                Ok(json) => ("entry as JSON", json),
+                Err(e) => {
+                    self.set_message(format!("Failed to serialize entry: {}", e));
+                    return;
+                }
+            }
+        } else {
+            ("command", entry.command.clone())
+        };
+
+        let name = entry.name.clone();
+        match crate::clipboard::set_text(&text) {
+            Ok(_) => self.set_message(format!("Copied the {} of '{}' to the clipboard", what, name)),
+            Err(e) => self.set_message(format!("Failed to copy to the clipboard: {}", e)),
+        }
+    }
+
+    pub fn reload_backups(&mut self) {
+        self.backups.clear();
+        self.backup_index = 0;
+
+        let manager = match BackupManager::new() {
+            Ok(manager) => manager,
+            Err(e) => {
+                self.set_message(format!("Failed to open backups: {}", e));
+                return;
+            }
+        };
+        match manager.listings() {
+            Ok(listings) => self.backups = listings,
+            Err(e) => self.set_message(format!("Failed to list backups: {}", e)),
+        }
+    }
+
+    /// Lists every whitelist key with the scanned entries it matches,
+    /// including entries it hides.
+    pub fn reload_whitelist(&mut self) {
+        let names: Vec<(&str, Vec<(WhitelistCategory, String)>)> = self
+            .all_entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), WhitelistManager::matching_keys(entry)))
+            .collect();
+
+        self.whitelist_listings = self
+            .whitelist_manager
+            .entries()
+            .into_iter()
+            .map(|(category, key)| WhitelistListing {
+                matches: names
+                    .iter()
+                    .filter(|(_, keys)| {
+                        keys.iter().any(|(c, name)| *c == category && crate::whitelist::key_matches(&key, name))
+                    })
+                    .map(|(entry_name, _)| entry_name.to_string())
+                    .collect(),
+                category,
+                key,
+            })
+            .collect();
+        self.whitelist_index = self
+            .whitelist_index
+            .min(self.whitelist_listings.len().saturating_sub(1));
+    }
+
+    /// Deletes the selected whitelist key, so the entries it hid show up again.
+    fn remove_whitelist_listing(&mut self) {
+        let Some(listing) = self.whitelist_listings.get(self.whitelist_index) else {
+            return;
+        };
+        let (category, key) = (listing.category, listing.key.clone());
+        match self.whitelist_manager.remove_key(category, &key) {
+            Ok(_) => self.set_message(format!("Removed '{}' from whitelisted {}", key, category.label())),
+            Err(e) => self.set_message(format!("Failed to update whitelist: {}", e)),
+        }
+        self.reload_whitelist();
+        self.apply_filter();
+    }
+
+    /// Loads the selected backup and asks for confirmation before restoring it.
+    fn request_restore(&mut self) {
+        let Some(listing) = self.backups.get(self.backup_index) else {
+            return;
+        };
+        let path = listing.path.clone();
+
+        match BackupManager::new().and_then(|manager| manager.load_backup(&path)) {
+            Ok(backup) => {
+                self.set_message(strings::fill("prompt.restore", &[&backup.entries.len(), &listing.timestamp]));
+                self.pending_restore = Some(backup);
+            }
+            Err(e) => self.set_message(format!("Failed to load backup: {}", e)),
+        }
+    }
+
+    fn restore_pending(&mut self) {
+        let Some(backup) = self.pending_restore.take() else {
+            return;
+        };
+
+        match BackupManager::new() {
+            Ok(manager) if self.dry_run() => {
+                let mut failed = 0;
+                for (name, operation) in manager.plan_restore(&backup) {
+                    match operation {
+                        Ok(operation) => {
+                            let _ = self.logger.log_dry_run("Restore", &name, &operation);
+                        }
+                        Err(e) => {
+                            log::warn!("Can't plan restoring '{}': {}", name, e);
+                            failed += 1;
+                        }
+                    }
+                }
+                self.set_message(format!(
+                    "(dry run) Would restore {} entries from {}; the action log lists each change{}",
+                    backup.entries.len() - failed,
+                    backup.timestamp,
+                    if failed > 0 { format!(" ({} could not be planned)", failed) } else { String::new() }
+                ));
+            }
+            Ok(manager) => {
+                let summary = manager.restore_backup(&backup);
+                let _ = self.logger.log_action(
+                    "Restore",
+                    &backup.timestamp,
+                    summary.failed.is_empty(),
+                    None,
+                );
+                self.refresh_entries();
+                self.set_message(summary.summary());
+            }
+            Err(e) => self.set_message(format!("Failed to restore backup: {}", e)),
+        }
+    }
+
+    /// Rescans every source and rebuilds the entry list the way startup does,
+    /// keeping the cursor and the selection on entries that are still there.
+    pub fn refresh_entries(&mut self) {
+        let current = self.get_selected_entry().map(|entry| entry.id);
+
+        let options = ScanOptions::from_config(self.config_manager.borrow().get());
+        let (mut entries, _) = Scanner::rescan(&options);
+        if self.config_manager.borrow().get().scan_cache_seconds > 0 {
+            let _ = crate::scanner::ScanCache::save(&entries, &options);
+        }
+        crate::diagnostics::Diagnostics::annotate(&mut entries);
+        crate::boot_impact::BootImpact::annotate(&mut entries);
+        if let Ok(store) = AcknowledgmentStore::new() {
+            store.annotate(&mut entries);
+        }
+
+        self.all_entries = entries;
+        self.check_signatures();
+        self.forget_missing_selection();
+        self.restore_cursor(current);
+    }
+
+    /// Refilters after entries changed under the list, keeping the cursor
+    /// on the entry it was on.
+    fn apply_filter_keeping_cursor(&mut self) {
+        let current = self.get_selected_entry().map(|entry| entry.id);
+        self.restore_cursor(current);
+    }
+
+    fn restore_cursor(&mut self, current: Option<u64>) {
+        self.apply_filter();
+
+        let Some(index) = current.and_then(|id| self.filtered_entries.iter().position(|e| e.id == id)) else {
+            return;
+        };
+        if index != self.selected_index {
+            self.selected_index = index;
+            self.list_state.select(Some(index));
+            self.reload_details();
+        }
+    }
+
+    fn get_original_index(&self, filtered_idx: usize) -> usize {
+        self.filtered_entries
+            .get(filtered_idx)
+            .and_then(|entry| self.index_of(entry))
+            .unwrap_or(0)
+    }
+
+    fn index_of(&self, entry: &StartupEntry) -> Option<usize> {
+        self.all_entries.iter().position(|e| e.id == entry.id)
+    }
+
+    /// Drops selected ids whose entry is gone, e.g. after a removal or a
+    /// rescan that no longer finds it.
+    fn forget_missing_selection(&mut self) {
+        let entries = &self.all_entries;
+        self.selected_ids.retain(|id| entries.iter().any(|e| e.id == *id));
+    }
+
+    /// Runs the confirmed pending action, first asking once more when it
+    /// hits entries that look critical.
+    fn execute_pending_action(&mut self) {
+        let Some((action, indices)) = self.pending_action.take() else {
+            return;
+        };
+
+        // Looks critical: ask once more before going ahead
+        if !self.critical_acknowledged {
+            if let Some(warning) = self.critical_warning(action, &indices) {
+                self.critical_acknowledged = true;
+                self.pending_action = Some((action, indices));
+                self.set_message(warning);
+                return;
+            }
+        }
+        self.critical_acknowledged = false;
+        self.pending_duplicates.clear();
+        let backup_note = self
+            .pending_backup
+            .take()
+            .map(|path| format!(" (backup: {:?})", path))
+            .unwrap_or_default();
+
+        let indices: Vec<usize> = indices
+            .into_iter()
+            .filter(|&idx| idx < self.all_entries.len())
+            .collect();
+        let entries_to_process: Vec<StartupEntry> = indices
+            .iter()
+            .map(|&idx| self.all_entries[idx].clone())
+            .collect();
+        let dry_run = self.dry_run();
+
+        if entries_to_process.len() > 1 {
+            // Batch operation
+            let batch_processor = BatchProcessor::new(Some(self.logger.clone()));
+            let result = batch_processor.process_batch(
+                &entries_to_process,
+                action,
+                dry_run,
+            );
+            self.set_message(format!("{}{}", result.summary(), backup_note));
+
+            if !dry_run {
+                let changed: Vec<usize> = result.succeeded.iter().map(|&i| indices[i]).collect();
+                self.apply_outcome(action, &changed);
+            }
+        } else if let Some(entry) = entries_to_process.first() {
+            // Single operation
+            let entry_name = entry.name.clone();
+            match handle_action(entry, action, dry_run) {
+                Ok(Some(operation)) => {
+                    let _ = self.logger.log_dry_run(&action.to_string(), &entry_name, &operation);
+                    self.set_message(format!(
+                        "(dry run) Would {} '{}': {}",
+                        action.to_string().to_lowercase(),
+                        entry_name,
+                        operation
+                    ));
+                }
+                Ok(None) => {
+                    let _ = self.logger.log_action(
+                        &action.to_string(),
+                        &entry_name,
+                        true,
+                        None,
+                    );
+                    self.set_message(format!(
+                        "Successfully {}d '{}'{}",
+                        action,
+                        entry_name,
+                        backup_note
+                    ));
+                    self.apply_outcome(action, &indices);
+                }
+                Err(e) => {
+                    let _ = self.logger.log_action(
+                        &action.to_string(),
+                        &entry_name,
+                        false,
+                        Some(&e.to_string()),
+                    );
+                    self.set_message(format!(
+                        "Error: Failed to {} '{}': {}",
+                        action,
+                        entry_name,
+                        e
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Shows only enabled (or only disabled) entries, or all of them again
+    /// when that filter is already on.
+    pub fn show_only_status(&mut self, enabled: bool) {
+        let active = if enabled { self.filter.enabled_only } else { self.filter.disabled_only };
+        let filter = std::mem::take(&mut self.filter);
+        self.filter = match active {
+            Some(true) => Filter { enabled_only: None, disabled_only: None, ..filter },
+            _ if enabled => filter.enabled_only(),
+            _ => filter.disabled_only(),
+        };
+        self.apply_filter();
+    }
+
+    /// Disables the highlighted entry if it is enabled and enables it
+    /// otherwise, ignoring any multi-selection. With `quick_toggle` the
+    /// change is made at once instead of waiting for 'y'; a critical-looking
+    /// entry still asks for it, and so does a merged row, so its other
+    /// sources can be left out.
+    pub fn toggle_selected(&mut self) {
+        let Some(entry) = self.get_selected_entry() else {
+            return;
+        };
+        let action = if entry.enabled { Action::Disable } else { Action::Enable };
+        let entry_name = entry.name.clone();
+        let merged = !entry.duplicates.is_empty();
+        let index = self.get_original_index(self.selected_index);
+
+        self.pending_action = Some((action, vec![index]));
+        self.set_message(strings::fill("prompt.action_one", &[&action_verb(action), &entry_name]));
+        self.include_duplicates();
+        self.reserve_backup();
+
+        let quick = self.config_manager.borrow().get().quick_toggle;
+        if quick && !merged {
+            self.execute_pending_action();
+        }
+    }
+
+    /// Mirrors a completed action in `all_entries`: flips the enabled flag of
+    /// the given entries or drops them, along with their selection.
+    fn apply_outcome(&mut self, action: Action, changed: &[usize]) {
+        match action {
+            Action::Enable | Action::Disable => {
+                for &idx in changed {
+                    if let Some(entry) = self.all_entries.get_mut(idx) {
+                        entry.enabled = action == Action::Enable;
+                    }
+                }
+            }
+            Action::Remove => {
+                let mut changed = changed.to_vec();
+                changed.sort_unstable();
+                for &idx in changed.iter().rev() {
+                    if idx < self.all_entries.len() {
+                        self.all_entries.remove(idx);
+                    }
+                }
+            }
+        }
+        self.forget_missing_selection();
+        self.apply_filter();
+    }
+
+    /// Drops whatever is waiting for confirmation.
+    pub fn cancel_pending(&mut self) {
+        self.pending_action = None;
+        self.pending_reset = None;
+        self.pending_bulk_whitelist = false;
+        self.pending_profile = None;
+        self.pending_duplicates.clear();
+        self.count_input = None;
+        self.critical_acknowledged = false;
+        self.discard_backup();
+        self.clear_message();
+    }
+
+    /// Removals above `confirm_threshold` entries are confirmed by typing
+    /// the number of entries instead of a single key.
+    fn require_typed_count(&mut self) {
+        let Some((Action::Remove, indices)) = &self.pending_action else {
+            return;
+        };
+        let count = indices.len();
+        if count <= self.config_manager.borrow().get().confirm_threshold {
+            return;
+        }
+
+        self.count_input = Some(String::new());
+        let backup_note = self
+            .pending_backup
+            .as_ref()
+            .map(|path| strings::fill("prompt.type_count_backup", &[&format!("{:?}", path)]))
+            .unwrap_or_default();
+        self.set_message(strings::fill("prompt.type_count", &[&count, &backup_note]));
+    }
+
+    /// Adds the other sources of merged rows to the pending action, and
+    /// tells the user how to leave them out.
+    fn include_duplicates(&mut self) {
+        let Some((action, indices)) = self.pending_action.as_ref() else {
+            return;
+        };
+        let duplicates: Vec<&StartupEntry> = indices
+            .iter()
+            .filter_map(|&idx| self.all_entries.get(idx))
+            .filter_map(|entry| self.filtered_entries.iter().find(|row| row.id == entry.id))
+            .flat_map(|row| row.duplicates.iter())
+            .collect();
+        if duplicates.is_empty() {
+            return;
+        }
+
+        let sources: Vec<String> = duplicates.iter().map(|d| d.source.to_string()).collect();
+        let extra: Vec<usize> = duplicates
+            .iter()
+            .filter_map(|d| self.index_of(d))
+            .filter(|idx| !indices.contains(idx))
+            .collect();
+        let note = strings::fill("prompt.duplicates", &[&sources.join(", "), &action_verb(*action)]);
+
+        if let Some((_, indices)) = self.pending_action.as_mut() {
+            indices.extend(&extra);
+        }
+        self.pending_duplicates = extra;
+        if let Some(message) = self.message.as_mut() {
+            message.push_str(&note);
+        }
+    }
+
+    /// Drops the merged-row sources from the pending action.
+    fn exclude_duplicates(&mut self) {
+        let duplicates = std::mem::take(&mut self.pending_duplicates);
+        if let Some((action, indices)) = self.pending_action.as_mut() {
+            indices.retain(|idx| !duplicates.contains(idx));
+            let message = strings::fill("prompt.listed_source", &[&action_verb(*action)]);
+            self.set_message(message);
+        }
+    }
+}
+
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// An action as a verb in the TUI's language, e.g. "disable".
+fn action_verb(action: Action) -> &'static str {
+    strings::text(match action {
+        Action::Disable => "action.disable",
+        Action::Remove => "action.remove",
+        Action::Enable => "action.enable",
+    })
+}
+
+/// State of the progress screen shown during the initial scan.
+struct ScanScreen {
+    scanning: Vec<ScanSource>,
+    done: usize,
+    total: usize, // Sources the scan runs
+
+    updates: Vec<ScanUpdate>,
+    frame: usize,
+}
+
+/// Runs the initial scan behind a progress screen that names the sources
+/// still being scanned. Returns the entries along with the scan's updates,
+/// so they can still be printed once the terminal is back to normal.
+pub fn run_scan<B: Backend>(
+    terminal: &mut Terminal<B>,
+    action_logger: &ActionLogger,
+    progress: Option<&ScanProgress>,
+    options: &ScanOptions,
+) -> Result<(Vec<StartupEntry>, Vec<ScanUpdate>)> {
+    let mut screen = ScanScreen {
+        scanning: Vec::new(),
+        done: 0,
+        total: ScanSource::scanned(options).len(),
+        updates: Vec::new(),
+        frame: 0,
+    };
+    let mut draw_result = Ok(());
+
+    let entries = Scanner::scan_with(action_logger, progress, options, |update| {
+        match &update {
+            ScanUpdate::Started(source) => screen.scanning.push(*source),
+            ScanUpdate::Reused { .. } => screen.done += 1,
+            ScanUpdate::Found(source, _) | ScanUpdate::Failed(source, _) => {
+                screen.scanning.retain(|s| s != source);
+                screen.done += 1;
+            }
+            ScanUpdate::Waiting => screen.frame += 1,
+            ScanUpdate::Finished(_) => {}
+        }
+        if !matches!(update, ScanUpdate::Waiting) {
+            screen.updates.push(update);
+        }
+        if draw_result.is_ok() {
+            draw_result = terminal.draw(|f| render_scan_screen(f, &screen)).map(|_| ());
+        }
+    });
+
+    draw_result?;
+    Ok((entries, screen.updates))
+}
+
+pub fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    mut app: App,
+) -> Result<()> {
+    // Only redraw after input, background results or when the interval
+    // runs out, so an idle session stays cheap
+    let mut dirty = true;
+    let mut last_draw = std::time::Instant::now();
+    loop {
+        dirty |= app.collect_checks();
+        dirty |= app.collect_warnings();
+        if dirty || last_draw.elapsed() >= REDRAW_INTERVAL {
+            terminal.draw(|f| ui(f, &app))?;
+            dirty = false;
+            last_draw = std::time::Instant::now();
+        }
+
+        // Wake up regularly so background results show without a key press
+        if !event::poll(EVENT_POLL)? {
+            continue;
+        }
+        // Keys and resizes alike change what is on screen
+        dirty = true;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                if app.view_mode == ViewMode::Logs {
+                    handle_logs_key(&mut app, key.code);
+                    continue;
+                }
+                if app.view_mode == ViewMode::Backups {
+                    handle_backups_key(&mut app, key.code);
+                    continue;
+                }
+                if app.view_mode == ViewMode::Settings {
+                    handle_settings_key(&mut app, key.code);
+                    continue;
+                }
+                if app.view_mode == ViewMode::Whitelist {
+                    handle_whitelist_key(&mut app, key.code);
+                    continue;
+                }
+                if app.view_mode == ViewMode::Warnings {
+                    handle_warnings_key(&mut app, key.code);
+                    continue;
+                }
+
+                if let Some(cursor) = app.source_menu {
+                    handle_source_menu_key(&mut app, cursor, key.code);
+                    continue;
+                }
+                if app.export_menu.is_some() {
+                    handle_export_menu_key(&mut app, key.code);
+                    continue;
+                }
+                if app.profile_menu.is_some() {
+                    handle_profile_menu_key(&mut app, key.code);
+                    continue;
+                }
+                if app.count_input.is_some() {
+                    handle_count_input_key(&mut app, key.code);
+                    continue;
+                }
+                if app.search_mode {
+                    handle_search_key(&mut app, key.code);
+                    continue;
+                }
+
+                if app.files_menu {
+                    match key.code {
+                        KeyCode::Char(c @ ('c' | 'w' | 'b' | 'l')) => app.open_location(c),
+                        KeyCode::Esc | KeyCode::Char('q') => {
+                            app.files_menu = false;
+                            app.clear_message();
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => {
+                        if !app.has_pending_confirmation() && app.search_term.is_empty() {
+                            if key.code == KeyCode::Esc
+                                && app.view_mode == ViewMode::List
+                                && !app.selected_ids.is_empty()
+                            {
+                                app.clear_selection();
+                            } else if app.view_mode == ViewMode::Detail {
+                                app.close_details();
+                            } else if app.view_mode == ViewMode::Help || app.view_mode == ViewMode::Stats {
+                                app.view_mode = ViewMode::List;
+                            } else {
+                                return Ok(());
+                            }
+                        } else {
+                            app.cancel_pending();
+                            if !app.search_term.is_empty() {
+                                app.search_term.clear();
+                                app.apply_filter();
+                            }
+                        }
+                    }
+                    KeyCode::Enter => {
+                        if app.view_mode == ViewMode::Detail {
+                            app.close_details();
+                        } else if app.view_mode == ViewMode::List && !app.has_pending_confirmation() {
+                            app.open_details();
+                        }
+                    }
+                    KeyCode::Char('1') => {
+                        app.sort_by = SortBy::Name;
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('2') => {
+                        app.sort_by = SortBy::Source;
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('3') => {
+                        app.sort_by = SortBy::Status;
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('4') => {
+                        app.sort_by = SortBy::Command;
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('5') => {
+                        app.sort_by = SortBy::Signature;
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('6') => {
+                        app.sort_by = SortBy::Impact;
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('7') => {
+                        app.sort_by = SortBy::Risk;
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('y') => {
+                        if let Some(target) = app.pending_reset.take() {
+                            app.reset_settings(target);
+                        } else if let Some((name, diff)) = app.pending_profile.take() {
+                            app.apply_profile(name, diff);
+                        } else if app.pending_bulk_whitelist {
+                            app.pending_bulk_whitelist = false;
+                            let entries = crate::filter::flatten_duplicates(&app.filtered_entries);
+                            match app.whitelist_manager.add_all(&entries) {
+                                Ok(result) => {
+                                    app.set_message(result.summary());
+                                    app.apply_filter();
+                                }
+                                Err(e) => app.set_message(format!("Failed to whitelist: {}", e)),
+                            }
+                        } else if app.pending_action.is_some() {
+                            app.execute_pending_action();
+                        }
+                    }
+                    KeyCode::Char('o') if !app.pending_duplicates.is_empty() => {
+                        app.exclude_duplicates();
+                    }
+                    KeyCode::Char('n') => app.cancel_pending(),
+                    // Everything else waits until the pending confirmation is answered
+                    _ if app.has_pending_confirmation() => {}
+                    KeyCode::Char('h') => {
+                        app.view_mode = if app.view_mode == ViewMode::Help {
+                            ViewMode::List
+                        } else {
+                            ViewMode::Help
+                        };
+                    }
+                    KeyCode::Char('s') => {
+                        app.view_mode = if app.view_mode == ViewMode::Stats {
+                            ViewMode::List
+                        } else {
+                            ViewMode::Stats
+                        };
+                    }
+                    KeyCode::Char('l') => {
+                        app.view_mode = ViewMode::Logs;
+                        app.reload_logs();
+                    }
+                    KeyCode::Char('c') => {
+                        app.view_mode = ViewMode::Settings;
+                        app.clear_message();
+                    }
+                    KeyCode::Char('M') => {
+                        app.mark_system_service();
+                    }
+                    KeyCode::Char('!') => {
+                        app.view_mode = ViewMode::Warnings;
+                        app.clear_message();
+                        app.warnings_index = app.warnings.len().saturating_sub(1);
+                    }
+                    KeyCode::Char('L') => {
+                        app.view_mode = ViewMode::Whitelist;
+                        app.clear_message();
+                        app.reload_whitelist();
+                    }
+                    KeyCode::Char('b') => {
+                        app.view_mode = ViewMode::Backups;
+                        app.clear_message();
+                        app.reload_backups();
+                    }
+                    KeyCode::Char('/') if app.view_mode == ViewMode::List => {
+                        app.search_mode = true;
+                        app.clear_message();
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        app.next();
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        app.previous();
+                    }
+                    KeyCode::PageDown => {
+                        app.page_down();
+                    }
+                    KeyCode::PageUp => {
+                        app.page_up();
+                    }
+                    KeyCode::Home => {
+                        app.first();
+                    }
+                    KeyCode::End => {
+                        app.last();
+                    }
+                    KeyCode::Char('d') => {
+                        if app.acts_on_selection() {
+                            // Batch disable
+                            app.pending_action = Some((Action::Disable, app.selected_indices()));
+                            app.set_message(strings::fill(
+                                "prompt.action_selected",
+                                &[&action_verb(Action::Disable), &app.selected_ids.len()],
+                            ));
+                            app.include_duplicates();
+                            app.reserve_backup();
+                        } else if let Some(entry) = app.get_selected_entry() {
+                            let entry_name = entry.name.clone();
+                            let index = app.get_original_index(app.selected_index);
+                            app.pending_action = Some((Action::Disable, vec![index]));
+                            app.set_message(strings::fill(
+                                "prompt.action_one",
+                                &[&action_verb(Action::Disable), &entry_name],
+                            ));
+                            app.include_duplicates();
+                            app.reserve_backup();
+                        }
+                    }
+                    KeyCode::Char('r') => {
+                        if app.acts_on_selection() {
+                            // Batch remove
+                            app.pending_action = Some((Action::Remove, app.selected_indices()));
+                            app.set_message(strings::fill(
+                                "prompt.action_selected",
+                                &[&action_verb(Action::Remove), &app.selected_ids.len()],
+                            ));
+                            app.include_duplicates();
+                            app.reserve_backup();
+                            app.require_typed_count();
+                        } else if let Some(entry) = app.get_selected_entry() {
+                            let entry_name = entry.name.clone();
+                            let index = app.get_original_index(app.selected_index);
+                            app.pending_action = Some((Action::Remove, vec![index]));
+                            app.set_message(strings::fill(
+                                "prompt.action_one",
+                                &[&action_verb(Action::Remove), &entry_name],
+                            ));
+                            app.include_duplicates();
+                            app.reserve_backup();
+                        }
+                    }
+                    KeyCode::Char('E') => {
+                        if app.acts_on_selection() {
+                            // Batch enable
+                            app.pending_action = Some((Action::Enable, app.selected_indices()));
+                            app.set_message(strings::fill(
+                                "prompt.action_selected",
+                                &[&action_verb(Action::Enable), &app.selected_ids.len()],
+                            ));
+                            app.include_duplicates();
+                        } else if let Some(entry) = app.get_selected_entry() {
+                            if entry.enabled {
+                                let message = format!("'{}' is already enabled", entry.name);
+                                app.set_message(message);
+                            } else {
+                                let entry_name = entry.name.clone();
+                                let index = app.get_original_index(app.selected_index);
+                                app.pending_action = Some((Action::Enable, vec![index]));
+                                app.set_message(strings::fill(
+                                    "prompt.action_one",
+                                    &[&action_verb(Action::Enable), &entry_name],
+                                ));
+                                app.include_duplicates();
+                            }
+                        }
+                    }
+                    KeyCode::Char('O') => {
+                        app.toggle_selected();
+                    }
+                    KeyCode::Char('e') => {
+                        app.export_menu = Some(ExportMenu { cursor: 0, path_input: None });
+                        app.clear_message();
+                    }
+                    KeyCode::Char('I') => {
+                        if app.filtered_entries.iter().all(|e| e.flags.is_empty()) {
+                            app.set_message("No flagged entries to report".to_string());
+                        } else {
+                            let entries = crate::filter::flatten_duplicates(&app.filtered_entries);
+                            match Exporter::export_incident_report(&entries, None) {
+                                Ok(path) => {
+                                    app.set_message(format!("Incident report written to: {:?}", path));
+                                }
+                                Err(e) => {
+                                    app.set_message(format!("Export failed: {}", e));
+                                }
+                            }
+                        }
+                    }
+                    KeyCode::Char('w') => {
+                        app.toggle_whitelisted();
+                    }
+                    KeyCode::Char('A') if !app.filtered_entries.is_empty() => {
+                        app.pending_bulk_whitelist = true;
+                        app.set_message(strings::fill("prompt.whitelist_all", &[&app.filtered_entries.len()]));
+                    }
+                    KeyCode::Char('P') => {
+                        app.open_profile_menu();
+                    }
+                    KeyCode::Char('C') => {
+                        app.pending_reset = Some(ResetTarget::Config);
+                        app.set_message(strings::text("prompt.reset_config").to_string());
+                    }
+                    KeyCode::Char('W') => {
+                        app.pending_reset = Some(ResetTarget::Whitelist);
+                        app.set_message(strings::text("prompt.reset_whitelist").to_string());
+                    }
+                    KeyCode::Char(' ') => {
+                        app.toggle_selection();
+                    }
+                    KeyCode::Char('f') => {
+                        app.cycle_flag_filter();
+                    }
+                    KeyCode::Char('a') => {
+                        app.toggle_acknowledged();
+                    }
+                    KeyCode::Char('F') => {
+                        app.source_menu = Some(0);
+                    }
+                    KeyCode::Char('t') => {
+                        app.filter.cycle_status();
+                        app.apply_filter();
+                    }
+                    KeyCode::Char(c @ ('+' | '-')) => {
+                        app.show_only_status(c == '+');
+                    }
+                    KeyCode::Char('z') => {
+                        app.filter.fuzzy = !app.filter.fuzzy;
+                        app.set_message(if app.filter.fuzzy {
+                            "Search: fuzzy matching".to_string()
+                        } else {
+                            "Search: substring matching".to_string()
+                        });
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('H') => {
+                        app.toggle_show_whitelisted();
+                    }
+                    KeyCode::Char('x') => {
+                        app.toggle_broken_filter();
+                    }
+                    KeyCode::Char('v') => {
+                        app.filter.min_severity = app.filter.min_severity.next();
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('g') => {
+                        app.group_by = app.group_by.next();
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('m') => {
+                        app.open_files_menu();
+                    }
+                    KeyCode::Char('S') => {
+                        app.select_all_visible();
+                    }
+                    KeyCode::Char('i') => {
+                        app.invert_selection();
+                    }
+                    KeyCode::Char('X') => {
+                        app.clear_selection();
+                    }
+                    KeyCode::Char('T') => {
+                        app.filter.min_risk = app.filter.min_risk.next();
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('R') | KeyCode::F(5) => {
+                        // Draw the notice first; the scan blocks until it finishes
+                        app.set_message("Scanning...".to_string());
+                        terminal.draw(|f| ui(f, &app))?;
+                        app.refresh_entries();
+                        app.set_message(format!("Rescan complete: {} entries", app.all_entries.len()));
+                    }
+                    KeyCode::Char('D') => {
+                        app.toggle_delayed_start();
+                    }
+                    KeyCode::Char('u') => {
+                        app.filter.unsigned_only = match app.filter.unsigned_only {
+                            Some(true) => None,
+                            _ => Some(true),
+                        };
+                        app.apply_filter();
+                    }
+                    KeyCode::Char('o') => {
+                        app.open_entry_location();
+                    }
+                    KeyCode::Char('K') => {
+                        app.reveal_in_regedit();
+                    }
+                    KeyCode::Char('p') => {
+                        app.copy_entry(false);
+                    }
+                    KeyCode::Char('Y') => {
+                        app.copy_entry(true);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+fn handle_logs_key(app: &mut App, code: KeyCode) {
+    // Typing an entry-name filter
+    if let Some(input) = app.log_name_input.as_mut() {
+        match code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                let name = app.log_name_input.take().unwrap_or_default();
+                app.log_query.entry_name = if name.is_empty() { None } else { Some(name) };
+                app.reload_logs();
+            }
+            KeyCode::Esc => {
+                app.log_name_input = None;
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    match code {
+        KeyCode::Char('q') | KeyCode::Char('l') | KeyCode::Esc => {
+            app.view_mode = ViewMode::List;
+            app.clear_message();
+        }
+        KeyCode::Right | KeyCode::PageDown if app.log_page + 1 < app.log_page_count() => {
+            app.log_page += 1;
+        }
+        KeyCode::Left | KeyCode::PageUp => {
+            app.log_page = app.log_page.saturating_sub(1);
+        }
+        KeyCode::Char('a') => app.cycle_log_action(),
+        KeyCode::Char('t') => app.cycle_log_status(),
+        KeyCode::Char('r') => {
+            app.log_range = app.log_range.next();
+            app.reload_logs();
+        }
+        KeyCode::Char('/') => {
+            app.log_name_input = Some(String::new());
+        }
+        KeyCode::Char('c') => {
+            app.log_query = LogQuery::default();
+            app.reload_logs();
+        }
+        _ => {}
+    }
+}
+
+/// Keys of the source filter overlay; changes apply to the list immediately.
+fn handle_source_menu_key(app: &mut App, cursor: usize, code: KeyCode) {
+    let count = StartupSource::ALL.len();
+    match code {
+        KeyCode::Down | KeyCode::Char('j') => app.source_menu = Some((cursor + 1) % count),
+        KeyCode::Up | KeyCode::Char('k') => app.source_menu = Some(cursor.checked_sub(1).unwrap_or(count - 1)),
+        KeyCode::Char(' ') => {
+            app.filter.toggle_source(&StartupSource::ALL[cursor]);
+            app.apply_filter();
+        }
+        KeyCode::Char('a') => {
+            app.filter.source_filter = None;
+            app.apply_filter();
+        }
+        KeyCode::Enter | KeyCode::Esc | KeyCode::Char('F') | KeyCode::Char('q') => app.source_menu = None,
+        _ => {}
+    }
+}
+
+/// Keys of the export overlay: pick a format, optionally type a path, Enter exports.
+fn handle_export_menu_key(app: &mut App, code: KeyCode) {
+    let Some(menu) = app.export_menu.as_mut() else {
+        return;
+    };
+
+    // Typing an output path
+    if let Some(input) = menu.path_input.as_mut() {
+        match code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => menu.path_input = None,
+            KeyCode::Enter => {
+                let (format, _) = EXPORT_FORMATS[menu.cursor];
+                let path = input.trim().to_string();
+                app.export_menu = None;
+                app.export(format, (!path.is_empty()).then(|| PathBuf::from(path)));
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    let count = EXPORT_FORMATS.len();
+    match code {
+        KeyCode::Down | KeyCode::Char('j') => menu.cursor = (menu.cursor + 1) % count,
+        KeyCode::Up | KeyCode::Char('k') => menu.cursor = menu.cursor.checked_sub(1).unwrap_or(count - 1),
+        KeyCode::Char('p') => menu.path_input = Some(String::new()),
+        KeyCode::Enter => {
+            let (format, _) = EXPORT_FORMATS[menu.cursor];
+            app.export_menu = None;
+            app.export(format, None);
+        }
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('e') => app.export_menu = None,
+        _ => {}
+    }
+}
+
+/// Keys while typing a search. Every edit refilters right away with the
+/// cursor back on the first (best) match; Enter keeps the search, Esc drops it.
+fn handle_search_key(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Char(c) => {
+            app.search_term.push(c);
+            app.selected_index = 0;
+            app.apply_filter();
+        }
+        KeyCode::Backspace => {
+            app.search_term.pop();
+            app.selected_index = 0;
+            app.apply_filter();
+        }
+        KeyCode::Down => app.next(),
+        KeyCode::Up => app.previous(),
+        KeyCode::Enter => app.search_mode = false,
+        KeyCode::Esc => {
+            app.search_mode = false;
+            app.search_term.clear();
+            app.apply_filter();
+        }
+        _ => {}
+    }
+}
+
+/// Keys of the profile overlay: Enter compares the highlighted profile with
+/// the current entries, `n` saves the current state under a typed name.
+fn handle_profile_menu_key(app: &mut App, code: KeyCode) {
+    let Some(menu) = app.profile_menu.as_mut() else {
+        return;
+    };
+
+    if let Some(input) = menu.name_input.as_mut() {
+        match code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => menu.name_input = None,
+            KeyCode::Enter => {
+                let name = input.trim().to_string();
+                app.profile_menu = None;
+                app.save_profile(&name);
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    let count = menu.names.len();
+    match code {
+        KeyCode::Down | KeyCode::Char('j') if count > 0 => menu.cursor = (menu.cursor + 1) % count,
+        KeyCode::Up | KeyCode::Char('k') if count > 0 => {
+            menu.cursor = menu.cursor.checked_sub(1).unwrap_or(count - 1)
+        }
+        KeyCode::Char('n') => menu.name_input = Some(String::new()),
+        KeyCode::Enter => {
+            if let Some(name) = menu.names.get(menu.cursor).cloned() {
+                app.profile_menu = None;
+                app.request_profile(&name);
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('P') => app.profile_menu = None,
+        _ => {}
+    }
+}
+
+/// Keys while typing the entry count that confirms a large removal.
+fn handle_count_input_key(app: &mut App, code: KeyCode) {
+    let Some(input) = app.count_input.as_mut() else {
+        return;
+    };
+    match code {
+        KeyCode::Char(c) if c.is_ascii_digit() => input.push(c),
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        KeyCode::Enter => {
+            let expected = app.pending_action.as_ref().map(|(_, indices)| indices.len());
+            if input.parse::<usize>().ok() == expected {
+                app.count_input = None;
+                app.execute_pending_action();
+            } else {
+                input.clear();
+                app.set_message(strings::fill("prompt.count_mismatch", &[&expected.unwrap_or(0)]));
+            }
+        }
+        KeyCode::Esc => app.cancel_pending(),
+        _ => {}
+    }
+}
+
+fn handle_backups_key(app: &mut App, code: KeyCode) {
+    if app.pending_restore.is_some() {
+        match code {
+            KeyCode::Char('y') => app.restore_pending(),
+            KeyCode::Char('n') | KeyCode::Esc => {
+                app.pending_restore = None;
+                app.clear_message();
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    match code {
+        KeyCode::Char('q') | KeyCode::Char('b') | KeyCode::Esc => {
+            app.view_mode = ViewMode::List;
+            app.clear_message();
+        }
+        KeyCode::Down | KeyCode::Char('j') if !app.backups.is_empty() => {
+            app.backup_index = (app.backup_index + 1) % app.backups.len();
+        }
+        KeyCode::Up | KeyCode::Char('k') if !app.backups.is_empty() => {
+            app.backup_index = app.backup_index.checked_sub(1).unwrap_or(app.backups.len() - 1);
+        }
+        KeyCode::Enter => app.request_restore(),
+        _ => {}
+    }
+}
+
+fn handle_settings_key(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Char('q') | KeyCode::Char('c') | KeyCode::Esc => {
+            app.view_mode = ViewMode::List;
+            app.clear_message();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.settings_index = (app.settings_index + 1) % EDITABLE_SETTINGS.len();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.settings_index = app
+                .settings_index
+                .checked_sub(1)
+                .unwrap_or(EDITABLE_SETTINGS.len() - 1);
+        }
+        KeyCode::Enter | KeyCode::Char(' ') => app.cycle_setting(),
+        _ => {}
+    }
+}
+
+fn handle_whitelist_key(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Char('q') | KeyCode::Char('L') | KeyCode::Esc => {
+            app.view_mode = ViewMode::List;
+            app.clear_message();
+        }
+        KeyCode::Down | KeyCode::Char('j') if !app.whitelist_listings.is_empty() => {
+            app.whitelist_index = (app.whitelist_index + 1) % app.whitelist_listings.len();
+        }
+        KeyCode::Up | KeyCode::Char('k') if !app.whitelist_listings.is_empty() => {
+            app.whitelist_index = app
+                .whitelist_index
+                .checked_sub(1)
+                .unwrap_or(app.whitelist_listings.len() - 1);
+        }
+        KeyCode::Char('d') | KeyCode::Delete => app.remove_whitelist_listing(),
+        _ => {}
+    }
+}
+
+fn handle_warnings_key(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Char('q') | KeyCode::Char('!') | KeyCode::Esc => {
+            app.view_mode = ViewMode::List;
+            app.clear_message();
+        }
+        KeyCode::Down | KeyCode::Char('j') if app.warnings_index + 1 < app.warnings.len() => {
+            app.warnings_index += 1;
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.warnings_index = app.warnings_index.saturating_sub(1);
+        }
+        KeyCode::Char('c') => {
+            app.warnings.clear();
+            app.warnings_index = 0;
+        }
+        _ => {}
+    }
+}
+
+fn ui(f: &mut Frame, app: &App) {
+    match app.view_mode {
+        ViewMode::Stats => {
+            render_stats_view(f, app);
+        }
+        ViewMode::Help => {
+            render_help_view(f, app);
+        }
+        ViewMode::List => {
+            render_list_view(f, app);
+        }
+        ViewMode::Logs => {
+            render_logs_view(f, app);
+        }
+        ViewMode::Backups => {
+            render_backups_view(f, app);
+        }
+        ViewMode::Detail => {
+            render_detail_view(f, app);
+        }
+        ViewMode::Settings => {
+            render_settings_view(f, app);
+        }
+        ViewMode::Whitelist => {
+            render_whitelist_view(f, app);
+        }
+        ViewMode::Warnings => {
+            render_warnings_view(f, app);
+        }
+    }
+}
+
+fn render_list_view(f: &mut Frame, app: &App) {
+    let theme = theme::current();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Status bar
+            Constraint::Min(10),  // Main list
+            Constraint::Length(10), // Details
+        ])
+        .split(f.size());
 
     // Status bar
     let status_text = vec![
         Line::from(vec![
             Span::styled(
-                format!("Entries: {}/{} | ", app.filtered_entries.len(), app.all_entries.len()),
-                Style::default().fg(Color::Cyan),
+                strings::fill("status.entries", &[&app.filtered_entries.len(), &app.all_entries.len()]),
+                Style::default().fg(theme.accent),
             ),
             Span::styled(
-                format!("Selected: {} | ", app.selected_indices.len()),
-                Style::default().fg(Color::Yellow),
+                strings::fill("status.selected", &[&app.selected_ids.len()]),
+                Style::default().fg(theme.emphasis),
             ),
             Span::styled(
-                format!("Sort: {:?} | ", app.sort_by),
-                Style::default().fg(Color::Magenta),
+                strings::fill("status.sort", &[&format!("{:?}", app.sort_by)]),
+                Style::default().fg(theme.secondary),
             ),
-            if !app.search_term.is_empty() {
+            if app.group_by != GroupBy::None {
+                Span::styled(
+                    strings::fill("status.group", &[&format!("{:?}", app.group_by)]),
+                    Style::default().fg(theme.tertiary),
+                )
+            } else {
+                Span::raw("")
+            },
+            if let Some(flag) = app.filter.flag_filter {
+                Span::styled(
+                    strings::fill("status.flag", &[&flag]),
+                    Style::default().fg(theme.bad),
+                )
+            } else {
+                Span::raw("")
+            },
+            match &app.filter.source_filter {
+                Some(sources) => Span::styled(
+                    strings::fill(
+                        "status.sources",
+                        &[&if sources.is_empty() {
+                            strings::text("status.sources_none").to_string()
+                        } else {
+                            sources.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(", ")
+                        }],
+                    ),
+                    Style::default().fg(theme.accent),
+                ),
+                None => Span::raw(""),
+            },
+            if app.filter.enabled_only == Some(true) {
+                Span::styled(strings::text("status.enabled_only"), Style::default().fg(theme.good))
+            } else if app.filter.disabled_only == Some(true) {
+                Span::styled(strings::text("status.disabled_only"), Style::default().fg(theme.bad))
+            } else {
+                Span::raw("")
+            },
+            if app.filter.unsigned_only == Some(true) {
+                Span::styled(strings::text("status.unsigned_only"), Style::default().fg(theme.bad))
+            } else {
+                Span::raw("")
+            },
+            if app.filter.min_severity > Severity::None {
+                Span::styled(
+                    strings::fill("status.severity", &[&app.filter.min_severity]),
+                    Style::default().fg(theme.bad),
+                )
+            } else {
+                Span::raw("")
+            },
+            if app.filter.min_risk > RiskLevel::Low {
+                Span::styled(
+                    strings::fill("status.risk", &[&app.filter.min_risk]),
+                    Style::default().fg(theme.bad),
+                )
+            } else {
+                Span::raw("")
+            },
+            if app.search_mode || !app.search_term.is_empty() {
+                Span::styled(
+                    strings::fill(
+                        "status.search",
+                        &[
+                            &if app.search_term.starts_with("re:") {
+                                strings::text("status.search_regex")
+                            } else if app.filter.fuzzy {
+                                strings::text("status.search_fuzzy")
+                            } else {
+                                ""
+                            },
+                            &app.search_term,
+                            &if app.search_mode { "_" } else { "" },
+                        ],
+                    ),
+                    Style::default().fg(theme.good),
+                )
+            } else {
+                Span::raw("")
+            },
+            if app.config_manager.borrow().get().show_whitelisted {
+                Span::styled(strings::text("status.whitelisted_shown"), Style::default().fg(theme.muted))
+            } else {
+                Span::styled(strings::text("status.whitelisted_hidden"), Style::default().fg(theme.muted))
+            },
+            if app.verifier.pending() > 0 {
                 Span::styled(
-                    format!("Search: {} | ", app.search_term),
-                    Style::default().fg(Color::Green),
+                    strings::fill("status.checking", &[&app.verifier.pending()]),
+                    Style::default().fg(theme.muted),
                 )
             } else {
                 Span::raw("")
             },
-            Span::styled("Press 'h' for help", Style::default().fg(Color::DarkGray)),
+            match app.warnings.len() {
+                0 => Span::raw(""),
+                1 => Span::styled(strings::text("status.warning"), Style::default().fg(theme.emphasis)),
+                n => Span::styled(strings::fill("status.warnings", &[&n]), Style::default().fg(theme.emphasis)),
+            },
+            Span::styled(strings::text("status.help"), Style::default().fg(theme.muted)),
         ]),
     ];
 
-    let status = Paragraph::new(status_text)
-        .block(Block::default().borders(Borders::ALL).title("Status"));
-    f.render_widget(status, chunks[0]);
+    let status = Paragraph::new(status_text)
+        .block(Block::default().borders(Borders::ALL).title(strings::text("status.title")));
+    f.render_widget(status, chunks[0]);
+
+    // Main list
+    let group_labels: Vec<Option<String>> = app
+        .filtered_entries
+        .iter()
+        .map(|entry| crate::filter::group_label(entry, app.group_by))
+        .collect();
+    let mut group_sizes: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for label in group_labels.iter().flatten() {
+        *group_sizes.entry(label.to_lowercase()).or_insert(0) += 1;
+    }
+
+    let list_items: Vec<ListItem> = app
+        .filtered_entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            let is_selected = app.selected_ids.contains(&entry.id);
+            let is_current = idx == app.selected_index;
+
+            let selection_indicator = if is_selected {
+                Span::styled(theme.selected_symbol, Style::default().fg(theme.good))
+            } else {
+                Span::raw("  ")
+            };
+
+            let enabled_indicator = if entry.enabled {
+                Span::styled(theme.enabled_symbol, Style::default().fg(theme.good))
+            } else {
+                Span::styled(theme.disabled_symbol, Style::default().fg(theme.bad))
+            };
+
+            // Only visible while whitelisted entries are shown ('H')
+            let whitelisted_indicator = if app.whitelist_manager.is_whitelisted(entry) {
+                Span::styled("W ", Style::default().fg(theme.muted))
+            } else {
+                Span::raw("  ")
+            };
+
+            let access_indicator = if app.can_modify(entry) {
+                Span::styled("✎ ", Style::default().fg(theme.good))
+            } else {
+                Span::styled("⚷ ", Style::default().fg(theme.emphasis))
+            };
+
+            let risk = match entry.risk.as_ref().map(|r| r.level) {
+                Some(level) => Span::styled(format!("{:<7}", level.to_string()), Style::default().fg(risk_color(level))),
+                // Signature still being verified
+                None => Span::styled(format!("{:<7}", "…"), Style::default().fg(theme.muted)),
+            };
+
+            let source = Span::styled(
+                if entry.duplicates.is_empty() {
+                    format!("[{}] ", entry.source)
+                } else {
+                    format!("[{} +{}] ", entry.source, entry.duplicates.len())
+                },
+                Style::default().fg(theme.accent),
+            );
+
+            let name_style = if is_current {
+                Style::default()
+                    .fg(theme.emphasis)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let name = highlight_matches(&entry.name, app.search_filter.as_ref(), name_style);
+            let command = highlight_matches(&entry.command, app.search_filter.as_ref(), Style::default().fg(theme.dim));
+
+            let flags = Span::styled(
+                entry
+                    .flags
+                    .iter()
+                    .map(|flag| format!("{} ", flag.indicator()))
+                    .collect::<String>(),
+                // Reviewed flags stay visible but no longer stand out
+                Style::default().fg(if entry.acknowledged { theme.muted } else { theme.bad }),
+            );
+
+            let mut spans = vec![
+                selection_indicator,
+                enabled_indicator,
+                whitelisted_indicator,
+                access_indicator,
+                risk,
+                flags,
+                source,
+            ];
+            spans.extend(name);
+            spans.push(Span::styled(" → ", Style::default().fg(theme.dim)));
+            spans.extend(command);
+            let line = Line::from(spans);
+
+            // The first entry of each group carries the group header, so list
+            // indices keep matching filtered_entries.
+            let starts_group = match (&group_labels[idx], idx.checked_sub(1).map(|i| &group_labels[i])) {
+                (Some(label), Some(Some(previous))) => !label.eq_ignore_ascii_case(previous),
+                (Some(_), _) => true,
+                (None, _) => false,
+            };
+            if starts_group {
+                let label = group_labels[idx].clone().unwrap_or_default();
+                let header = Line::from(Span::styled(
+                    format!("▼ {} ({})", label, group_sizes.get(&label.to_lowercase()).copied().unwrap_or(0)),
+                    Style::default().fg(theme.tertiary).add_modifier(Modifier::BOLD),
+                ));
+                ListItem::new(vec![header, line])
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let page_size = chunks[1].height.saturating_sub(2) as usize;
+    app.list_page_size.set(page_size);
+    let position = if app.filtered_entries.is_empty() {
+        String::new()
+    } else {
+        strings::fill("list.position", &[&(app.selected_index + 1), &app.filtered_entries.len()])
+    };
+
+    let list = List::new(list_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(if app.dry_run() {
+                    "DeepBoot Pro - Startup Entries [DRY RUN: nothing is changed]"
+                } else {
+                    "DeepBoot Pro - Startup Entries"
+                })
+                .title_alignment(Alignment::Center)
+                .title(
+                    Title::from(position)
+                        .position(Position::Bottom)
+                        .alignment(Alignment::Right),
+                ),
+        )
+        .highlight_style(theme.selection)
+        .highlight_symbol(">> ");
+
+    f.render_stateful_widget(list, chunks[1], &mut app.list_state.clone());
+
+    // Scrollbar on the right border once the list no longer fits
+    if app.filtered_entries.len() > page_size {
+        let mut scrollbar_state = ScrollbarState::new(app.filtered_entries.len()).position(app.selected_index);
+        f.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .style(Style::default().fg(theme.muted)),
+            chunks[1].inner(&Margin { vertical: 1, horizontal: 0 }),
+            &mut scrollbar_state,
+        );
+    }
+
+    // Details panel
+    let details_text = if let Some(entry) = app.get_selected_entry() {
+        vec![
+            Line::from(Span::styled(
+                format!("Name: {}", entry.name),
+                Style::default().fg(theme.text),
+            )),
+            Line::from(Span::styled(
+                format!(
+                    "Source: {}{}",
+                    entry.source,
+                    entry
+                        .duplicates
+                        .iter()
+                        .map(|d| format!(
+                            ", {} ({})",
+                            d.source,
+                            if d.enabled { "enabled" } else { "disabled" }
+                        ))
+                        .collect::<String>()
+                ),
+                Style::default().fg(theme.accent),
+            )),
+            Line::from(Span::styled(
+                match entry.hosted_dll {
+                    Some(ref dll) => format!("Command: {} (hosts {})", entry.command, dll),
+                    None => format!("Command: {}", entry.command),
+                },
+                Style::default().fg(theme.dim),
+            )),
+            Line::from(Span::styled(
+                format!(
+                    "Status: {}{}",
+                    if entry.enabled { "Enabled" } else { "Disabled" },
+                    if entry.delayed_start { " (Automatic, delayed start)" } else { "" }
+                ),
+                Style::default().fg(if entry.enabled { theme.good } else { theme.bad }),
+            )),
+            Line::from(Span::styled(
+                format!(
+                    "Startup impact: {}",
+                    entry.impact.unwrap_or(crate::models::StartupImpact::Unknown)
+                ),
+                Style::default().fg(match entry.impact {
+                    Some(crate::models::StartupImpact::High) => theme.bad,
+                    Some(crate::models::StartupImpact::Medium) => theme.emphasis,
+                    _ => theme.dim,
+                }),
+            )),
+            match &entry.risk {
+                Some(risk) => Line::from(Span::styled(
+                    format!("Risk: {}", risk),
+                    Style::default().fg(risk_color(risk.level)),
+                )),
+                None => Line::from(Span::styled("Risk: computing…", Style::default().fg(theme.muted))),
+            },
+            match (&entry.user_sid, &entry.user_name) {
+                (Some(sid), Some(name)) => Line::from(Span::styled(
+                    format!("User: {} ({})", name, sid),
+                    Style::default().fg(theme.secondary),
+                )),
+                (Some(sid), None) => Line::from(Span::styled(
+                    format!("User: {}", sid),
+                    Style::default().fg(theme.secondary),
+                )),
+                _ => match &entry.task {
+                    Some(task) => Line::from(Span::styled(
+                        format!("Runs: {}", task),
+                        Style::default().fg(if task.highest_privileges { theme.emphasis } else { theme.dim }),
+                    )),
+                    None => Line::from(""),
+                },
+            },
+            if !app.can_modify(entry) {
+                Line::from(Span::styled(
+                    format!("Access: Needs admin ({} scope, run DeepBoot as administrator)", entry.scope),
+                    Style::default().fg(theme.emphasis),
+                ))
+            } else {
+                Line::from(Span::styled(
+                    format!("Access: Modifiable ({} scope)", entry.scope),
+                    Style::default().fg(theme.good),
+                ))
+            },
+            match crate::signature::peek_entry_status(entry) {
+                Some(status @ crate::signature::SignatureStatus::Signed(_)) => Line::from(Span::styled(
+                    format!("Signature: {}", status),
+                    Style::default().fg(theme.good),
+                )),
+                Some(status) => Line::from(Span::styled(
+                    format!("Signature: {}", status),
+                    Style::default().fg(theme.emphasis),
+                )),
+                None => Line::from(Span::styled("Signature: computing…", Style::default().fg(theme.muted))),
+            },
+            match crate::virustotal::entry_verdict(entry, app.config_manager.borrow().get()) {
+                Some(verdict) => Line::from(Span::styled(
+                    verdict.to_string(),
+                    Style::default().fg(verdict_color(&verdict)),
+                )),
+                None => Line::from(""),
+            },
+            if let Some(desc) = &entry.description {
+                Line::from(Span::styled(
+                    format!("Description: {}", desc),
+                    Style::default().fg(theme.muted),
+                ))
+            } else {
+                Line::from("")
+            },
+            if let Some(classification) = &entry.classification {
+                Line::from(Span::styled(
+                    format!("Classification: {}", classification),
+                    Style::default().fg(theme.muted),
+                ))
+            } else {
+                Line::from("")
+            },
+            if !entry.flags.is_empty() {
+                Line::from(Span::styled(
+                    format!(
+                        "Flags: {}{}",
+                        entry.flags.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(", "),
+                        if entry.acknowledged { " (acknowledged)" } else { "" }
+                    ),
+                    Style::default().fg(theme.bad),
+                ))
+            } else {
+                Line::from("")
+            },
+        ]
+    } else {
+        vec![Line::from("No entry selected")]
+    };
+
+    let details = Paragraph::new(details_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Details")
+                .title_alignment(Alignment::Center),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(details, chunks[2]);
+
+    render_message(f, app);
+
+    if let Some(cursor) = app.source_menu {
+        render_source_menu(f, app, cursor);
+    }
+    if let Some(menu) = &app.export_menu {
+        render_export_menu(f, app, menu);
+    }
+    if let Some(menu) = &app.profile_menu {
+        render_profile_menu(f, menu);
+    }
+}
+
+fn render_profile_menu(f: &mut Frame, menu: &ProfileMenu) {
+    let theme = theme::current();
+    let area = centered_rect(50, 40, f.size());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3)])
+        .split(area);
+
+    let items: Vec<ListItem> = if menu.names.is_empty() {
+        vec![ListItem::new(Span::styled("No saved profiles", Style::default().fg(theme.muted)))]
+    } else {
+        menu.names.iter().map(|name| ListItem::new(name.as_str())).collect()
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Profiles - Enter apply, n save current, Esc close")
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(theme.selection)
+        .highlight_symbol(">> ");
+
+    let name_line = match &menu.name_input {
+        Some(input) => Line::from(Span::styled(
+            format!("New profile name: {}_", input),
+            Style::default().fg(theme.emphasis),
+        )),
+        None => Line::from(Span::styled(
+            "Press n to save the current state as a profile",
+            Style::default().fg(theme.muted),
+        )),
+    };
+    let name = Paragraph::new(name_line).block(Block::default().borders(Borders::ALL));
+
+    let mut state = ListState::default();
+    if !menu.names.is_empty() {
+        state.select(Some(menu.cursor));
+    }
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, chunks[0], &mut state);
+    f.render_widget(name, chunks[1]);
+}
+
+fn risk_color(level: RiskLevel) -> Color {
+    let theme = theme::current();
+    match level {
+        RiskLevel::High => theme.bad,
+        RiskLevel::Medium => theme.emphasis,
+        RiskLevel::Low => theme.muted,
+    }
+}
+
+fn verdict_color(verdict: &crate::virustotal::Verdict) -> Color {
+    let theme = theme::current();
+    match verdict {
+        v if v.is_malicious() => theme.bad,
+        crate::virustotal::Verdict::Detections { .. } => theme.good,
+        _ => theme.muted,
+    }
+}
+
+fn render_message(f: &mut Frame, app: &App) {
+    let theme = theme::current();
+    if let Some(msg) = &app.message {
+        let msg_paragraph = Paragraph::new(msg.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Message")
+                    .title_alignment(Alignment::Center),
+            )
+            .style(Style::default().fg(theme.emphasis))
+            .wrap(Wrap { trim: true });
+
+        let area = centered_rect(60, 5, f.size());
+        f.render_widget(msg_paragraph, area);
+    }
+}
+
+/// Everything known about one entry, with the full command unwrapped.
+fn render_detail_view(f: &mut Frame, app: &App) {
+    let theme = theme::current();
+    let Some(entry) = app.get_selected_entry() else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(3)])
+        .split(f.size());
+
+    let field = |label: &str, value: String, color: Color| {
+        Line::from(vec![
+            Span::styled(format!("{:<14}", label), Style::default().fg(theme.muted)),
+            Span::styled(value, Style::default().fg(color)),
+        ])
+    };
+
+    let mut lines = vec![
+        field("Name", entry.name.clone(), theme.text),
+        field("Source", entry.source.to_string(), theme.accent),
+        field("Scope", entry.scope.to_string(), theme.accent),
+        field(
+            "Status",
+            if entry.enabled { "Enabled" } else { "Disabled" }.to_string(),
+            if entry.enabled { theme.good } else { theme.bad },
+        ),
+        Line::from(""),
+        field("Command", entry.command.clone(), theme.text),
+    ];
+    if let Some(raw) = &entry.raw_command {
+        lines.push(field("Stored as", raw.clone(), theme.dim));
+    }
+    if let Some(dll) = &entry.hosted_dll {
+        lines.push(field("Service DLL", dll.clone(), theme.dim));
+    }
+
+    // Source-specific facts
+    match entry.source {
+        StartupSource::Service => lines.push(field(
+            "Start type",
+            match (entry.enabled, entry.delayed_start) {
+                (false, _) => "Disabled",
+                (true, true) => "Automatic (delayed start)",
+                (true, false) => "Automatic",
+            }
+            .to_string(),
+            theme.dim,
+        )),
+        StartupSource::TaskScheduler => {
+            lines.push(field("Task", entry.name.clone(), theme.dim));
+            if let Some(task) = &entry.task {
+                lines.push(field("Triggers", task.triggers.join(", "), theme.dim));
+                if let Some(account) = &task.run_as {
+                    lines.push(field("Run as", account.clone(), theme.dim));
+                }
+                lines.push(if task.highest_privileges {
+                    field("Run level", "Highest privileges".to_string(), theme.emphasis)
+                } else {
+                    field("Run level", "Standard".to_string(), theme.dim)
+                });
+            }
+        }
+        _ => {}
+    }
+    if let Some(location) = app.details.as_ref().and_then(|d| d.location.as_ref()) {
+        lines.push(field("Location", location.clone(), theme.dim));
+    }
+    if let (Some(sid), name) = (&entry.user_sid, &entry.user_name) {
+        let user = match name {
+            Some(name) => format!("{} ({})", name, sid),
+            None => sid.clone(),
+        };
+        lines.push(field("User", user, theme.secondary));
+    }
+
+    lines.push(Line::from(""));
+    match app.details.as_ref().and_then(|d| d.file.as_ref()) {
+        Some(file) => {
+            lines.push(field("Executable", file.path.display().to_string(), theme.text));
+            if file.exists {
+                lines.push(field(
+                    "Modified",
+                    file.modified
+                        .map(|m| m.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    theme.dim,
+                ));
+                if let Some(size) = file.size {
+                    lines.push(field("Size", format!("{} bytes", size), theme.dim));
+                }
+                if let Some(company) = &file.company {
+                    lines.push(field("Company", company.clone(), theme.dim));
+                }
+                let sha256 = match (&file.sha256, app.hashes.contains_key(&file.path)) {
+                    (Some(hash), _) => hash.clone(),
+                    (None, true) => "n/a".to_string(),
+                    (None, false) => "computing…".to_string(),
+                };
+                lines.push(field("SHA-256", sha256, theme.dim));
+            } else {
+                lines.push(field("File", "Not found".to_string(), theme.bad));
+            }
+        }
+        None => lines.push(field("Executable", "Could not be resolved".to_string(), theme.emphasis)),
+    }
+    match crate::signature::peek_entry_status(entry) {
+        Some(signature) => {
+            let signature_color = if signature.is_signed() { theme.good } else { theme.emphasis };
+            lines.push(field("Signature", signature.to_string(), signature_color));
+        }
+        None => lines.push(field("Signature", "computing…".to_string(), theme.muted)),
+    }
+    if let Some(verdict) = crate::virustotal::entry_verdict(entry, app.config_manager.borrow().get()) {
+        let color = verdict_color(&verdict);
+        lines.push(field("Reputation", verdict.to_string(), color));
+    }
+    lines.push(field(
+        "Impact",
+        entry.impact.unwrap_or(crate::models::StartupImpact::Unknown).to_string(),
+        theme.dim,
+    ));
+    match &entry.risk {
+        Some(risk) => lines.push(field("Risk", risk.to_string(), risk_color(risk.level))),
+        None => lines.push(field("Risk", "computing…".to_string(), theme.muted)),
+    }
 
-    // Main list
-    let list_items: Vec<ListItem> = app
-        .filtered_entries
-        .iter()
-        .enumerate()
-        .map(|(idx, entry)| {
-            let is_selected = app.selected_indices.contains(&app.get_original_index(idx));
-            let is_current = idx == app.selected_index;
+    if let Some(desc) = &entry.description {
+        lines.push(field("Description", desc.clone(), theme.dim));
+    }
+    if let Some(classification) = &entry.classification {
+        lines.push(field("Classified", classification.clone(), theme.dim));
+    }
+    if !entry.flags.is_empty() {
+        lines.push(Line::from(""));
+        for flag in &entry.flags {
+            lines.push(field(
+                "Flag",
+                format!("{}: {}", flag, flag.explanation()),
+                theme.bad,
+            ));
+        }
+        if entry.acknowledged {
+            lines.push(field("", "(acknowledged)".to_string(), theme.muted));
+        }
+    }
+    for duplicate in &entry.duplicates {
+        lines.push(field(
+            "Also in",
+            format!(
+                "{} ({})",
+                duplicate.source,
+                if duplicate.enabled { "enabled" } else { "disabled" }
+            ),
+            theme.accent,
+        ));
+    }
 
-            let selection_indicator = if is_selected {
-                Span::styled("✓ ", Style::default().fg(Color::Green))
-            } else {
-                Span::raw("  ")
-            };
+    let details = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Entry: {}", entry.name))
+                .title_alignment(Alignment::Center),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(details, chunks[0]);
 
-            let enabled_indicator = if entry.enabled {
-                Span::styled("● ", Style::default().fg(Color::Green))
-            } else {
-                Span::styled("○ ", Style::default().fg(Color::Red))
-            };
+    let keys = Paragraph::new("d Disable  E Enable  r Remove  D Delayed start  w Whitelist  a Acknowledge  j/k Next/previous  Enter/Esc Back")
+        .style(Style::default().fg(theme.muted))
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(keys, chunks[1]);
 
-            let source = Span::styled(
-                format!("[{}] ", entry.source),
-                Style::default().fg(Color::Cyan),
-            );
+    render_message(f, app);
+}
 
-            let name = Span::styled(
-                entry.name.clone(),
-                if is_current {
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default()
-                },
-            );
+/// Splits `text` into spans, marking what the search matched.
+fn highlight_matches(text: &str, search: Option<&Filter>, style: Style) -> Vec<Span<'static>> {
+    let theme = theme::current();
+    let ranges = search.map(|filter| filter.match_ranges(text)).unwrap_or_default();
+    if ranges.is_empty() {
+        return vec![Span::styled(text.to_string(), style)];
+    }
 
-            let command = Span::styled(
-                format!(" → {}", entry.command),
-                Style::default().fg(Color::Gray),
-            );
+    let matched = style.patch(theme.search_match);
+    let mut spans = Vec::new();
+    let mut position = 0;
+    for (start, end) in ranges {
+        if start < position {
+            continue;
+        }
+        if start > position {
+            spans.push(Span::styled(text[position..start].to_string(), style));
+        }
+        // Fuzzy matches come one character at a time; join neighbours
+        match spans.last_mut() {
+            Some(last) if start == position && last.style == matched => {
+                last.content.to_mut().push_str(&text[start..end]);
+            }
+            _ => spans.push(Span::styled(text[start..end].to_string(), matched)),
+        }
+        position = end;
+    }
+    if position < text.len() {
+        spans.push(Span::styled(text[position..].to_string(), style));
+    }
+    spans
+}
 
-            ListItem::new(Line::from(vec![selection_indicator, enabled_indicator, source, name, command]))
+fn render_source_menu(f: &mut Frame, app: &App, cursor: usize) {
+    let theme = theme::current();
+    let items: Vec<ListItem> = StartupSource::ALL
+        .iter()
+        .map(|source| {
+            let checkbox = if app.filter.includes_source(source) { "[x]" } else { "[ ]" };
+            ListItem::new(format!("{} {}", checkbox, source))
         })
         .collect();
 
-    let list = List::new(list_items)
+    let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("DeepBoot Pro - Startup Entries")
+                .title("Sources - Space toggle, a all, Enter close")
                 .title_alignment(Alignment::Center),
         )
-        .highlight_style(
-            Style::default()
-                .bg(Color::DarkGray)
-                .add_modifier(Modifier::BOLD),
+        .highlight_style(theme.selection)
+        .highlight_symbol(">> ");
+
+    let mut state = ListState::default();
+    state.select(Some(cursor));
+    let area = centered_rect(50, 40, f.size());
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_export_menu(f: &mut Frame, app: &App, menu: &ExportMenu) {
+    let theme = theme::current();
+    let area = centered_rect(50, 40, f.size());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3)])
+        .split(area);
+
+    let items: Vec<ListItem> = EXPORT_FORMATS
+        .iter()
+        .map(|(_, label)| ListItem::new(*label))
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Export {} entries - Enter export, p path, Esc close", app.filtered_entries.len()))
+                .title_alignment(Alignment::Center),
         )
+        .highlight_style(theme.selection)
         .highlight_symbol(">> ");
 
-    f.render_stateful_widget(list, chunks[1], &mut app.list_state.clone());
+    let path_line = match &menu.path_input {
+        Some(input) => Line::from(Span::styled(
+            format!("Path: {}_", input),
+            Style::default().fg(theme.emphasis),
+        )),
+        None => Line::from(Span::styled(
+            "Path: timestamped file in the current directory",
+            Style::default().fg(theme.muted),
+        )),
+    };
+    let path = Paragraph::new(path_line).block(Block::default().borders(Borders::ALL));
 
-    // Details panel
-    let details_text = if let Some(entry) = app.get_selected_entry() {
-        vec![
-            Line::from(Span::styled(
-                format!("Name: {}", entry.name),
-                Style::default().fg(Color::White),
-            )),
-            Line::from(Span::styled(
-                format!("Source: {}", entry.source),
-                Style::default().fg(Color::Cyan),
-            )),
-            Line::from(Span::styled(
-                format!("Command: {}", entry.command),
-                Style::default().fg(Color::Gray),
-            )),
-            Line::from(Span::styled(
-                format!("Status: {}", if entry.enabled { "Enabled" } else { "Disabled" }),
-                Style::default().fg(if entry.enabled { Color::Green } else { Color::Red }),
-            )),
-            if let Some(desc) = &entry.description {
+    let mut state = ListState::default();
+    state.select(Some(menu.cursor));
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, chunks[0], &mut state);
+    f.render_widget(path, chunks[1]);
+}
+
+fn render_stats_view(f: &mut Frame, app: &App) {
+    let theme = theme::current();
+    let stats_text = app.stats.get_summary();
+    let rating_color = match app.stats.footprint.rating {
+        FootprintRating::Good => theme.good,
+        FootprintRating::Fair => theme.emphasis,
+        FootprintRating::Heavy => theme.bad,
+    };
+    let mut stats_lines: Vec<Line> = stats_text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            // The first line is the footprint headline
+            if i == 0 {
                 Line::from(Span::styled(
-                    format!("Description: {}", desc),
-                    Style::default().fg(Color::DarkGray),
+                    line.to_string(),
+                    Style::default().fg(rating_color).add_modifier(Modifier::BOLD),
                 ))
             } else {
-                Line::from("")
-            },
-        ]
-    } else {
-        vec![Line::from("No entry selected")]
-    };
+                Line::from(Span::raw(line))
+            }
+        })
+        .collect();
 
-    let details = Paragraph::new(details_text)
+    if !app.scan_summary.is_empty() {
+        stats_lines.push(Line::from(""));
+        stats_lines.push(Line::from("Last Scan:"));
+        for line in &app.scan_summary {
+            stats_lines.push(Line::from(format!("  {}", line)));
+        }
+    }
+
+    let stats_paragraph = Paragraph::new(stats_lines)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Details")
+                .title("Statistics")
                 .title_alignment(Alignment::Center),
         )
         .wrap(Wrap { trim: true });
 
-    f.render_widget(details, chunks[2]);
+    f.render_widget(stats_paragraph, f.size());
+}
+
+fn render_logs_view(f: &mut Frame, app: &App) {
+    let theme = theme::current();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Filters
+            Constraint::Min(5),    // Records
+            Constraint::Length(3), // Key hints
+        ])
+        .split(f.size());
+
+    let page_size = chunks[1].height.saturating_sub(2).max(1) as usize;
+    app.log_page_size.set(page_size);
+    let page = app.log_page.min(app.log_page_count() - 1);
+
+    let status = match app.log_query.success {
+        None => "All",
+        Some(true) => "Success",
+        Some(false) => "Failed",
+    };
+    let name_filter = match (&app.log_name_input, &app.log_query.entry_name) {
+        (Some(input), _) => format!("{}_", input),
+        (None, Some(name)) => name.clone(),
+        (None, None) => "-".to_string(),
+    };
+
+    let filters = Paragraph::new(Line::from(vec![
+        Span::styled(format!("Range: {} | ", app.log_range), Style::default().fg(theme.accent)),
+        Span::styled(
+            format!("Action: {} | ", app.log_query.action.as_deref().unwrap_or("All")),
+            Style::default().fg(theme.secondary),
+        ),
+        Span::styled(format!("Status: {} | ", status), Style::default().fg(theme.emphasis)),
+        Span::styled(format!("Entry: {} | ", name_filter), Style::default().fg(theme.good)),
+        Span::styled(
+            format!("Page {}/{} ({} records)", page + 1, app.log_page_count(), app.log_records.len()),
+            Style::default().fg(theme.muted),
+        ),
+    ]))
+    .block(Block::default().borders(Borders::ALL).title("Log Filters"));
+    f.render_widget(filters, chunks[0]);
+
+    let items: Vec<ListItem> = app
+        .log_records
+        .iter()
+        .skip(page * page_size)
+        .take(page_size)
+        .map(|record| {
+            let color = match record.success {
+                Some(true) => theme.good,
+                Some(false) => theme.bad,
+                None => theme.dim,
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("[{}] ", record.timestamp.format("%Y-%m-%d %H:%M:%S")),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::styled(record.message.clone(), Style::default().fg(color)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Action Log")
+            .title_alignment(Alignment::Center),
+    );
+    f.render_widget(list, chunks[1]);
+
+    let hints = Paragraph::new(
+        "←/→ Page | a Action | t Status | r Range | / Entry name | c Clear filters | Esc Back",
+    )
+    .style(Style::default().fg(theme.muted))
+    .block(Block::default().borders(Borders::ALL));
+    f.render_widget(hints, chunks[2]);
 
-    // Show message if any
     if let Some(msg) = &app.message {
         let msg_paragraph = Paragraph::new(msg.as_str())
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title("Message")
-                    .title_alignment(Alignment::Center),
-            )
-            .style(Style::default().fg(Color::Yellow))
+            .block(Block::default().borders(Borders::ALL).title("Message"))
+            .style(Style::default().fg(theme.emphasis))
             .wrap(Wrap { trim: true });
+        f.render_widget(msg_paragraph, centered_rect(60, 5, f.size()));
+    }
+}
 
-        let area = centered_rect(60, 5, f.size());
-        f.render_widget(msg_paragraph, area);
+fn render_backups_view(f: &mut Frame, app: &App) {
+    let theme = theme::current();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),    // Backups
+            Constraint::Length(3), // Key hints
+        ])
+        .split(f.size());
+
+    let items: Vec<ListItem> = app
+        .backups
+        .iter()
+        .map(|listing| {
+            let file_name = listing
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let line = match listing.entry_count {
+                Some(count) => Line::from(vec![
+                    Span::styled(format!("[{}] ", listing.timestamp), Style::default().fg(theme.accent)),
+                    Span::raw(format!("{} entries ", count)),
+                    Span::styled(format!("({}) ", listing.scope), Style::default().fg(theme.secondary)),
+                    Span::styled(file_name, Style::default().fg(theme.muted)),
+                ]),
+                None => Line::from(Span::styled(
+                    format!("{} (unreadable)", file_name),
+                    Style::default().fg(theme.bad),
+                )),
+            };
+            ListItem::new(line)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Backups ({})", app.backups.len()))
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(theme.selection)
+        .highlight_symbol(">> ");
+
+    let mut state = ListState::default();
+    if !app.backups.is_empty() {
+        state.select(Some(app.backup_index));
+    }
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let hints = Paragraph::new("↑/↓ Select | Enter Restore | Esc Back")
+        .style(Style::default().fg(theme.muted))
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(hints, chunks[1]);
+
+    if let Some(msg) = &app.message {
+        let msg_paragraph = Paragraph::new(msg.as_str())
+            .block(Block::default().borders(Borders::ALL).title("Message"))
+            .style(Style::default().fg(theme.emphasis))
+            .wrap(Wrap { trim: true });
+        f.render_widget(msg_paragraph, centered_rect(60, 5, f.size()));
     }
 }
 
-fn render_stats_view(f: &mut Frame, app: &App) {
-    let stats_text = app.stats.get_summary();
-    let stats_lines: Vec<Line> = stats_text
-        .lines()
-        .map(|line| Line::from(Span::raw(line)))
+fn render_settings_view(f: &mut Frame, app: &App) {
+    let theme = theme::current();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),    // Settings
+            Constraint::Length(3), // Key hints
+        ])
+        .split(f.size());
+
+    let config_manager = app.config_manager.borrow();
+    let config = config_manager.get();
+    let items: Vec<ListItem> = EDITABLE_SETTINGS
+        .iter()
+        .map(|key| {
+            let value = config.setting(key).unwrap_or_default();
+            let color = match value.as_str() {
+                "true" => theme.good,
+                "false" | "none" => theme.muted,
+                _ => theme.accent,
+            };
+            let locked = if config_manager.is_locked(key) {
+                Span::styled("  (locked)", Style::default().fg(theme.muted))
+            } else {
+                Span::raw("")
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<26}", key)),
+                Span::styled(value, Style::default().fg(color)),
+                locked,
+            ]))
+        })
         .collect();
 
-    let stats_paragraph = Paragraph::new(stats_lines)
+    let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Statistics")
+                .title(match config_manager.machine_path() {
+                    Some(machine) => format!(
+                        "Settings ({}, over {})",
+                        config_manager.path().display(),
+                        machine.display()
+                    ),
+                    None => format!("Settings ({})", config_manager.path().display()),
+                })
                 .title_alignment(Alignment::Center),
         )
-        .wrap(Wrap { trim: true });
+        .highlight_style(theme.selection)
+        .highlight_symbol(">> ");
 
-    f.render_widget(stats_paragraph, f.size());
+    let mut state = ListState::default();
+    state.select(Some(app.settings_index));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let hints = Paragraph::new("↑/↓ Select | Enter/Space Change | Esc Back | Other settings live in config.json")
+        .style(Style::default().fg(theme.muted))
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(hints, chunks[1]);
+
+    render_message(f, app);
+}
+
+fn render_whitelist_view(f: &mut Frame, app: &App) {
+    let theme = theme::current();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),    // Whitelist keys
+            Constraint::Length(3), // Key hints
+        ])
+        .split(f.size());
+
+    let items: Vec<ListItem> = app
+        .whitelist_listings
+        .iter()
+        .map(|listing| {
+            // A key matching nothing would silently hide whatever later
+            // reuses the name, so stale keys stand out
+            let matches = match listing.matches.as_slice() {
+                [] => Span::styled("matches nothing in this scan", Style::default().fg(theme.emphasis)),
+                [name] => Span::styled(format!("matches {}", name), Style::default().fg(theme.muted)),
+                [name, rest @ ..] => Span::styled(
+                    format!("matches {} and {} more", name, rest.len()),
+                    Style::default().fg(theme.muted),
+                ),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<11}", listing.category.label()), Style::default().fg(theme.accent)),
+                Span::raw(format!("{:<40} ", listing.key)),
+                matches,
+            ]))
+        })
+        .collect();
+
+    let stale = app.whitelist_listings.iter().filter(|l| l.matches.is_empty()).count();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Whitelist ({} keys, {} matching nothing) - {}",
+                    app.whitelist_listings.len(),
+                    stale,
+                    app.whitelist_manager.path().display()
+                ))
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(theme.selection)
+        .highlight_symbol(">> ");
+
+    let mut state = ListState::default();
+    if !app.whitelist_listings.is_empty() {
+        state.select(Some(app.whitelist_index));
+    }
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let hints = Paragraph::new("↑/↓ Select | d/Del Remove from whitelist | Esc Back")
+        .style(Style::default().fg(theme.muted))
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(hints, chunks[1]);
+
+    render_message(f, app);
+}
+
+fn render_warnings_view(f: &mut Frame, app: &App) {
+    let theme = theme::current();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),    // Warnings
+            Constraint::Length(3), // Key hints
+        ])
+        .split(f.size());
+
+    let items: Vec<ListItem> = if app.warnings.is_empty() {
+        vec![ListItem::new(Span::styled("No warnings.", Style::default().fg(theme.muted)))]
+    } else {
+        app.warnings
+            .iter()
+            .map(|warning| ListItem::new(Span::styled(warning.clone(), Style::default().fg(theme.emphasis))))
+            .collect()
+    };
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Warnings ({})", app.warnings.len()))
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(theme.selection)
+        .highlight_symbol(">> ");
+
+    let mut state = ListState::default();
+    if !app.warnings.is_empty() {
+        state.select(Some(app.warnings_index));
+    }
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let hints = Paragraph::new("↑/↓ Scroll | c Clear | Esc Back")
+        .style(Style::default().fg(theme.muted))
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(hints, chunks[1]);
 }
 
+/// Sections of the help screen: heading and key list message ids.
+const HELP_SECTIONS: &[(&str, &str)] = &[
+    ("help.navigation", "help.navigation.keys"),
+    ("help.actions", "help.actions.keys"),
+    ("help.views", "help.views.keys"),
+    ("help.search", "help.search.keys"),
+    ("help.sorting", "help.sorting.keys"),
+    ("help.other", "help.other.keys"),
+    ("help.legend", "help.legend.keys"),
+];
+
 fn render_help_view(f: &mut Frame, _app: &App) {
-    let help_text = vec![
-        Line::from(""),
-        Line::from(Span::styled("Navigation:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-        Line::from("  ↑/k - Move up"),
-        Line::from("  ↓/j - Move down"),
-        Line::from("  Space - Toggle selection"),
-        Line::from(""),
-        Line::from(Span::styled("Actions:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-        Line::from("  d   - Disable selected entry(ies)"),
-        Line::from("  r   - Remove selected entry(ies)"),
-        Line::from("  w   - Add to whitelist"),
-        Line::from("  e   - Export to JSON"),
-        Line::from(""),
-        Line::from(Span::styled("Views:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-        Line::from("  s   - Show statistics"),
-        Line::from("  h   - Toggle help"),
-        Line::from(""),
-        Line::from(Span::styled("Search & Filter:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-        Line::from("  /   - Start search"),
-        Line::from("  Esc - Cancel search"),
-        Line::from(""),
-        Line::from(Span::styled("Sorting:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-        Line::from("  1   - Sort by name"),
-        Line::from("  2   - Sort by source"),
-        Line::from("  3   - Sort by status"),
-        Line::from("  4   - Sort by command"),
-        Line::from(""),
-        Line::from(Span::styled("Other:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-        Line::from("  q   - Quit"),
-        Line::from(""),
-        Line::from(Span::styled("Legend:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-        Line::from("  ● - Enabled"),
-        Line::from("  ○ - Disabled"),
-        Line::from("  ✓ - Selected"),
-    ];
+    let theme = theme::current();
+    let heading = Style::default().fg(theme.emphasis).add_modifier(Modifier::BOLD);
+    let mut help_text = Vec::new();
+    for &(title, keys) in HELP_SECTIONS {
+        help_text.push(Line::from(""));
+        help_text.push(Line::from(Span::styled(strings::text(title), heading)));
+        if title == "help.legend" {
+            // The status markers depend on the theme
+            for (symbol, id) in [
+                (theme.enabled_symbol, "help.legend.enabled"),
+                (theme.disabled_symbol, "help.legend.disabled"),
+                (theme.selected_symbol, "help.legend.selected"),
+            ] {
+                help_text.push(Line::from(format!("  {} - {}", symbol.trim_end(), strings::text(id))));
+            }
+        }
+        help_text.extend(strings::text(keys).lines().map(Line::from));
+    }
 
     let help_paragraph = Paragraph::new(help_text)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Help - DeepBoot Pro")
+                .title(strings::text("help.title"))
                 .title_alignment(Alignment::Center),
         )
         .wrap(Wrap { trim: true });
@@ -637,6 +3609,54 @@ fn render_help_view(f: &mut Frame, _app: &App) {
     f.render_widget(help_paragraph, f.size());
 }
 
+fn render_scan_screen(f: &mut Frame, screen: &ScanScreen) {
+    let theme = theme::current();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Min(0)])
+        .split(f.size());
+
+    let title = Paragraph::new("DeepBoot Pro - Advanced Startup Manager")
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let status = if screen.scanning.is_empty() {
+        format!("Scan complete ({}/{})", screen.done, screen.total)
+    } else {
+        let names: Vec<String> = screen.scanning.iter().map(|s| s.to_string()).collect();
+        format!(
+            "{} Scanning {}... ({}/{})",
+            SPINNER[screen.frame % SPINNER.len()],
+            names.join(", "),
+            screen.done,
+            screen.total
+        )
+    };
+    let status = Paragraph::new(status)
+        .style(Style::default().fg(theme.emphasis))
+        .block(Block::default().borders(Borders::ALL).title("Progress"));
+    f.render_widget(status, chunks[1]);
+
+    // Keep the latest updates in view
+    let height = chunks[2].height.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = screen
+        .updates
+        .iter()
+        .skip(screen.updates.len().saturating_sub(height))
+        .map(|update| {
+            let color = match update {
+                ScanUpdate::Failed(..) => theme.bad,
+                _ => theme.text,
+            };
+            ListItem::new(update.to_string()).style(Style::default().fg(color))
+        })
+        .collect();
+    let log = List::new(items).block(Block::default().borders(Borders::ALL).title("Scan"));
+    f.render_widget(log, chunks[2]);
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
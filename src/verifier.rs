@@ -0,0 +1,96 @@
+use crate::file_info;
+use crate::signature;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Threads verifying signatures and hashing files at the same time.
+const WORKERS: usize = 4;
+
+/// Work for the pool, one file each.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Check {
+    Signature(PathBuf),
+    Hash(PathBuf),
+}
+
+/// A finished check. Signatures are read back through
+/// `signature::peek_entry_status`, since verifying fills its cache.
+#[derive(Debug, Clone)]
+pub enum CheckResult {
+    Signature(PathBuf),
+    Hash(PathBuf, Option<String>), // None when the file can't be read
+}
+
+/// Verifies signatures and hashes executables on a small thread pool, so
+/// the TUI keeps responding while hundreds of files are checked. The event
+/// loop collects results with `finished` between frames.
+pub struct Verifier {
+    jobs: Sender<Check>,
+    results: Receiver<CheckResult>,
+    requested: HashSet<Check>,
+    pending: usize,
+}
+
+impl Verifier {
+    pub fn start() -> Self {
+        let (jobs, queue) = mpsc::channel::<Check>();
+        let (done, results) = mpsc::channel();
+        let queue = Arc::new(Mutex::new(queue));
+
+        for _ in 0..WORKERS {
+            let queue = Arc::clone(&queue);
+            let done = done.clone();
+            thread::spawn(move || loop {
+                // The lock is only held while waiting for the next job; the
+                // channel closes when the Verifier is dropped
+                let Ok(check) = queue.lock().unwrap().recv() else {
+                    break;
+                };
+                let result = match check {
+                    Check::Signature(path) => {
+                        signature::cached_status(&path);
+                        CheckResult::Signature(path)
+                    }
+                    Check::Hash(path) => {
+                        let hash = file_info::sha256_file(&path)
+                            .map_err(|e| log::warn!("Failed to hash {:?}: {}", path, e))
+                            .ok();
+                        CheckResult::Hash(path, hash)
+                    }
+                };
+                if done.send(result).is_err() {
+                    break;
+                }
+            });
+        }
+
+        Self {
+            jobs,
+            results,
+            requested: HashSet::new(),
+            pending: 0,
+        }
+    }
+
+    /// Queues a check, unless it was requested before.
+    pub fn request(&mut self, check: Check) {
+        if self.requested.insert(check.clone()) && self.jobs.send(check).is_ok() {
+            self.pending += 1;
+        }
+    }
+
+    /// Results that came in since the last call, without waiting.
+    pub fn finished(&mut self) -> Vec<CheckResult> {
+        let results: Vec<CheckResult> = self.results.try_iter().collect();
+        self.pending -= results.len();
+        results
+    }
+
+    /// Checks queued or running.
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+}
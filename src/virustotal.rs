@@ -0,0 +1,220 @@
+use crate::config::AppConfig;
+use crate::file_info;
+use crate::models::StartupEntry;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::c_void;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use windows::{
+    core::{w, HSTRING, PCWSTR},
+    Win32::Networking::WinHttp::*,
+};
+
+const API_HOST: &str = "www.virustotal.com";
+/// Takes precedence over `virustotal_api_key` in config.json.
+const API_KEY_ENV: &str = "DEEPBOOT_VT_API_KEY";
+
+// The public API allows 4 lookups a minute
+const RATE_LIMIT: usize = 4;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+lazy_static::lazy_static! {
+    static ref RESULTS: Mutex<HashMap<PathBuf, Verdict>> = Mutex::new(HashMap::new());
+    static ref RECENT: Mutex<VecDeque<Instant>> = Mutex::new(VecDeque::new());
+}
+
+/// Outcome of a reputation lookup for one executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pending,
+    Detections { malicious: u64, engines: u64 },
+    NotFound, // The hash was never submitted
+    RateLimited,
+    Failed(String),
+}
+
+impl Verdict {
+    pub fn is_malicious(&self) -> bool {
+        matches!(self, Verdict::Detections { malicious, .. } if *malicious > 0)
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pending => write!(f, "VT: looking up..."),
+            Verdict::Detections { malicious, engines } => write!(f, "VT: {}/{}", malicious, engines),
+            Verdict::NotFound => write!(f, "VT: unknown file"),
+            Verdict::RateLimited => write!(f, "VT: rate limited, retrying shortly"),
+            Verdict::Failed(e) => write!(f, "VT: lookup failed ({})", e),
+        }
+    }
+}
+
+fn api_key(config: &AppConfig) -> Option<String> {
+    std::env::var(API_KEY_ENV)
+        .ok()
+        .or_else(|| config.virustotal_api_key.clone())
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
+
+/// Reputation of the entry's executable. Returns `None` when no API key is
+/// configured in `config` or there is no file to look up. The first call for a file
+/// starts the lookup on a background thread and reports `Pending`; later
+/// calls return the result once it is in.
+pub fn entry_verdict(entry: &StartupEntry, config: &AppConfig) -> Option<Verdict> {
+    let key = api_key(config)?;
+    let path = entry.target_path().filter(|path| path.is_file())?;
+
+    let mut results = RESULTS.lock().unwrap();
+    match results.get(&path) {
+        Some(Verdict::RateLimited) | None => {}
+        Some(verdict) => return Some(verdict.clone()),
+    }
+    if !take_slot() {
+        results.insert(path, Verdict::RateLimited);
+        return Some(Verdict::RateLimited);
+    }
+    results.insert(path.clone(), Verdict::Pending);
+    drop(results);
+
+    std::thread::spawn(move || {
+        let verdict = lookup_file(&path, &key);
+        RESULTS.lock().unwrap().insert(path, verdict);
+    });
+    Some(Verdict::Pending)
+}
+
+/// Claims one of the lookups allowed in the current window.
+fn take_slot() -> bool {
+    let mut recent = RECENT.lock().unwrap();
+    let now = Instant::now();
+    while recent.front().is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW) {
+        recent.pop_front();
+    }
+    if recent.len() >= RATE_LIMIT {
+        return false;
+    }
+    recent.push_back(now);
+    true
+}
+
+fn lookup_file(path: &Path, key: &str) -> Verdict {
+    let result = file_info::sha256_file(path).and_then(|hash| file_report(&hash, key));
+    result.unwrap_or_else(|e| {
+        log::warn!("VirusTotal lookup for {:?} failed: {}", path, e);
+        Verdict::Failed(e.to_string())
+    })
+}
+
+/// Fetches `/api/v3/files/<sha256>` and reads its last analysis stats.
+fn file_report(sha256: &str, key: &str) -> Result<Verdict> {
+    let (status, body) = https_get(&format!("/api/v3/files/{}", sha256), &format!("x-apikey: {}", key))?;
+    match status {
+        200 => {}
+        404 => return Ok(Verdict::NotFound),
+        429 => return Ok(Verdict::RateLimited),
+        401 | 403 => anyhow::bail!("API key rejected"),
+        _ => anyhow::bail!("HTTP {}", status),
+    }
+
+    let report: serde_json::Value = serde_json::from_slice(&body).context("Invalid response")?;
+    let stats = report["data"]["attributes"]["last_analysis_stats"]
+        .as_object()
+        .context("Response has no analysis stats")?;
+    Ok(Verdict::Detections {
+        malicious: stats.get("malicious").and_then(|v| v.as_u64()).unwrap_or(0),
+        engines: stats.values().filter_map(|v| v.as_u64()).sum(),
+    })
+}
+
+/// WinHTTP handle closed on drop.
+struct Handle(*mut c_void);
+
+impl Handle {
+    fn new(raw: *mut c_void, what: &str) -> Result<Self> {
+        if raw.is_null() {
+            return Err(windows::core::Error::from_win32()).context(format!("Failed to {}", what));
+        }
+        Ok(Self(raw))
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = WinHttpCloseHandle(self.0);
+        }
+    }
+}
+
+/// GET over HTTPS through the system proxy settings. Returns the status
+/// code and body.
+fn https_get(path: &str, header: &str) -> Result<(u32, Vec<u8>)> {
+    unsafe {
+        let session = Handle::new(
+            WinHttpOpen(
+                w!("DeepBoot"),
+                WINHTTP_ACCESS_TYPE_AUTOMATIC_PROXY,
+                PCWSTR::null(),
+                PCWSTR::null(),
+                0,
+            ),
+            "open HTTP session",
+        )?;
+        WinHttpSetTimeouts(session.0, 10_000, 10_000, 10_000, 15_000).context("Failed to set timeouts")?;
+        let connection = Handle::new(
+            WinHttpConnect(session.0, &HSTRING::from(API_HOST), INTERNET_DEFAULT_HTTPS_PORT, 0),
+            "connect",
+        )?;
+        let request = Handle::new(
+            WinHttpOpenRequest(
+                connection.0,
+                w!("GET"),
+                &HSTRING::from(path),
+                PCWSTR::null(),
+                PCWSTR::null(),
+                std::ptr::null(),
+                WINHTTP_FLAG_SECURE,
+            ),
+            "open request",
+        )?;
+
+        let headers: Vec<u16> = header.encode_utf16().collect();
+        WinHttpSendRequest(request.0, Some(&headers), None, 0, 0, 0).context("Failed to send request")?;
+        WinHttpReceiveResponse(request.0, std::ptr::null_mut()).context("No response")?;
+
+        let mut status = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let mut index = 0u32;
+        WinHttpQueryHeaders(
+            request.0,
+            WINHTTP_QUERY_STATUS_CODE | WINHTTP_QUERY_FLAG_NUMBER,
+            PCWSTR::null(),
+            Some(&mut status as *mut u32 as *mut c_void),
+            &mut size,
+            &mut index,
+        )
+        .context("Failed to read status code")?;
+
+        let mut body = Vec::new();
+        loop {
+            let mut available = 0u32;
+            WinHttpQueryDataAvailable(request.0, &mut available).context("Failed to read response")?;
+            if available == 0 {
+                break;
+            }
+            let start = body.len();
+            body.resize(start + available as usize, 0);
+            let mut read = 0u32;
+            WinHttpReadData(request.0, body[start..].as_mut_ptr() as *mut c_void, available, &mut read)
+                .context("Failed to read response")?;
+            body.truncate(start + read as usize);
+        }
+        Ok((status, body))
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::Local;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    // Collected since the last `take`, oldest first
+    static ref COLLECTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+}
+
+// Set while the TUI owns the terminal, where stderr output would garble it
+static TUI_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Passes records on to env_logger and keeps every warning and error, so
+/// scan problems printed to a console the TUI hides can be shown in it.
+struct CollectingLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for CollectingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() <= Level::Warn {
+            push(record.args().to_string());
+        }
+        if !TUI_ACTIVE.load(Ordering::Relaxed) && self.inner.matches(record) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Sets up logging like `env_logger` with `RUST_LOG` (default "info"),
+/// collecting warnings and errors even when the filter hides them.
+pub fn init() {
+    let inner = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let max_level = inner.filter().max(LevelFilter::Warn);
+    if log::set_boxed_logger(Box::new(CollectingLogger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Stops passing records on to env_logger while `active`, so nothing is
+/// written to stderr behind the TUI. Warnings are still collected.
+pub fn set_tui_active(active: bool) {
+    TUI_ACTIVE.store(active, Ordering::Relaxed);
+}
+
+/// Keeps a warning for the TUI, stamped with the time it happened.
+pub fn push(message: String) {
+    if let Ok(mut collected) = COLLECTED.lock() {
+        collected.push(format!("{} {}", Local::now().format("%H:%M:%S"), message));
+    }
+}
+
+/// Warnings collected since the last call.
+pub fn take() -> Vec<String> {
+    COLLECTED.lock().map(|mut collected| std::mem::take(&mut *collected)).unwrap_or_default()
+}
@@ -1,15 +1,88 @@
+use crate::backup::BackupManager;
+use crate::config::{load_settings, save_settings, VersionedSettings};
 use crate::models::StartupEntry;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Format version written to whitelist.json.
+const WHITELIST_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WhitelistConfig {
+    #[serde(default)]
+    pub version: u32, // 0 for files written before versioning
     pub safe_processes: HashSet<String>,
     pub safe_services: HashSet<String>,
     pub safe_tasks: HashSet<String>,
+    #[serde(flatten)]
+    pub unknown: serde_json::Map<String, serde_json::Value>, // Kept so saving doesn't drop them
+}
+
+impl VersionedSettings for WhitelistConfig {
+    const FILE: &'static str = "whitelist.json";
+    const VERSION: u32 = WHITELIST_VERSION;
+}
+
+/// The whitelist set an entry's key is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WhitelistCategory {
+    Process,
+    Service,
+    Task,
+}
+
+impl WhitelistCategory {
+    pub const ALL: &'static [WhitelistCategory] = &[
+        WhitelistCategory::Process,
+        WhitelistCategory::Service,
+        WhitelistCategory::Task,
+    ];
+
+    /// Plural label used in messages.
+    pub fn label(&self) -> &'static str {
+        match self {
+            WhitelistCategory::Process => "processes",
+            WhitelistCategory::Service => "services",
+            WhitelistCategory::Task => "tasks",
+        }
+    }
+
+    /// Name of the matching field in `whitelist.json`.
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            WhitelistCategory::Process => "safe_processes",
+            WhitelistCategory::Service => "safe_services",
+            WhitelistCategory::Task => "safe_tasks",
+        }
+    }
+}
+
+impl WhitelistConfig {
+    pub fn keys(&self, category: WhitelistCategory) -> &HashSet<String> {
+        match category {
+            WhitelistCategory::Process => &self.safe_processes,
+            WhitelistCategory::Service => &self.safe_services,
+            WhitelistCategory::Task => &self.safe_tasks,
+        }
+    }
+
+    pub fn keys_mut(&mut self, category: WhitelistCategory) -> &mut HashSet<String> {
+        match category {
+            WhitelistCategory::Process => &mut self.safe_processes,
+            WhitelistCategory::Service => &mut self.safe_services,
+            WhitelistCategory::Task => &mut self.safe_tasks,
+        }
+    }
+
+    /// Whether `name` is whitelisted in the category, by an exact key or a
+    /// wildcard pattern.
+    pub fn matches(&self, category: WhitelistCategory, name: &str) -> bool {
+        let keys = self.keys(category);
+        keys.contains(name) || keys.iter().any(|key| is_pattern(key) && key_matches(key, name))
+    }
 }
 
 impl Default for WhitelistConfig {
@@ -26,13 +99,86 @@ impl Default for WhitelistConfig {
         safe_processes.insert("conhost.exe".to_lowercase());
 
         Self {
+            version: WHITELIST_VERSION,
             safe_processes,
             safe_services: HashSet::new(),
             safe_tasks: HashSet::new(),
+            unknown: serde_json::Map::new(),
         }
     }
 }
 
+/// Whether a whitelist key is a wildcard pattern rather than a plain name.
+pub fn is_pattern(key: &str) -> bool {
+    key.contains(['*', '?'])
+}
+
+/// Whether a whitelist key matches a lowercase name. Plain keys must equal
+/// the name; in patterns `*` matches any run of characters and `?` a single
+/// one, ignoring case, so `nvidia*` or `*updater.exe` cover a whole family.
+pub fn key_matches(key: &str, name: &str) -> bool {
+    if !is_pattern(key) {
+        return key == name;
+    }
+    let pattern: Vec<char> = key.to_lowercase().chars().collect();
+    let text: Vec<char> = name.chars().collect();
+    wildcard_match(&pattern, &text)
+}
+
+fn wildcard_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it has consumed up
+    // to, so a mismatch can retry with the star taking one more character
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((after_star, consumed)) => {
+                    star = Some((after_star, consumed + 1));
+                    p = after_star;
+                    t = consumed + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Outcome of whitelisting several entries at once.
+#[derive(Debug, Clone, Default)]
+pub struct BulkWhitelistResult {
+    pub added: Vec<(WhitelistCategory, usize)>,
+    pub skipped: usize,
+}
+
+impl BulkWhitelistResult {
+    pub fn summary(&self) -> String {
+        let added: Vec<String> = self
+            .added
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(category, count)| format!("{} {}", count, category.label()))
+            .collect();
+
+        format!(
+            "Whitelisted {}; skipped {} already present or without a key",
+            if added.is_empty() { "nothing new".to_string() } else { added.join(", ") },
+            self.skipped
+        )
+    }
+}
+
 pub struct WhitelistManager {
     config: WhitelistConfig,
     config_path: PathBuf,
@@ -53,15 +199,10 @@ impl WhitelistManager {
         let config_path = config_dir.join("whitelist.json");
 
         let config = if config_path.exists() {
-            let content = fs::read_to_string(&config_path)
-                .context("Failed to read whitelist config")?;
-            serde_json::from_str(&content).unwrap_or_else(|_| WhitelistConfig::default())
+            load_settings(&config_path)?
         } else {
             let default_config = WhitelistConfig::default();
-            let content = serde_json::to_string_pretty(&default_config)
-                .context("Failed to serialize default config")?;
-            fs::write(&config_path, content)
-                .context("Failed to write default whitelist config")?;
+            save_settings(&config_path, &default_config)?;
             default_config
         };
 
@@ -71,75 +212,141 @@ impl WhitelistManager {
         })
     }
 
+    /// A whitelist that is never saved, for tests.
+    #[cfg(test)]
+    pub(crate) fn in_memory(config: WhitelistConfig) -> Self {
+        Self {
+            config,
+            config_path: PathBuf::new(),
+        }
+    }
+
     pub fn is_whitelisted(&self, entry: &StartupEntry) -> bool {
-        let name_lower = entry.name.to_lowercase();
-        let command_lower = entry.command.to_lowercase();
+        Self::matching_keys(entry)
+            .iter()
+            .any(|(category, name)| self.config.matches(*category, name))
+    }
+
+    /// Every key that would whitelist this entry: its process name, plus
+    /// its service or task name for those sources.
+    pub fn matching_keys(entry: &StartupEntry) -> Vec<(WhitelistCategory, String)> {
+        let mut keys = Vec::new();
 
         // Check process name in command
-        if let Some(process_name) = Self::extract_process_name(&command_lower) {
-            if self.config.safe_processes.contains(&process_name) {
-                return true;
-            }
+        if let Some(process_name) = Self::extract_process_name(&entry.command.to_lowercase()) {
+            keys.push((WhitelistCategory::Process, process_name));
         }
 
         // Check service name
-        if let Some(service_name) = entry.description.as_ref() {
-            if let Some(name) = service_name.strip_prefix("Service: ") {
-                if self.config.safe_services.contains(&name.to_lowercase()) {
-                    return true;
-                }
-            }
+        if let Some(name) = entry.description.as_ref().and_then(|d| d.strip_prefix("Service: ")) {
+            keys.push((WhitelistCategory::Service, name.to_lowercase()));
         }
 
         // Check task name
         if matches!(entry.source, crate::models::StartupSource::TaskScheduler) {
-            if self.config.safe_tasks.contains(&name_lower) {
-                return true;
-            }
+            keys.push((WhitelistCategory::Task, entry.name.to_lowercase()));
         }
 
-        false
+        keys
     }
 
-    pub fn add_to_whitelist(&mut self, entry: &StartupEntry) -> Result<()> {
+    /// Every whitelist key, grouped by category and sorted within each.
+    pub fn entries(&self) -> Vec<(WhitelistCategory, String)> {
+        WhitelistCategory::ALL
+            .iter()
+            .flat_map(|&category| {
+                let mut keys: Vec<&String> = self.config.keys(category).iter().collect();
+                keys.sort();
+                keys.into_iter().map(move |key| (category, key.clone()))
+            })
+            .collect()
+    }
+
+    /// Removes a single key, whatever entries it matches.
+    pub fn remove_key(&mut self, category: WhitelistCategory, key: &str) -> Result<()> {
+        self.config.keys_mut(category).remove(key);
+        self.save()
+    }
+
+    /// The category and key `add_to_whitelist` stores for this entry.
+    pub fn key_for(entry: &StartupEntry) -> Option<(WhitelistCategory, String)> {
         match entry.source {
-            crate::models::StartupSource::Service => {
-                if let Some(service_name) = entry.description.as_ref() {
-                    if let Some(name) = service_name.strip_prefix("Service: ") {
-                        self.config.safe_services.insert(name.to_lowercase());
-                    }
-                }
-            }
+            crate::models::StartupSource::Service => entry
+                .description
+                .as_ref()
+                .and_then(|d| d.strip_prefix("Service: "))
+                .map(|name| (WhitelistCategory::Service, name.to_lowercase())),
             crate::models::StartupSource::TaskScheduler => {
-                self.config.safe_tasks.insert(entry.name.to_lowercase());
-            }
-            _ => {
-                if let Some(process_name) = Self::extract_process_name(&entry.command.to_lowercase()) {
-                    self.config.safe_processes.insert(process_name);
-                }
+                Some((WhitelistCategory::Task, entry.name.to_lowercase()))
             }
+            _ => Self::extract_process_name(&entry.command.to_lowercase())
+                .map(|name| (WhitelistCategory::Process, name)),
+        }
+    }
+
+    pub fn add_to_whitelist(&mut self, entry: &StartupEntry) -> Result<()> {
+        if let Some((category, key)) = Self::key_for(entry) {
+            self.config.keys_mut(category).insert(key);
         }
 
         self.save()
     }
 
-    pub fn remove_from_whitelist(&mut self, entry: &StartupEntry) -> Result<()> {
-        match entry.source {
-            crate::models::StartupSource::Service => {
-                if let Some(service_name) = entry.description.as_ref() {
-                    if let Some(name) = service_name.strip_prefix("Service: ") {
-                        self.config.safe_services.remove(&name.to_lowercase());
-                    }
+    /// Whitelists every entry in one save, skipping keys that are already present.
+    pub fn add_all(&mut self, entries: &[StartupEntry]) -> Result<BulkWhitelistResult> {
+        let mut result = BulkWhitelistResult {
+            added: WhitelistCategory::ALL.iter().map(|&c| (c, 0)).collect(),
+            skipped: 0,
+        };
+
+        for entry in entries {
+            let Some((category, key)) = Self::key_for(entry) else {
+                result.skipped += 1;
+                continue;
+            };
+
+            if self.config.keys_mut(category).insert(key) {
+                if let Some((_, count)) = result.added.iter_mut().find(|(c, _)| *c == category) {
+                    *count += 1;
                 }
+            } else {
+                result.skipped += 1;
             }
-            crate::models::StartupSource::TaskScheduler => {
-                self.config.safe_tasks.remove(&entry.name.to_lowercase());
-            }
-            _ => {
-                if let Some(process_name) = Self::extract_process_name(&entry.command.to_lowercase()) {
-                    self.config.safe_processes.remove(&process_name);
+        }
+
+        self.save()?;
+        Ok(result)
+    }
+
+    /// Merges another whitelist file, such as a baseline shared across a
+    /// team, into this one: every key it has that this one lacks is added.
+    pub fn import(&mut self, path: &Path) -> Result<BulkWhitelistResult> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read whitelist {:?}", path))?;
+        let other: WhitelistConfig = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse whitelist {:?}", path))?;
+
+        let mut result = BulkWhitelistResult::default();
+        for &category in WhitelistCategory::ALL {
+            let mut added = 0;
+            for key in other.keys(category) {
+                // Keys only match lowercase, and patterns ignore case anyway
+                if self.config.keys_mut(category).insert(key.trim().to_lowercase()) {
+                    added += 1;
+                } else {
+                    result.skipped += 1;
                 }
             }
+            result.added.push((category, added));
+        }
+
+        self.save()?;
+        Ok(result)
+    }
+
+    pub fn remove_from_whitelist(&mut self, entry: &StartupEntry) -> Result<()> {
+        if let Some((category, key)) = Self::key_for(entry) {
+            self.config.keys_mut(category).remove(&key);
         }
 
         self.save()
@@ -152,26 +359,33 @@ impl WhitelistManager {
             .collect()
     }
 
+    pub fn path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// Backs up the current whitelist file and overwrites it with the defaults.
+    /// Returns the backup location, if there was a file to back up.
+    pub fn reset(&mut self, backup_manager: &BackupManager) -> Result<Option<PathBuf>> {
+        let backup_path = if self.config_path.exists() {
+            Some(backup_manager.backup_file(&self.config_path)?)
+        } else {
+            None
+        };
+
+        self.config = WhitelistConfig::default();
+        self.save()?;
+        Ok(backup_path)
+    }
+
     fn save(&self) -> Result<()> {
-        let content = serde_json::to_string_pretty(&self.config)
-            .context("Failed to serialize whitelist config")?;
-        fs::write(&self.config_path, content)
-            .context("Failed to save whitelist config")?;
-        Ok(())
-    }
-
-    fn extract_process_name(command: &str) -> Option<String> {
-        // Extract executable name from command
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        if let Some(first_part) = parts.first() {
-            // Remove quotes if present
-            let cleaned = first_part.trim_matches('"');
-            // Extract just the filename
-            if let Some(filename) = std::path::Path::new(cleaned).file_name() {
-                return Some(filename.to_string_lossy().to_lowercase());
-            }
-        }
-        None
+        save_settings(&self.config_path, &self.config)
+    }
+
+    pub fn extract_process_name(command: &str) -> Option<String> {
+        // Handles quoted paths, paths with spaces and trailing arguments
+        crate::paths::resolve_executable(command)?
+            .file_name()
+            .map(|filename| filename.to_string_lossy().to_lowercase())
     }
 
     pub fn get_config(&self) -> &WhitelistConfig {
@@ -179,3 +393,57 @@ impl WhitelistManager {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leading_star_matches_any_prefix() {
+        assert!(key_matches("*updater.exe", "googleupdater.exe"));
+        assert!(key_matches("*updater.exe", "updater.exe"));
+        assert!(!key_matches("*updater.exe", "updater.exe.bak"));
+        assert!(!key_matches("*updater.exe", "updater.com"));
+    }
+
+    #[test]
+    fn trailing_star_matches_any_suffix() {
+        assert!(key_matches("onedrive*", "onedrive.exe"));
+        assert!(key_matches("onedrive*", "onedrive"));
+        assert!(!key_matches("onedrive*", "microsoftonedrive.exe"));
+    }
+
+    #[test]
+    fn patterns_ignore_the_key_case() {
+        assert!(key_matches("OneDrive*", "onedrivestandaloneupdater.exe"));
+    }
+
+    #[test]
+    fn several_stars_match_in_order() {
+        assert!(key_matches("*nvidia*helper*", "nvidia share helper.exe"));
+        assert!(key_matches("a*b*c", "abc"));
+        assert!(key_matches("a*b*c", "aXbYbZc"));
+        assert!(!key_matches("a*b*c", "acb"));
+        assert!(key_matches("**", ""));
+    }
+
+    #[test]
+    fn question_mark_matches_exactly_one_character() {
+        assert!(key_matches("app?.exe", "app2.exe"));
+        assert!(!key_matches("app?.exe", "app.exe"));
+        assert!(!key_matches("app?.exe", "app12.exe"));
+        assert!(key_matches("*?.exe", "a.exe"));
+        assert!(!key_matches("*?.exe", ".exe"));
+    }
+
+    #[test]
+    fn plain_keys_must_match_exactly() {
+        assert!(!is_pattern("updater.exe"));
+        assert!(key_matches("updater.exe", "updater.exe"));
+        assert!(!key_matches("updater.exe", "googleupdater.exe"));
+        assert!(!key_matches("updater.exe", "updater.exe2"));
+        // Only * and ? are special; other glob characters are literal
+        assert!(key_matches("[setup].exe", "[setup].exe"));
+        assert!(!key_matches("[setup].exe", "s.exe"));
+    }
+}
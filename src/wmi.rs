@@ -0,0 +1,175 @@
+use crate::models::{EntryScope, StartupEntry, StartupSource};
+use crate::startup_folder::STARTUP_EXTENSIONS;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::process::Command;
+
+/// Start of the description of entries only WMI reported.
+const WMI_ONLY_DESCRIPTION: &str = "Reported only by WMI";
+
+/// One row of WMI's `Win32_StartupCommand` class.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StartupCommand {
+    name: Option<String>,
+    command: Option<String>,
+    location: Option<String>, // "Startup", "Common Startup" or a registry key such as "HKLM\SOFTWARE\...\Run"
+    user: Option<String>, // Account the command runs for, "DOMAIN\user" or "Public"
+}
+
+/// Cross-checks the dedicated scanners against `Win32_StartupCommand`,
+/// which lists the Run keys and Startup folders in a single query.
+pub struct WmiScanner;
+
+impl WmiScanner {
+    /// The startup commands WMI reports, as the entries the dedicated
+    /// scanners would have produced for them. Other accounts' entries are
+    /// only included with `scan_other_users`; locations DeepBoot has no
+    /// scanner for are logged and skipped.
+    pub fn scan(scan_other_users: bool) -> Result<Vec<StartupEntry>> {
+        let current_sid = crate::privileges::current_user_sid();
+        let mut entries = Vec::new();
+        for command in Self::query()? {
+            let (Some(name), Some(location)) = (command.name, command.location) else {
+                continue;
+            };
+            // Only the current account's Startup folder is scanned, or could be changed
+            if location.eq_ignore_ascii_case("startup") && !Self::is_current_user(command.user.as_deref()) {
+                continue;
+            }
+            let Some(entry) = Self::to_entry(name, command.command.unwrap_or_default(), &location, current_sid.as_deref())
+            else {
+                log::warn!("WMI reports a startup command at {}, which DeepBoot doesn't scan", location);
+                continue;
+            };
+            // Other accounts stay out of the list unless they are scanned anyway
+            if entry.scope == EntryScope::OtherUser && !scan_other_users {
+                continue;
+            }
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    /// Adds the entries WMI reported that the dedicated scanners missed to
+    /// `entries`, logging a warning for each.
+    pub fn cross_check(entries: &mut Vec<StartupEntry>, reported: Vec<StartupEntry>) {
+        let mut known: HashSet<String> = entries.iter().map(Self::match_key).collect();
+        for entry in reported {
+            if known.insert(Self::match_key(&entry)) {
+                log::warn!(
+                    "WMI reports '{}' in {} ({}), which the scan missed",
+                    entry.name,
+                    entry.source,
+                    entry.scope
+                );
+                entries.push(entry);
+            }
+        }
+    }
+
+    fn query() -> Result<Vec<StartupCommand>> {
+        let ps_command = "ConvertTo-Json -Compress -InputObject @(\
+             Get-CimInstance -ClassName Win32_StartupCommand -ErrorAction Stop | \
+             Select-Object Name, Command, Location, User)";
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", ps_command])
+            .output()
+            .context("Failed to run PowerShell")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Win32_StartupCommand query failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        serde_json::from_slice(&output.stdout).context("Failed to parse Win32_StartupCommand results")
+    }
+
+    /// Builds the entry a dedicated scanner would have produced for a WMI
+    /// row, or `None` for a location none of them covers.
+    fn to_entry(name: String, command: String, location: &str, current_sid: Option<&str>) -> Option<StartupEntry> {
+        let lower = location.to_lowercase();
+        let (source, scope, sid) = match lower.as_str() {
+            "startup" => (StartupSource::StartupFolder, EntryScope::User, None),
+            "common startup" => (StartupSource::StartupFolder, EntryScope::Machine, None),
+            _ => {
+                let (hive, key) = lower.split_once('\\')?;
+                let source = if key.ends_with("\\wow6432node\\microsoft\\windows\\currentversion\\run") {
+                    StartupSource::RegistryWow6432Node
+                } else if key.contains("\\wow6432node\\") {
+                    return None;
+                } else if key.ends_with("\\runonce") {
+                    StartupSource::RegistryRunOnce
+                } else if key.ends_with("\\runservices") {
+                    StartupSource::RegistryRunServices
+                } else if key.ends_with("\\run") {
+                    StartupSource::RegistryRun
+                } else {
+                    return None;
+                };
+                match hive {
+                    "hklm" | "hkey_local_machine" => (source, EntryScope::Machine, None),
+                    "hkcu" | "hkey_current_user" => (source, EntryScope::User, None),
+                    "hku" | "hkey_users" => {
+                        // Keep the SID's original case, as the registry scanner does
+                        let sid = location.split('\\').nth(1)?.to_string();
+                        if current_sid.is_some_and(|current| current.eq_ignore_ascii_case(&sid)) {
+                            (source, EntryScope::User, None)
+                        } else {
+                            (source, EntryScope::OtherUser, Some(sid))
+                        }
+                    }
+                    _ => return None,
+                }
+            }
+        };
+
+        let entry = StartupEntry::new(name, command, source, true)
+            .with_scope(scope)
+            .with_description(format!("{} (Win32_StartupCommand) at {}", WMI_ONLY_DESCRIPTION, location));
+        Some(match sid {
+            Some(sid) => {
+                let account = crate::privileges::account_name(&sid);
+                entry.with_user(sid, account)
+            }
+            None => entry,
+        })
+    }
+
+    /// Whether `cross_check` added this entry, as none of the dedicated
+    /// scanners found it.
+    pub fn reported_only_by_wmi(entry: &StartupEntry) -> bool {
+        entry.description.as_deref().is_some_and(|d| d.starts_with(WMI_ONLY_DESCRIPTION))
+    }
+
+    fn is_current_user(user: Option<&str>) -> bool {
+        let Ok(current) = std::env::var("USERNAME") else {
+            return true;
+        };
+        user.and_then(|user| user.rsplit('\\').next())
+            .is_some_and(|name| name.eq_ignore_ascii_case(&current))
+    }
+
+    /// What makes a WMI row and a scanned entry the same startup command.
+    /// WMI names Startup folder items without their file extension.
+    fn match_key(entry: &StartupEntry) -> String {
+        let name = match entry.name.rsplit_once('.') {
+            Some((stem, extension))
+                if entry.source == StartupSource::StartupFolder
+                    && STARTUP_EXTENSIONS.contains(&extension.to_lowercase().as_str()) =>
+            {
+                stem
+            }
+            _ => entry.name.as_str(),
+        };
+        format!(
+            "{}|{:?}|{}|{}",
+            entry.source,
+            entry.scope,
+            entry.user_sid.as_deref().unwrap_or_default(),
+            name
+        )
+        .to_lowercase()
+    }
+}